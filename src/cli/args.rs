@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use clap::builder::RangedU64ValueParser;
+use clap::{Args, Subcommand};
+
+use crate::palette::extractor::MAX_COLORS;
+use crate::types::config::ColorSpace;
+
+/**
+ * The subcommands. Without one, colorbuddy extracts palettes from the images it's given.
+ */
+#[derive(Clone, Debug, Subcommand)]
+pub enum Command {
+    /// Remap an image to a palette previously saved as JSON or as a GIMP palette.
+    Recolor(RecolorArgs),
+    /// Time every quantisation method on an image, and show the palette each extracts.
+    Bench(BenchArgs),
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct RecolorArgs {
+    #[arg(help = "The image to recolor.")]
+    pub image: PathBuf,
+
+    #[arg(
+        short = 'p',
+        long = "palette",
+        help = "A palette saved by colorbuddy with --output-type json, json-file or gpl. GIMP palettes are recognised by their .gpl extension."
+    )]
+    pub palette: PathBuf,
+
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Where to save the recolored image. Defaults to <image>_recolored.<ext> alongside the input."
+    )]
+    pub output: Option<PathBuf>,
+
+    #[arg(
+        long = "dither",
+        help = "Use Floyd–Steinberg dithering rather than mapping each pixel to its nearest color."
+    )]
+    pub dither: bool,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct BenchArgs {
+    #[arg(help = "The image to extract palettes from.")]
+    pub image: PathBuf,
+
+    #[arg(
+        short = 'n',
+        long = "number-of-colors",
+        help = "The number of colors each method extracts.",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..=MAX_COLORS as u64),
+        default_value = "8"
+    )]
+    pub number_of_colors: usize,
+
+    #[arg(
+        long = "color-space",
+        help = "The color space K-Means measures color differences in.",
+        default_value_t = ColorSpace::Rgb
+    )]
+    pub color_space: ColorSpace,
+
+    #[arg(long = "json", help = "Print the results as JSON rather than a table.")]
+    pub json: bool,
+}
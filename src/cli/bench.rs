@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use image::RgbImage;
+use serde_json::json;
+
+use crate::palette::extractor::extract_palette;
+use crate::types::config::{ColorSpace, QuantisationMethod};
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * How one quantisation method fared on an image in the `bench` subcommand.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchResult {
+    /// The quantisation method.
+    pub method: QuantisationMethod,
+    /// How long extracting the palette took.
+    pub duration: Duration,
+    /// The palette extracted, as hex codes.
+    pub palette: Vec<String>,
+}
+
+/**
+ * Extracts a palette from the image with every quantisation method in turn (but Auto, which just
+ * runs one of the others), timing each.
+ *
+ * [&RgbImage] The image to extract palettes from.
+ * [usize] The number of colors to extract.
+ * [ColorSpace] The color space K-Means clusters in.
+ */
+pub fn bench(
+    input_image: &RgbImage,
+    number_of_colors: usize,
+    color_space: ColorSpace,
+) -> Vec<BenchResult> {
+    QuantisationMethod::value_variants()
+        .iter()
+        // Auto just runs one of the others
+        .filter(|&&method| method != QuantisationMethod::Auto)
+        .map(|&method| {
+            let start = Instant::now();
+            let palette = extract_palette(input_image, number_of_colors, method, color_space);
+            let duration = start.elapsed();
+            BenchResult {
+                method,
+                duration,
+                palette: palette.iter().map(|c| rgb_to_hex(c.r, c.g, c.b)).collect(),
+            }
+        })
+        .collect()
+}
+
+/**
+ * Formats the bench results as a table: one line per method, with how long it took in
+ * milliseconds and the palette it extracted.
+ *
+ * [&[BenchResult]] The bench results.
+ */
+pub fn format_bench_table(results: &[BenchResult]) -> String {
+    let methods: Vec<String> = results.iter().map(|r| r.method.to_string()).collect();
+    let method_width = methods
+        .iter()
+        .map(|m| m.len())
+        .chain(std::iter::once("Method".len()))
+        .max()
+        .unwrap();
+
+    let mut table = format!(
+        "{:<method_width$}  {:>10}  Palette\n",
+        "Method", "Time (ms)"
+    );
+    for (result, method) in results.iter().zip(&methods) {
+        table.push_str(&format!(
+            "{:<method_width$}  {:>10.2}  {}\n",
+            method,
+            result.duration.as_secs_f64() * 1000.0,
+            result.palette.join(" ")
+        ));
+    }
+
+    table
+}
+
+/**
+ * Formats the bench results as a JSON array, with each method's time in milliseconds.
+ *
+ * [&[BenchResult]] The bench results.
+ */
+pub fn format_bench_json(results: &[BenchResult]) -> String {
+    let results: Vec<serde_json::Value> = results
+        .iter()
+        .map(|r| {
+            json!({
+                "method": r.method.to_string(),
+                "milliseconds": r.duration.as_secs_f64() * 1000.0,
+                "palette": r.palette,
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&results).expect("bench results are always serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_covers_every_method() {
+        let image = RgbImage::from_fn(16, 16, |x, y| image::Rgb([x as u8 * 16, y as u8 * 16, 128]));
+        let results = bench(&image, 4, ColorSpace::Rgb);
+
+        // Test case 1: There's a result for each method but Auto, in order, with a palette
+        let methods: Vec<QuantisationMethod> = results.iter().map(|r| r.method).collect();
+        assert_eq!(
+            methods,
+            QuantisationMethod::value_variants()
+                .iter()
+                .copied()
+                .filter(|&method| method != QuantisationMethod::Auto)
+                .collect::<Vec<_>>()
+        );
+        for result in &results {
+            assert!(result.duration >= Duration::ZERO);
+            assert!(!result.palette.is_empty());
+        }
+
+        // Test case 2: The table has a header and a line per method
+        let table = format_bench_table(&results);
+        assert_eq!(table.lines().count(), 1 + results.len());
+        assert!(table.lines().nth(2).unwrap().starts_with("median-cut"));
+
+        // Test case 3: The JSON has an entry per method
+        let json: serde_json::Value = serde_json::from_str(&format_bench_json(&results)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), results.len());
+        assert_eq!(json[0]["method"], "k-means");
+        assert!(json[0]["milliseconds"].as_f64().unwrap() >= 0.0);
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::Write;
+
+use log::LevelFilter;
+
+/**
+ * The level logged at for a number of `-v` flags: warnings and errors by default, each stage's
+ * timings with `-v`, and the details of each stage (dimensions, pixel counts, output paths) with
+ * `-vv`.
+ *
+ * [u8] The number of times `-v` was given.
+ */
+pub fn verbosity_level(verbose: u8) -> LevelFilter {
+    match verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        _ => LevelFilter::Debug,
+    }
+}
+
+/**
+ * Sets up logging to stderr, at the level `-v` asks for unless `RUST_LOG` says otherwise (e.g.
+ * `RUST_LOG=debug` for every detail, or `RUST_LOG=off` for none).
+ *
+ * Records are written as bare messages, without a timestamp or level. The images that failed
+ * aren't logged, but written to stderr directly (see `format_error`), so that no filter can hide
+ * them.
+ *
+ * [u8] The number of times `-v` was given.
+ */
+pub fn init_logging(verbose: u8) {
+    env_logger::Builder::new()
+        .filter_level(verbosity_level(verbose))
+        .parse_default_env()
+        .format(|buf, record| writeln!(buf, "{}", record.args()))
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_level() {
+        assert_eq!(verbosity_level(0), LevelFilter::Warn);
+        assert_eq!(verbosity_level(1), LevelFilter::Info);
+        assert_eq!(verbosity_level(2), LevelFilter::Debug);
+        assert_eq!(verbosity_level(5), LevelFilter::Debug);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod args;
+pub mod bench;
+pub mod logging;
+pub mod output_path;
+pub mod output_type;
+pub mod tui;
@@ -0,0 +1,339 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+
+use crate::error::ColorBuddyError;
+
+/**
+ * What to do when an output file already exists.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OverwritePolicy {
+    /// Replace it (the default).
+    Overwrite,
+    /// Leave it as it is, and skip writing the output.
+    NoClobber,
+}
+
+/**
+ * Checks whether an output may be written to `path` under the given policy. Returns `false` when
+ * the output should be skipped, because the file already exists and mustn't be clobbered.
+ *
+ * [&Path] The output file.
+ * [OverwritePolicy] What to do when the file already exists.
+ */
+pub fn prepare_output_path(path: &Path, policy: OverwritePolicy) -> bool {
+    match policy {
+        OverwritePolicy::Overwrite => true,
+        OverwritePolicy::NoClobber => !path.exists(),
+    }
+}
+
+/**
+ * Whether an `--output` path names a directory to write outputs into, rather than a file: it's
+ * an existing directory, or ends with a path separator (e.g. `palettes/`), so that a directory
+ * that doesn't exist yet can be given and created with `--create-dirs`.
+ *
+ * [&Path] The `--output` path.
+ */
+pub fn is_output_directory(path: &Path) -> bool {
+    path.is_dir()
+        || path
+            .as_os_str()
+            .to_string_lossy()
+            .ends_with(std::path::is_separator)
+}
+
+/**
+ * Makes sure the directory an output will be written to exists, creating it (and any missing
+ * parents) when asked to. Otherwise a missing directory fails with
+ * `ColorBuddyError::MissingOutputDirectory`, rather than with whatever the encoder reports when it
+ * can't create the file.
+ *
+ * [&Path] The output file.
+ * [bool] Whether to create the directory when it's missing.
+ */
+pub fn ensure_output_directory(path: &Path, create: bool) -> Result<()> {
+    let Some(directory) = path.parent().filter(|d| !d.as_os_str().is_empty()) else {
+        return Ok(());
+    };
+    if directory.is_dir() {
+        return Ok(());
+    }
+
+    if create {
+        std::fs::create_dir_all(directory)
+            .with_context(|| format!("Failed to create directory: {}", directory.display()))
+    } else {
+        Err(ColorBuddyError::MissingOutputDirectory(directory.display().to_string()).into())
+    }
+}
+
+/**
+ * Makes an output path unique within a batch, so that inputs sharing a stem (e.g. `a/photo.jpg`
+ * and `b/photo.jpg`) don't write to the same file. Paths already used are given an incrementing
+ * suffix: `photo_palette.png`, then `photo_palette_1.png`, `photo_palette_2.png`, ...
+ *
+ * [PathBuf] The output path the input would otherwise be written to.
+ * [&mut HashSet<PathBuf>] The output paths used so far in the batch. The returned path is added.
+ */
+pub fn unique_output_path(path: PathBuf, used: &mut HashSet<PathBuf>) -> PathBuf {
+    let mut unique = path.clone();
+    let mut suffix = 1;
+    while used.contains(&unique) {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let file_name = match path.extension() {
+            Some(extension) => format!("{stem}_{suffix}.{}", extension.to_string_lossy()),
+            None => format!("{stem}_{suffix}"),
+        };
+        unique = path.with_file_name(file_name);
+        suffix += 1;
+    }
+
+    used.insert(unique.clone());
+    unique
+}
+
+/**
+ * The details of an image's run that an `--output-template` can use, besides the stem and the
+ * extension, which come from the image itself.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct TemplateFields<'a> {
+    /// The template, e.g. `{stem}-{method}-{count}.{ext}`.
+    pub template: &'a str,
+    /// The quantisation method, e.g. `k-means`.
+    pub method: &'a str,
+    /// The number of colors requested.
+    pub count: usize,
+    /// The date of the run, as `YYYY-MM-DD`.
+    pub date: &'a str,
+}
+
+/**
+ * Expands the placeholders in an output file name template: `{stem}` (the input's file name
+ * without its extension), `{ext}` (the output's usual extension), `{method}`, `{count}` and
+ * `{date}`. Fails, naming the placeholder, for anything else in braces, or an unclosed brace.
+ *
+ * [&TemplateFields] The template, and the method, count and date to put in it.
+ * [&str] The stem of the input's file name.
+ * [&str] The output's extension.
+ */
+pub fn expand_output_template(
+    fields: &TemplateFields,
+    stem: &str,
+    ext: &str,
+) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = fields.template;
+    while let Some(open) = rest.find('{') {
+        expanded.push_str(&rest[..open]);
+        let placeholder = &rest[open + 1..];
+        let close = placeholder
+            .find('}')
+            .ok_or_else(|| format!("\"{}\" has a {{ with no closing }}", fields.template))?;
+        match &placeholder[..close] {
+            "stem" => expanded.push_str(stem),
+            "ext" => expanded.push_str(ext),
+            "method" => expanded.push_str(fields.method),
+            "count" => expanded.push_str(&fields.count.to_string()),
+            "date" => expanded.push_str(fields.date),
+            unknown => {
+                return Err(format!(
+                    "unknown placeholder {{{unknown}}}; use {{stem}}, {{ext}}, {{method}}, {{count}} or {{date}}"
+                ))
+            }
+        }
+        rest = &placeholder[close + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/**
+ * This helper function is used by clap when handling the output-template option. It checks that
+ * every placeholder in the template is one `expand_output_template` knows.
+ */
+pub fn output_template_parser(s: &str) -> Result<String, String> {
+    let fields = TemplateFields {
+        template: s,
+        method: "",
+        count: 0,
+        date: "",
+    };
+    expand_output_template(&fields, "", "")?;
+
+    Ok(s.to_owned())
+}
+
+/**
+ * Today's date (in UTC), as `YYYY-MM-DD`, for the `{date}` placeholder.
+ */
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/**
+ * Converts a number of days since 1970-01-01 to a (year, month, day) date in the proleptic
+ * Gregorian calendar, using Howard Hinnant's `civil_from_days` algorithm.
+ */
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_output_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let existing = dir.path().join("existing_palette.png");
+        let missing = dir.path().join("missing_palette.png");
+        std::fs::write(&existing, b"").unwrap();
+
+        // Test case 1: Overwriting goes ahead either way
+        assert!(prepare_output_path(&existing, OverwritePolicy::Overwrite));
+        assert!(prepare_output_path(&missing, OverwritePolicy::Overwrite));
+
+        // Test case 2: No clobbering skips existing files only
+        assert!(!prepare_output_path(&existing, OverwritePolicy::NoClobber));
+        assert!(prepare_output_path(&missing, OverwritePolicy::NoClobber));
+    }
+
+    #[test]
+    fn test_ensure_output_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let nested = dir.path().join("a/b/palette.png");
+
+        // Test case 1: Files in existing directories, or the current one, are fine
+        assert!(ensure_output_directory(&dir.path().join("palette.png"), false).is_ok());
+        assert!(ensure_output_directory(Path::new("palette.png"), false).is_ok());
+
+        // Test case 2: A missing directory is named in the error
+        let error = ensure_output_directory(&nested, false).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ColorBuddyError>(),
+            Some(&ColorBuddyError::MissingOutputDirectory(
+                dir.path().join("a/b").display().to_string()
+            ))
+        );
+
+        // Test case 3: ... unless it's created
+        ensure_output_directory(&nested, true).unwrap();
+        assert!(dir.path().join("a/b").is_dir());
+    }
+
+    #[test]
+    fn test_is_output_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        // Test case 1: Existing directories, and paths ending with a separator
+        assert!(is_output_directory(dir.path()));
+        assert!(is_output_directory(&dir.path().join("missing/")));
+        assert!(is_output_directory(Path::new("palettes/")));
+
+        // Test case 2: Anything else is a file name
+        assert!(!is_output_directory(&dir.path().join("missing")));
+        assert!(!is_output_directory(Path::new("palette.png")));
+    }
+
+    #[test]
+    fn test_unique_output_path() {
+        let mut used = HashSet::new();
+        let path = PathBuf::from("out/photo_palette.png");
+
+        // Test case 1: The first use keeps the name, later ones are numbered
+        assert_eq!(unique_output_path(path.clone(), &mut used), path);
+        assert_eq!(
+            unique_output_path(path.clone(), &mut used),
+            PathBuf::from("out/photo_palette_1.png")
+        );
+        assert_eq!(
+            unique_output_path(path, &mut used),
+            PathBuf::from("out/photo_palette_2.png")
+        );
+
+        // Test case 2: Names without an extension
+        let path = PathBuf::from("out/photo_palette");
+        assert_eq!(unique_output_path(path.clone(), &mut used), path);
+        assert_eq!(
+            unique_output_path(path, &mut used),
+            PathBuf::from("out/photo_palette_1")
+        );
+    }
+
+    fn fields(template: &str) -> TemplateFields<'_> {
+        TemplateFields {
+            template,
+            method: "median-cut",
+            count: 6,
+            date: "2024-02-29",
+        }
+    }
+
+    #[test]
+    fn test_expand_output_template() {
+        // Test case 1: Each placeholder on its own
+        let expand = |template| expand_output_template(&fields(template), "photo", "png");
+        assert_eq!(expand("{stem}").unwrap(), "photo");
+        assert_eq!(expand("{ext}").unwrap(), "png");
+        assert_eq!(expand("{method}").unwrap(), "median-cut");
+        assert_eq!(expand("{count}").unwrap(), "6");
+        assert_eq!(expand("{date}").unwrap(), "2024-02-29");
+
+        // Test case 2: Placeholders mixed with text, and repeated
+        assert_eq!(
+            expand("{stem}-{method}-{count}.{ext}").unwrap(),
+            "photo-median-cut-6.png"
+        );
+        assert_eq!(expand("{stem}_{stem}").unwrap(), "photo_photo");
+        assert_eq!(expand("palette.json").unwrap(), "palette.json");
+
+        // Test case 3: Unknown placeholders and unclosed braces are errors
+        let error = expand("{stem}-{size}.{ext}").unwrap_err();
+        assert!(error.contains("{size}"), "{error}");
+        assert!(expand("{stem").is_err());
+    }
+
+    #[test]
+    fn test_output_template_parser() {
+        assert_eq!(
+            output_template_parser("{stem}.{ext}").unwrap(),
+            "{stem}.{ext}"
+        );
+        assert!(output_template_parser("{name}.{ext}").is_err());
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(today().len(), 10);
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use crate::cli::output_path::is_output_directory;
+use crate::types::config::OutputType;
+
+/**
+ * The output type an `--output` path asks for, going by its extension, for when `--output-type`
+ * isn't given: `.json` for a JSON file, `.txt` for text, `.scss` for SCSS, `.svg` for SVG, `.gpl`
+ * for a GIMP palette, and the image formats for the original image with its palette. Paint.NET
+ * palettes are also `.txt`, so still need `--output-type paint-net`.
+ *
+ * Returns `None` for directories, and for extensions that don't imply a type.
+ *
+ * [&Path] The `--output` path.
+ */
+pub fn output_type_from_path(path: &Path) -> Option<OutputType> {
+    if is_output_directory(path) {
+        return None;
+    }
+
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "json" => Some(OutputType::JsonFile),
+        "txt" => Some(OutputType::Text),
+        "scss" => Some(OutputType::Scss),
+        "svg" => Some(OutputType::Svg),
+        "gpl" => Some(OutputType::Gpl),
+        "png" | "jpg" | "jpeg" | "gif" | "bmp" | "tif" | "tiff" | "webp" | "tga" | "ico" => {
+            Some(OutputType::OriginalImage)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_type_from_path() {
+        // Test case 1: Extensions that imply a type
+        assert_eq!(
+            output_type_from_path(Path::new("out/palette.json")),
+            Some(OutputType::JsonFile)
+        );
+        assert_eq!(
+            output_type_from_path(Path::new("palette.SVG")),
+            Some(OutputType::Svg)
+        );
+        assert_eq!(
+            output_type_from_path(Path::new("palette.gpl")),
+            Some(OutputType::Gpl)
+        );
+        assert_eq!(
+            output_type_from_path(Path::new("banner.jpg")),
+            Some(OutputType::OriginalImage)
+        );
+
+        // Test case 2: Extensions that don't, and directories
+        assert_eq!(output_type_from_path(Path::new("palette.xyz")), None);
+        assert_eq!(output_type_from_path(Path::new("palette")), None);
+        let dir = tempfile::TempDir::new().unwrap();
+        let json_dir = dir.path().join("palettes.json");
+        std::fs::create_dir(&json_dir).unwrap();
+        assert_eq!(output_type_from_path(&json_dir), None);
+        assert_eq!(output_type_from_path(Path::new("new.json/")), None);
+    }
+}
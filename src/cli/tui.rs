@@ -0,0 +1,319 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use exoquant::Color;
+use image::RgbImage;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color as TermColor, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::palette::extractor::{extract_palette, MAX_COLORS};
+use crate::types::config::{ColorSpace, QuantisationMethod};
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * The quantisation methods the left and right arrow keys cycle through, in order.
+ */
+const METHODS: [QuantisationMethod; 5] = [
+    QuantisationMethod::KMeans,
+    QuantisationMethod::MedianCut,
+    QuantisationMethod::Uniform,
+    QuantisationMethod::Popularity,
+    QuantisationMethod::Auto,
+];
+
+/**
+ * What the event loop should do after a key press.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TuiCommand {
+    /// Keep going, redrawing (and re-extracting, if the settings changed).
+    Continue,
+    /// Write the output for the current settings.
+    Write,
+    /// Leave the TUI.
+    Quit,
+}
+
+/**
+ * The settings being tuned in `--tui` mode, and the palette they last produced.
+ */
+#[derive(Clone)]
+pub struct TuiState {
+    /// The number of colors to extract.
+    pub number_of_colors: usize,
+    /// The quantisation method to extract with.
+    pub quantisation_method: QuantisationMethod,
+    /// The color space K-Means clusters in.
+    pub color_space: ColorSpace,
+    /// The palette extracted with the current settings.
+    pub palette: Vec<Color>,
+    /// How long the last extraction took.
+    pub extraction_time: Duration,
+    /// How many times the palette has been extracted.
+    pub extractions: usize,
+    /// A message for the status line, e.g. where the output was written.
+    pub status: String,
+    /// Whether the settings have changed since the palette was last extracted.
+    stale: bool,
+}
+
+impl TuiState {
+    /**
+     * Starts tuning from the given settings. Nothing is extracted until the first `refresh`.
+     *
+     * [usize] The number of colors to extract.
+     * [QuantisationMethod] The quantisation method to extract with.
+     * [ColorSpace] The color space K-Means clusters in.
+     */
+    pub fn new(
+        number_of_colors: usize,
+        quantisation_method: QuantisationMethod,
+        color_space: ColorSpace,
+    ) -> TuiState {
+        TuiState {
+            number_of_colors,
+            quantisation_method,
+            color_space,
+            palette: Vec::new(),
+            extraction_time: Duration::ZERO,
+            extractions: 0,
+            status: String::new(),
+            stale: true,
+        }
+    }
+
+    /**
+     * Updates the settings for a key press. Up and down change the number of colors (from 1 to
+     * `MAX_COLORS`), left and right cycle through the quantisation methods, `w` or Enter asks for
+     * the output to be written, and `q` or Esc to quit. Other keys are ignored.
+     *
+     * [KeyCode] The key pressed.
+     */
+    pub fn handle_key(&mut self, code: KeyCode) -> TuiCommand {
+        let method_index = METHODS
+            .iter()
+            .position(|m| *m == self.quantisation_method)
+            .unwrap_or(0);
+
+        match code {
+            KeyCode::Up | KeyCode::Char('+') if self.number_of_colors < MAX_COLORS => {
+                self.number_of_colors += 1;
+                self.stale = true;
+            }
+            KeyCode::Down | KeyCode::Char('-') if self.number_of_colors > 1 => {
+                self.number_of_colors -= 1;
+                self.stale = true;
+            }
+            KeyCode::Right => {
+                self.quantisation_method = METHODS[(method_index + 1) % METHODS.len()];
+                self.stale = true;
+            }
+            KeyCode::Left => {
+                self.quantisation_method =
+                    METHODS[(method_index + METHODS.len() - 1) % METHODS.len()];
+                self.stale = true;
+            }
+            KeyCode::Char('w') | KeyCode::Enter => return TuiCommand::Write,
+            KeyCode::Char('q') | KeyCode::Esc => return TuiCommand::Quit,
+            _ => {}
+        }
+
+        TuiCommand::Continue
+    }
+
+    /**
+     * Re-extracts the palette if the settings have changed since it was last extracted, timing
+     * the extraction. Returns whether it was re-extracted.
+     *
+     * [&RgbImage] The image to extract the palette from.
+     */
+    pub fn refresh(&mut self, image: &RgbImage) -> bool {
+        if !self.stale {
+            return false;
+        }
+
+        let start = Instant::now();
+        self.palette = extract_palette(
+            image,
+            self.number_of_colors,
+            self.quantisation_method,
+            self.color_space,
+        );
+        self.extraction_time = start.elapsed();
+        self.extractions += 1;
+        self.stale = false;
+        true
+    }
+}
+
+/**
+ * Runs the interactive tuning mode: the palette is drawn as a row of colored blocks under the
+ * current settings and how long they took, and is re-extracted whenever a key changes them (see
+ * `TuiState::handle_key`). The terminal is restored when the TUI is left, even on an error.
+ *
+ * [&Path] The image being tuned, shown in the header.
+ * [&RgbImage] The pixels to extract the palette from.
+ * [TuiState] The settings to start from.
+ * [W] Writes the output for the current settings, returning where it went.
+ */
+pub fn run_tui<W>(file: &Path, image: &RgbImage, state: TuiState, write: W) -> Result<()>
+where
+    W: FnMut(&TuiState) -> Result<PathBuf>,
+{
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, file, image, state, write);
+    ratatui::restore();
+    result
+}
+
+fn event_loop<W>(
+    terminal: &mut DefaultTerminal,
+    file: &Path,
+    image: &RgbImage,
+    mut state: TuiState,
+    mut write: W,
+) -> Result<()>
+where
+    W: FnMut(&TuiState) -> Result<PathBuf>,
+{
+    loop {
+        if state.refresh(image) {
+            state.status.clear();
+        }
+        terminal.draw(|frame| draw(frame, file, &state))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match state.handle_key(key.code) {
+            TuiCommand::Continue => {}
+            TuiCommand::Write => {
+                state.status = match write(&state) {
+                    Ok(output) => format!("Wrote {}", output.display()),
+                    Err(e) => format!("Error: {e:#}"),
+                };
+            }
+            TuiCommand::Quit => return Ok(()),
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, file: &Path, state: &TuiState) {
+    let [header, swatches, hexes, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(3),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let settings = format!(
+        "{} colors using {} in {:.2?}  {}",
+        state.number_of_colors, state.quantisation_method, state.extraction_time, state.status
+    );
+    frame.render_widget(
+        Paragraph::new(settings).block(Block::bordered().title(file.display().to_string())),
+        header,
+    );
+
+    let columns = Layout::horizontal(vec![Constraint::Fill(1); state.palette.len()]);
+    let swatch_areas = columns.split(swatches);
+    let hex_areas = columns.split(hexes);
+    for (i, color) in state.palette.iter().enumerate() {
+        let fill = Style::new().bg(TermColor::Rgb(color.r, color.g, color.b));
+        frame.render_widget(Block::new().style(fill), swatch_areas[i]);
+        frame.render_widget(
+            Paragraph::new(rgb_to_hex(color.r, color.g, color.b)),
+            hex_areas[i],
+        );
+    }
+
+    frame.render_widget(
+        Line::from("↑/↓ colors  ←/→ method  w write  q quit"),
+        footer,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stripes() -> RgbImage {
+        let colors = [[200, 30, 30], [30, 200, 30], [30, 30, 200], [220, 220, 40]];
+        RgbImage::from_fn(40, 10, |x, _| image::Rgb(colors[(x / 10) as usize]))
+    }
+
+    #[test]
+    fn test_handle_key_changes_settings() {
+        let mut state = TuiState::new(4, QuantisationMethod::KMeans, ColorSpace::Rgb);
+
+        // Test case 1: Up and down change the number of colors
+        assert_eq!(state.handle_key(KeyCode::Up), TuiCommand::Continue);
+        assert_eq!(state.number_of_colors, 5);
+        state.handle_key(KeyCode::Down);
+        state.handle_key(KeyCode::Down);
+        assert_eq!(state.number_of_colors, 3);
+
+        // Test case 2: The number of colors stays between 1 and MAX_COLORS
+        let mut state = TuiState::new(1, QuantisationMethod::KMeans, ColorSpace::Rgb);
+        state.handle_key(KeyCode::Down);
+        assert_eq!(state.number_of_colors, 1);
+        let mut state = TuiState::new(MAX_COLORS, QuantisationMethod::KMeans, ColorSpace::Rgb);
+        state.handle_key(KeyCode::Up);
+        assert_eq!(state.number_of_colors, MAX_COLORS);
+
+        // Test case 3: Left and right cycle through the methods, wrapping around
+        state.handle_key(KeyCode::Right);
+        assert_eq!(state.quantisation_method, QuantisationMethod::MedianCut);
+        state.handle_key(KeyCode::Left);
+        state.handle_key(KeyCode::Left);
+        assert_eq!(state.quantisation_method, QuantisationMethod::Auto);
+        state.handle_key(KeyCode::Left);
+        assert_eq!(state.quantisation_method, QuantisationMethod::Popularity);
+
+        // Test case 4: Every method is reachable, and each can be left and come back to
+        for method in METHODS {
+            let mut state = TuiState::new(4, method, ColorSpace::Rgb);
+            state.handle_key(KeyCode::Right);
+            assert_ne!(state.quantisation_method, method);
+            state.handle_key(KeyCode::Left);
+            assert_eq!(state.quantisation_method, method);
+        }
+
+        // Test case 5: Writing and quitting
+        assert_eq!(state.handle_key(KeyCode::Char('w')), TuiCommand::Write);
+        assert_eq!(state.handle_key(KeyCode::Esc), TuiCommand::Quit);
+    }
+
+    #[test]
+    fn test_refresh_re_extracts_on_change() {
+        let image = stripes();
+        let mut state = TuiState::new(2, QuantisationMethod::MedianCut, ColorSpace::Rgb);
+
+        // Test case 1: The first refresh extracts the palette, and later ones don't until a change
+        assert!(state.refresh(&image));
+        assert_eq!(state.palette.len(), 2);
+        assert!(!state.refresh(&image));
+        assert_eq!(state.extractions, 1);
+
+        // Test case 2: Changing the number of colors re-extracts with the new count
+        state.handle_key(KeyCode::Up);
+        state.handle_key(KeyCode::Up);
+        assert!(state.refresh(&image));
+        assert_eq!(state.palette.len(), 4);
+        assert_eq!(state.extractions, 2);
+
+        // Test case 3: Keys that change nothing don't re-extract
+        state.handle_key(KeyCode::Char('w'));
+        assert!(!state.refresh(&image));
+    }
+}
@@ -0,0 +1,82 @@
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+/**
+ * The errors colorbuddy reports itself, as opposed to those passed up from the libraries it uses
+ * (which are wrapped in `anyhow` context instead).
+ */
+#[derive(Debug, PartialEq)]
+pub enum ColorBuddyError {
+    /// The palette can't be drawn, e.g. because its swatches would be less than a pixel wide.
+    InvalidPalette(String),
+    /// The image is wider or taller than the `--max-dimension` allows.
+    ImageTooLarge {
+        width: u32,
+        height: u32,
+        max_dimension: u32,
+    },
+    /// The image is in a format (going by its extension) that can't be decoded.
+    UnsupportedFormat(String),
+    /// An image URL couldn't be fetched, e.g. because the server couldn't be reached in time.
+    FetchFailed { url: String, reason: String },
+    /// The server responded to an image URL with something other than 200 OK.
+    HttpStatus { url: String, status: u16 },
+    /// Fewer colors were extracted than requested, and `--strict-count` asked for exactly that
+    /// many.
+    TooFewColors { extracted: usize, requested: usize },
+    /// Extracting the palette took longer than the `--timeout-per-image` allows.
+    ExtractionTimedOut(Duration),
+    /// The directory an output would be written to doesn't exist, and `--create-dirs` wasn't given.
+    MissingOutputDirectory(String),
+    /// A PDF was given, but colorbuddy was built without the `pdf` feature that renders them.
+    #[cfg(not(feature = "pdf"))]
+    PdfSupportDisabled,
+}
+
+impl fmt::Display for ColorBuddyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorBuddyError::InvalidPalette(reason) => write!(f, "Invalid palette: {reason}"),
+            ColorBuddyError::ImageTooLarge {
+                width,
+                height,
+                max_dimension,
+            } => write!(
+                f,
+                "Image is too large: {width}x{height} exceeds the maximum dimension of {max_dimension}px (see --max-dimension)"
+            ),
+            ColorBuddyError::UnsupportedFormat(extension) => {
+                write!(f, "Unsupported image format: .{extension}")
+            }
+            ColorBuddyError::FetchFailed { url, reason } => {
+                write!(f, "Error fetching image: {url}: {reason}")
+            }
+            ColorBuddyError::HttpStatus { url, status } => {
+                write!(f, "Error fetching image: {url}: the server responded with HTTP {status}")
+            }
+            ColorBuddyError::TooFewColors {
+                extracted,
+                requested,
+            } => write!(
+                f,
+                "Too few colors: extracted {extracted} of the {requested} requested (see --strict-count)"
+            ),
+            ColorBuddyError::ExtractionTimedOut(timeout) => write!(
+                f,
+                "Extraction timed out: gave up after {timeout:.2?} (see --timeout-per-image)"
+            ),
+            ColorBuddyError::MissingOutputDirectory(directory) => write!(
+                f,
+                "Output directory doesn't exist: {directory} (create it, or pass --create-dirs)"
+            ),
+            #[cfg(not(feature = "pdf"))]
+            ColorBuddyError::PdfSupportDisabled => write!(
+                f,
+                "PDF support isn't built in; rebuild colorbuddy with `--features pdf` to read PDFs"
+            ),
+        }
+    }
+}
+
+impl Error for ColorBuddyError {}
@@ -0,0 +1,46 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+/**
+ * Computes the SHA-256 digest of a file's bytes, as lowercase hex. The file is streamed through
+ * the hasher rather than read into memory.
+ *
+ * [&Path] The file to hash.
+ */
+pub fn sha256_file(file: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    File::open(file)
+        .and_then(|mut f| io::copy(&mut f, &mut hasher))
+        .with_context(|| format!("Error reading image: {}", file.display()))?;
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("image.png");
+
+        // Test case 1: A known digest
+        std::fs::write(&path, b"colorbuddy").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "2d4bb42890939349a6e8eb04f141571f71dbeadf1145a389e1849132bc24a87f"
+        );
+
+        // Test case 2: A missing file
+        assert!(sha256_file(&dir.path().join("missing.png")).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, DynamicImage, RgbImage};
+
+/**
+ * Decodes every frame of an animated GIF, fully composited, as RGB images.
+ *
+ * [&Path] The GIF to decode.
+ */
+pub fn read_gif_frames(file: &Path) -> Result<Vec<RgbImage>> {
+    let reader = BufReader::new(
+        File::open(file).with_context(|| format!("Error opening image: {}", file.display()))?,
+    );
+    let frames = GifDecoder::new(reader)
+        .and_then(|decoder| decoder.into_frames().collect_frames())
+        .with_context(|| format!("Error decoding GIF frames: {}", file.display()))?;
+
+    Ok(frames
+        .into_iter()
+        .map(|frame| DynamicImage::ImageRgba8(frame.into_buffer()).to_rgb8())
+        .collect())
+}
+
+/**
+ * Stacks frames vertically into a single image, so that the pixels of every frame contribute to
+ * the histogram used when extracting a palette.
+ *
+ * [&[RgbImage]] The frames to stack. These are expected to share the same width, as the frames
+ * of a GIF do.
+ */
+pub fn stack_frames(frames: &[RgbImage]) -> RgbImage {
+    let width = frames.iter().map(|f| f.width()).max().unwrap_or(0);
+    let height = frames.iter().map(|f| f.height()).sum();
+    let mut stacked = RgbImage::new(width, height);
+
+    let mut y_offset = 0;
+    for frame in frames {
+        image::imageops::replace(&mut stacked, frame, 0, y_offset);
+        y_offset += frame.height() as i64;
+    }
+
+    stacked
+}
+
+#[cfg(test)]
+mod tests {
+    use image::codecs::gif::GifEncoder;
+    use image::{Frame, Rgba, RgbaImage};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::palette::extractor::extract_palette;
+    use crate::types::config::{ColorSpace, QuantisationMethod};
+
+    #[test]
+    fn test_all_frames_palette_contains_every_frame() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("animated.gif");
+        {
+            let mut encoder = GifEncoder::new(File::create(&path).unwrap());
+            encoder
+                .encode_frames(vec![
+                    Frame::new(RgbaImage::from_pixel(8, 8, Rgba([255, 0, 0, 255]))),
+                    Frame::new(RgbaImage::from_pixel(8, 8, Rgba([0, 0, 255, 255]))),
+                ])
+                .unwrap();
+        }
+
+        let frames = read_gif_frames(&path).unwrap();
+        assert_eq!(frames.len(), 2);
+
+        let stacked = stack_frames(&frames);
+        assert_eq!(stacked.dimensions(), (8, 16));
+
+        let palette: Vec<[u8; 3]> =
+            extract_palette(&stacked, 2, QuantisationMethod::KMeans, ColorSpace::Rgb)
+                .iter()
+                .map(|c| [c.r, c.g, c.b])
+                .collect();
+
+        assert!(palette.contains(&[255, 0, 0]));
+        assert!(palette.contains(&[0, 0, 255]));
+    }
+}
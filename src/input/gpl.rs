@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use exoquant::Color;
+
+/**
+ * Reads the colors out of a GIMP palette (`.gpl`), such as one saved by colorbuddy with
+ * `--output-type gpl`. The header, comments and the names after each color's values are ignored.
+ *
+ * [&Path] The GIMP palette file.
+ */
+pub fn read_gpl_palette(file: &Path) -> Result<Vec<Color>> {
+    let contents = fs::read_to_string(file)
+        .with_context(|| format!("Error opening palette: {}", file.display()))?;
+    let mut lines = contents.lines().enumerate();
+    if lines.next().map(|(_, l)| l.trim()) != Some("GIMP Palette") {
+        return Err(anyhow!(
+            "Error parsing palette: {} is not a GIMP palette",
+            file.display()
+        ));
+    }
+
+    let mut colors = Vec::new();
+    for (index, line) in lines {
+        let line = line.trim();
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("Name:")
+            || line.starts_with("Columns:")
+        {
+            continue;
+        }
+
+        let mut channels = line.split_whitespace().map(|c| c.parse::<u8>().ok());
+        match (channels.next(), channels.next(), channels.next()) {
+            (Some(Some(r)), Some(Some(g)), Some(Some(b))) => colors.push(Color::new(r, g, b, 0xff)),
+            _ => {
+                return Err(anyhow!(
+                    "Error parsing palette: line {} of {} is not a color",
+                    index + 1,
+                    file.display()
+                ))
+            }
+        }
+    }
+
+    if colors.is_empty() {
+        return Err(anyhow!(
+            "Error parsing palette: {} contains no colors",
+            file.display()
+        ));
+    }
+
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::gpl::write_gpl_palette;
+
+    #[test]
+    fn test_read_gpl_palette() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("palette.gpl");
+        let colors = vec![
+            Color::new(10, 20, 30, 255),
+            Color::new(200, 100, 0, 255),
+            Color::new(255, 255, 255, 255),
+        ];
+
+        // Test case 1: A palette written by colorbuddy round-trips
+        write_gpl_palette(&colors, "palette", &path).unwrap();
+        assert!(read_gpl_palette(&path).unwrap() == colors);
+
+        // Test case 2: Palettes saved by GIMP, with comments and unnamed colors, are read
+        fs::write(
+            &path,
+            "GIMP Palette\nName: Test\nColumns: 4\n# A comment\n\n  0   0   0\tBlack\n255 0 128\n",
+        )
+        .unwrap();
+        let result: Vec<[u8; 3]> = read_gpl_palette(&path)
+            .unwrap()
+            .iter()
+            .map(|c| [c.r, c.g, c.b])
+            .collect();
+        assert_eq!(result, vec![[0, 0, 0], [255, 0, 128]]);
+
+        // Test case 3: Lines that aren't colors are reported
+        fs::write(&path, "GIMP Palette\n0 0 256\n").unwrap();
+        let error = read_gpl_palette(&path).err().unwrap().to_string();
+        assert!(error.starts_with("Error parsing palette: line 2 of"));
+
+        // Test case 4: Files that aren't GIMP palettes, or hold no colors, are rejected
+        fs::write(&path, "0 0 0\n").unwrap();
+        assert!(read_gpl_palette(&path).is_err());
+        fs::write(&path, "GIMP Palette\nName: Empty\n").unwrap();
+        assert!(read_gpl_palette(&path).is_err());
+    }
+}
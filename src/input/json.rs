@@ -0,0 +1,89 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use exoquant::Color;
+use serde_json::Value;
+
+/**
+ * Reads the colors back out of a palette saved as JSON by colorbuddy (the `color_1`, `color_2`,
+ * ... entries, in order). Any other fields, like a thumbnail, are ignored.
+ *
+ * [&Path] The JSON palette file.
+ */
+pub fn read_json_palette(file: &Path) -> Result<Vec<Color>> {
+    let contents = fs::read_to_string(file)
+        .with_context(|| format!("Error opening palette: {}", file.display()))?;
+    let document: Value = serde_json::from_str(&contents)
+        .with_context(|| format!("Error parsing palette: {}", file.display()))?;
+    let entries = document
+        .as_object()
+        .ok_or_else(|| anyhow!("Error parsing palette: {} is not an object", file.display()))?;
+
+    let mut colors = Vec::new();
+    for (key, entry) in entries.iter().filter(|(k, _)| k.starts_with("color_")) {
+        let channel = |name: &str| {
+            entry[name]
+                .as_u64()
+                .and_then(|c| u8::try_from(c).ok())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Error parsing palette: {} has no valid {key}.{name}",
+                        file.display()
+                    )
+                })
+        };
+        colors.push(Color::new(
+            channel("r")?,
+            channel("g")?,
+            channel("b")?,
+            0xff,
+        ));
+    }
+
+    if colors.is_empty() {
+        return Err(anyhow!(
+            "Error parsing palette: {} contains no colors",
+            file.display()
+        ));
+    }
+
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::json::write_json_palette_to_file;
+    use crate::types::color::{ColorDetails, PaletteOutput};
+
+    #[test]
+    fn test_read_json_palette() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("palette.json");
+        let colors = vec![Color::new(10, 20, 30, 255), Color::new(200, 100, 0, 255)];
+
+        // Test case 1: A palette written by colorbuddy round-trips
+        let details = ColorDetails {
+            color_spaces: true,
+            named_colors: true,
+            simulate: None,
+        };
+        write_json_palette_to_file(&PaletteOutput::new(&colors, details), &path).unwrap();
+        let result: Vec<[u8; 3]> = read_json_palette(&path)
+            .unwrap()
+            .iter()
+            .map(|c| [c.r, c.g, c.b])
+            .collect();
+        assert_eq!(result, vec![[10, 20, 30], [200, 100, 0]]);
+
+        // Test case 2: Missing channels are reported
+        fs::write(&path, r#"{"color_1": {"r": 1, "g": 2}}"#).unwrap();
+        let error = read_json_palette(&path).err().unwrap().to_string();
+        assert!(error.ends_with("has no valid color_1.b"));
+
+        // Test case 3: Palettes with no colors are rejected
+        fs::write(&path, "{}").unwrap();
+        assert!(read_json_palette(&path).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use crate::error::ColorBuddyError;
+
+/**
+ * The default `--max-dimension`: comfortably larger than any camera, but small enough that the
+ * pixels of an image can be held in memory.
+ */
+pub const DEFAULT_MAX_DIMENSION: u32 = 20000;
+
+/**
+ * Rejects images wider or taller than `max_dimension` pixels (0 disables the check).
+ *
+ * [u32] The width of the image.
+ * [u32] The height of the image.
+ * [u32] The largest width or height allowed.
+ */
+pub fn check_dimensions(
+    width: u32,
+    height: u32,
+    max_dimension: u32,
+) -> Result<(), ColorBuddyError> {
+    if max_dimension > 0 && (width > max_dimension || height > max_dimension) {
+        return Err(ColorBuddyError::ImageTooLarge {
+            width,
+            height,
+            max_dimension,
+        });
+    }
+
+    Ok(())
+}
+
+/**
+ * Checks an image's dimensions against `max_dimension` by reading only its header, before any
+ * pixels are decoded. Images whose header can't be read are let through, so that opening them
+ * reports the real problem.
+ *
+ * [&Path] The image file.
+ * [u32] The largest width or height allowed (0 disables the check).
+ */
+pub fn check_image_dimensions(file: &Path, max_dimension: u32) -> Result<(), ColorBuddyError> {
+    match image::image_dimensions(file) {
+        Ok((width, height)) => check_dimensions(width, height, max_dimension),
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_dimensions() {
+        // Test case 1: Within the limit
+        assert!(check_dimensions(20000, 100, DEFAULT_MAX_DIMENSION).is_ok());
+
+        // Test case 2: Metadata claiming an enormous image
+        assert_eq!(
+            check_dimensions(100, 60000, DEFAULT_MAX_DIMENSION),
+            Err(ColorBuddyError::ImageTooLarge {
+                width: 100,
+                height: 60000,
+                max_dimension: DEFAULT_MAX_DIMENSION
+            })
+        );
+
+        // Test case 3: A limit of 0 disables the check
+        assert!(check_dimensions(u32::MAX, u32::MAX, 0).is_ok());
+    }
+
+    #[test]
+    fn test_check_image_dimensions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("wide.png");
+        image::RgbImage::new(64, 8).save(&path).unwrap();
+
+        assert!(check_image_dimensions(&path, 64).is_ok());
+        let error = check_image_dimensions(&path, 32).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Image is too large: 64x8 exceeds the maximum dimension of 32px (see --max-dimension)"
+        );
+        assert!(check_image_dimensions(&dir.path().join("missing.png"), 32).is_ok());
+    }
+}
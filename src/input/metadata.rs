@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/**
+ * The path to record as the source of a palette: the absolute path of the file where it can be
+ * resolved, or the input as given otherwise (e.g. a URL).
+ *
+ * [&Path] The input, as given on the command line.
+ */
+pub fn source_path(file: &Path) -> PathBuf {
+    fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf())
+}
+
+/**
+ * When a file was last modified, in whole seconds since the Unix epoch. `None` when it can't be
+ * told, e.g. for a URL, or on a filesystem that doesn't record it.
+ *
+ * [&Path] The file.
+ */
+pub fn modified_timestamp(file: &Path) -> Option<u64> {
+    let modified = fs::metadata(file).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_source_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("red.png");
+        fs::write(&path, b"").unwrap();
+
+        // Test case 1: Files are made absolute
+        let relative = dir.path().join(".").join("red.png");
+        assert_eq!(source_path(&relative), fs::canonicalize(&path).unwrap());
+
+        // Test case 2: Anything else is left as given
+        let url = Path::new("https://example.com/red.png");
+        assert_eq!(source_path(url), url);
+    }
+
+    #[test]
+    fn test_modified_timestamp() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("red.png");
+        fs::write(&path, b"").unwrap();
+
+        // Test case 1: A file just written was modified moments ago
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let modified = modified_timestamp(&path).unwrap();
+        assert!(now.abs_diff(modified) < 60);
+
+        // Test case 2: Missing files
+        assert_eq!(modified_timestamp(&dir.path().join("missing.png")), None);
+    }
+}
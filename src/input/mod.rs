@@ -0,0 +1,9 @@
+pub mod checksum;
+pub mod gif;
+pub mod gpl;
+pub mod json;
+pub mod limits;
+pub mod metadata;
+pub mod paths;
+pub mod pdf;
+pub mod url;
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::input::url::is_url;
+
+/**
+ * Removes inputs that refer to a file already given, e.g. when a glob and an explicit path overlap,
+ * or a symlink points to another input. Paths are compared once canonicalized; paths that can't
+ * be (e.g. because the file doesn't exist) are compared as given, so they still fail later with
+ * their own error.
+ *
+ * Returns the inputs to process, in the order given, and the duplicates that were skipped.
+ *
+ * [&[PathBuf]] The input paths.
+ */
+pub fn deduplicate_inputs(images: &[PathBuf]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let mut seen = HashSet::new();
+    let mut inputs = Vec::new();
+    let mut duplicates = Vec::new();
+
+    for image in images {
+        let key = fs::canonicalize(image).unwrap_or_else(|_| image.clone());
+        if seen.insert(key) {
+            inputs.push(image.clone());
+        } else {
+            duplicates.push(image.clone());
+        }
+    }
+
+    (inputs, duplicates)
+}
+
+/**
+ * Reads a list of inputs from a file, one per line. Blank lines, and lines starting with `#`, are
+ * skipped, and surrounding whitespace is trimmed. Relative paths are relative to the directory
+ * the list is in (not the current directory), so a list can be moved along with its images; URLs
+ * and absolute paths are kept as they are.
+ *
+ * [&Path] The list file.
+ */
+pub fn read_input_list(list_file: &Path) -> Result<Vec<PathBuf>> {
+    let contents = fs::read_to_string(list_file)
+        .with_context(|| format!("Error reading input list: {}", list_file.display()))?;
+    let base = list_file.parent().unwrap_or(Path::new(""));
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let input = Path::new(line);
+            if is_url(input) {
+                input.to_path_buf()
+            } else {
+                base.join(input)
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deduplicate_inputs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let red = dir.path().join("red.png");
+        let blue = dir.path().join("blue.png");
+        let missing = dir.path().join("missing.png");
+        fs::write(&red, b"").unwrap();
+        fs::write(&blue, b"").unwrap();
+
+        // Test case 1: The same file by another path
+        let relative = dir.path().join(".").join("red.png");
+        let (inputs, duplicates) = deduplicate_inputs(&[red.clone(), blue.clone(), relative]);
+        assert_eq!(inputs, vec![red.clone(), blue.clone()]);
+        assert_eq!(duplicates.len(), 1);
+
+        // Test case 2: Missing files are compared as given
+        let (inputs, _) = deduplicate_inputs(&[missing.clone(), missing.clone(), red.clone()]);
+        assert_eq!(inputs, vec![missing, red.clone()]);
+
+        // Test case 3: A symlink to an input
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link.png");
+            std::os::unix::fs::symlink(&red, &link).unwrap();
+            let (inputs, duplicates) = deduplicate_inputs(&[link.clone(), red]);
+            assert_eq!(inputs, vec![link]);
+            assert_eq!(duplicates.len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_read_input_list() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let list = dir.path().join("images.txt");
+        fs::write(
+            &list,
+            "# Holiday photos\nbeach.png\n\n  nested/sunset.jpg  \n/absolute/cliff.png\nhttps://example.com/sea.png\n",
+        )
+        .unwrap();
+
+        // Test case 1: Comments and blank lines are skipped, and relative paths are joined to the
+        // list's directory
+        assert_eq!(
+            read_input_list(&list).unwrap(),
+            vec![
+                dir.path().join("beach.png"),
+                dir.path().join("nested/sunset.jpg"),
+                PathBuf::from("/absolute/cliff.png"),
+                PathBuf::from("https://example.com/sea.png"),
+            ]
+        );
+
+        // Test case 2: A missing list is an error
+        assert!(read_input_list(&dir.path().join("missing.txt")).is_err());
+    }
+}
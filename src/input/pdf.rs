@@ -0,0 +1,165 @@
+use std::path::Path;
+
+use anyhow::Result;
+use image::RgbImage;
+
+#[cfg(not(feature = "pdf"))]
+use crate::error::ColorBuddyError;
+
+/**
+ * The longest edge, in pixels, PDF pages are rendered at. Pages are vector art, so they're drawn
+ * at a size large enough to capture fine detail without taking too long to extract from.
+ */
+#[cfg(feature = "pdf")]
+const PDF_RENDER_SIZE: i32 = 2000;
+
+/**
+ * Whether a file is a PDF, going by its extension.
+ *
+ * [&Path] The file.
+ */
+pub fn is_pdf(file: &Path) -> bool {
+    file.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+}
+
+/**
+ * Renders pages of a PDF as RGB images, using the PDFium library installed on the system. Each
+ * page is drawn so that its longest edge is `PDF_RENDER_SIZE` pixels.
+ *
+ * Only available when colorbuddy is built with the `pdf` feature; otherwise this fails with
+ * `ColorBuddyError::PdfSupportDisabled`.
+ *
+ * [&Path] The PDF to render.
+ * [Option<u16>] The 1-based number of the page to render, or `None` for every page.
+ */
+#[cfg(feature = "pdf")]
+pub fn render_pdf_pages(file: &Path, page: Option<u16>) -> Result<Vec<RgbImage>> {
+    use anyhow::{anyhow, Context};
+    use pdfium_render::prelude::{PdfRenderConfig, Pdfium};
+
+    let bindings = Pdfium::bind_to_system_library()
+        .context("Error loading the PDFium library, which is needed to read PDFs")?;
+    let pdfium = Pdfium::new(bindings);
+    let document = pdfium
+        .load_pdf_from_file(file, None)
+        .with_context(|| format!("Error opening PDF: {}", file.display()))?;
+    let pages = document.pages();
+    let page_count = pages.len();
+
+    let indices = match page {
+        Some(page) if i32::from(page) <= page_count => vec![i32::from(page) - 1],
+        Some(page) => {
+            return Err(anyhow!(
+                "{} has {page_count} pages, so there's no page {page}",
+                file.display()
+            ))
+        }
+        None => (0..page_count).collect(),
+    };
+
+    let config = PdfRenderConfig::new()
+        .set_maximum_width(PDF_RENDER_SIZE)
+        .set_maximum_height(PDF_RENDER_SIZE);
+    indices
+        .into_iter()
+        .map(|index| {
+            let rendered = pages.get(index).and_then(|page| {
+                page.render_with_config(&config)
+                    .and_then(|bitmap| bitmap.as_image())
+            });
+            rendered.map(|image| image.to_rgb8()).with_context(|| {
+                format!("Error rendering page {} of {}", index + 1, file.display())
+            })
+        })
+        .collect()
+}
+
+/**
+ * Stands in for the PDF renderer when colorbuddy is built without the `pdf` feature, failing with
+ * `ColorBuddyError::PdfSupportDisabled`.
+ */
+#[cfg(not(feature = "pdf"))]
+pub fn render_pdf_pages(_file: &Path, _page: Option<u16>) -> Result<Vec<RgbImage>> {
+    Err(ColorBuddyError::PdfSupportDisabled.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_pdf() {
+        assert!(is_pdf(Path::new("brand/guidelines.pdf")));
+        assert!(is_pdf(Path::new("GUIDELINES.PDF")));
+        assert!(!is_pdf(Path::new("logo.png")));
+        assert!(!is_pdf(Path::new("pdf")));
+    }
+
+    #[cfg(not(feature = "pdf"))]
+    #[test]
+    fn test_render_pdf_pages_without_the_feature() {
+        let error = render_pdf_pages(Path::new("guidelines.pdf"), Some(1)).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ColorBuddyError>(),
+            Some(&ColorBuddyError::PdfSupportDisabled)
+        );
+    }
+
+    /// A one-page PDF, 100pt square, filled with red.
+    #[cfg(feature = "pdf")]
+    fn red_page_pdf() -> Vec<u8> {
+        let content = "1 0 0 rg 0 0 100 100 re f";
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_owned(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 100 100] /Contents 4 0 R >>".to_owned(),
+            format!(
+                "<< /Length {} >>\nstream\n{content}\nendstream",
+                content.len()
+            ),
+        ];
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n{object}\nendobj\n", i + 1).bytes());
+        }
+        let xref = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+        for offset in offsets {
+            pdf.extend(format!("{offset:010} 00000 n \n").bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .bytes(),
+        );
+        pdf
+    }
+
+    #[cfg(feature = "pdf")]
+    #[test]
+    fn test_render_pdf_pages() {
+        use crate::palette::extractor::extract_palette;
+        use crate::types::config::{ColorSpace, QuantisationMethod};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("red.pdf");
+        std::fs::write(&path, red_page_pdf()).unwrap();
+
+        // Test case 1: The page renders, and its palette is red
+        let pages = render_pdf_pages(&path, Some(1)).unwrap();
+        assert_eq!(pages.len(), 1);
+        let palette = extract_palette(&pages[0], 1, QuantisationMethod::MedianCut, ColorSpace::Rgb);
+        let [r, g, b] = [palette[0].r, palette[0].g, palette[0].b];
+        assert!(r > 240 && g < 16 && b < 16, "{r} {g} {b}");
+
+        // Test case 2: Every page, and a page that isn't there
+        assert_eq!(render_pdf_pages(&path, None).unwrap().len(), 1);
+        assert!(render_pdf_pages(&path, Some(2)).is_err());
+    }
+}
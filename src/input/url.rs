@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::ColorBuddyError;
+
+/**
+ * Whether an input is an `http://` or `https://` URL rather than a file.
+ *
+ * [&Path] The input, as given on the command line.
+ */
+pub fn is_url(input: &Path) -> bool {
+    input
+        .to_str()
+        .map(|s| {
+            let s = s.to_ascii_lowercase();
+            s.starts_with("http://") || s.starts_with("https://")
+        })
+        .unwrap_or(false)
+}
+
+/**
+ * The file name to name outputs after for a URL input: the last segment of its path, without any
+ * query string or fragment (e.g. `photo.png` for `https://example.com/a/photo.png?w=800`). URLs
+ * without a path are named `download`.
+ *
+ * [&str] The URL.
+ */
+pub fn url_file_name(url: &str) -> PathBuf {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = without_scheme.split(['?', '#']).next().unwrap_or_default();
+
+    match path.split_once('/') {
+        Some((_, path)) => match path.rsplit('/').find(|segment| !segment.is_empty()) {
+            Some(segment) => PathBuf::from(segment),
+            None => PathBuf::from("download"),
+        },
+        None => PathBuf::from("download"),
+    }
+}
+
+/**
+ * Downloads an image, returning its bytes. Fails with `ColorBuddyError::FetchFailed` when the
+ * server can't be reached (or doesn't answer in time), and `ColorBuddyError::HttpStatus` when it
+ * responds with anything other than 200 OK.
+ *
+ * [&str] The URL of the image.
+ * [Option<Duration>] How long the whole request may take; `None` uses reqwest's default of 30s.
+ */
+pub fn fetch_image(url: &str, timeout: Option<Duration>) -> Result<Vec<u8>, ColorBuddyError> {
+    let fetch_failed = |e: reqwest::Error| ColorBuddyError::FetchFailed {
+        url: url.to_owned(),
+        reason: e.without_url().to_string(),
+    };
+
+    let mut client = reqwest::blocking::Client::builder();
+    if let Some(timeout) = timeout {
+        client = client.timeout(timeout);
+    }
+    let response = client
+        .build()
+        .map_err(fetch_failed)?
+        .get(url)
+        .send()
+        .map_err(fetch_failed)?;
+
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(ColorBuddyError::HttpStatus {
+            url: url.to_owned(),
+            status: response.status().as_u16(),
+        });
+    }
+
+    Ok(response.bytes().map_err(fetch_failed)?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url(Path::new("https://example.com/photo.png")));
+        assert!(is_url(Path::new("HTTP://example.com/photo.png")));
+        assert!(!is_url(Path::new("photos/photo.png")));
+        assert!(!is_url(Path::new("ftp://example.com/photo.png")));
+    }
+
+    #[test]
+    fn test_url_file_name() {
+        // Test case 1: The last segment of the path
+        assert_eq!(
+            url_file_name("https://example.com/a/photo.png"),
+            PathBuf::from("photo.png")
+        );
+
+        // Test case 2: Query strings, fragments and trailing slashes are dropped
+        assert_eq!(
+            url_file_name("https://example.com/a/photo.jpg?w=800#top"),
+            PathBuf::from("photo.jpg")
+        );
+        assert_eq!(
+            url_file_name("http://example.com/gallery/sunset/"),
+            PathBuf::from("sunset")
+        );
+
+        // Test case 3: No path at all
+        assert_eq!(
+            url_file_name("https://example.com"),
+            PathBuf::from("download")
+        );
+        assert_eq!(
+            url_file_name("https://example.com/?id=4"),
+            PathBuf::from("download")
+        );
+    }
+}
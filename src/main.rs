@@ -1,52 +1,98 @@
-use std::fmt;
+mod cli;
+mod error;
+mod input;
+mod output;
+mod palette;
+mod types;
+mod utils;
+
+use std::collections::HashSet;
+use std::io;
 use std::path::*;
-
-use anyhow::Result;
-use clap::{Parser, ValueEnum};
+use std::process::ExitCode;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use clap::builder::RangedU64ValueParser;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser};
 use console::style;
 use console::Color as ConsoleColor;
-use exoquant::{generate_palette, optimizer, Color, Histogram, SimpleColorSpace};
-use image::{DynamicImage, RgbImage};
-use mcq::ColorNode;
-use mcq::MMCQ;
-
-#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
-enum OutputType {
-    Json,
-    OriginalImage,
-    StandalonePalette,
-}
-
-impl fmt::Display for OutputType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            OutputType::Json => write!(f, "json"),
-            OutputType::OriginalImage => write!(f, "original-image"),
-            OutputType::StandalonePalette => write!(f, "standalone"),
-        }
-    }
-}
-
-#[derive(Clone, Copy, Debug, ValueEnum)]
-enum QuantisationMethod {
-    KMeans,
-    MedianCut,
-}
-
-impl fmt::Display for QuantisationMethod {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            QuantisationMethod::MedianCut => write!(f, "median-cut"),
-            QuantisationMethod::KMeans => write!(f, "k-means"),
-        }
-    }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum PaletteHeight {
-    Absolute(u32),
-    Percentage(f32),
-}
+use exoquant::Color;
+use image::{ColorType, DynamicImage, ImageError, ImageFormat, ImageOutputFormat, RgbImage};
+use log::{debug, info, log_enabled, warn, Level};
+
+use crate::cli::args::{BenchArgs, Command, RecolorArgs};
+use crate::cli::bench::{bench, format_bench_json, format_bench_table};
+use crate::cli::logging::init_logging;
+use crate::cli::output_path::{
+    ensure_output_directory, expand_output_template, is_output_directory, output_template_parser,
+    prepare_output_path, today, unique_output_path, OverwritePolicy, TemplateFields,
+};
+use crate::cli::output_type::output_type_from_path;
+use crate::cli::tui::{run_tui, TuiState};
+use crate::error::ColorBuddyError;
+use crate::input::checksum::sha256_file;
+use crate::input::gif::{read_gif_frames, stack_frames};
+use crate::input::gpl::read_gpl_palette;
+use crate::input::json::read_json_palette;
+use crate::input::limits::{check_dimensions, check_image_dimensions, DEFAULT_MAX_DIMENSION};
+use crate::input::metadata::{modified_timestamp, source_path};
+use crate::input::paths::{deduplicate_inputs, read_input_list};
+use crate::input::pdf::{is_pdf, render_pdf_pages};
+use crate::input::url::{fetch_image, is_url, url_file_name};
+use crate::output::clut::write_clut_palette;
+use crate::output::dither::dither;
+use crate::output::error::format_error;
+use crate::output::gpl::write_gpl_palette;
+use crate::output::image::{
+    save_original_with_palette, write_original_with_palette, CompositeLayout,
+};
+use crate::output::json::{generate_palette_json, group_by_hue, write_json_palette_to_file};
+use crate::output::layout::{layout_file_name, write_layout_json};
+use crate::output::metadata::{embed_png_text, palette_text, METHOD_KEYWORD, PALETTE_KEYWORD};
+use crate::output::paintnet::write_paintnet_palette;
+use crate::output::preview::{is_headless, preview_output};
+use crate::output::scss::write_scss_palette;
+use crate::output::standalone::{
+    save_standalone_palette, write_standalone_palette, StandaloneLayout,
+};
+use crate::output::summary::format_summary;
+use crate::output::svg::write_svg_palette;
+use crate::output::text::write_text_palette;
+use crate::output::thumbnail::thumbnail_data_uri;
+use crate::output::{output_format, save_image, write_image, DEFAULT_JPEG_QUALITY};
+use crate::palette::alpha::with_source_alpha;
+use crate::palette::average::{mean_color, mean_color_linear};
+use crate::palette::border::crop_border;
+use crate::palette::cache::{palette_cache_key, read_cached_palette, write_cached_palette};
+use crate::palette::chroma::boost_chroma;
+use crate::palette::extractor::{
+    choose_method, count_pixels, dominant_color, downsample, extract_palette_auto,
+    extract_palette_detailed, extract_palette_high_precision, extract_palette_kmeans,
+    ExtractionResult, Rgb16Image, MAX_COLORS,
+};
+use crate::palette::filter::{exclude_colors, filter_by_luminance, filter_by_saturation};
+use crate::palette::histogram::distinct_colors;
+use crate::palette::include::include_colors;
+use crate::palette::kmeans::MAX_ITERATIONS;
+use crate::palette::linear::{delinearize_palette, linearize_image};
+use crate::palette::pad::pad_palette;
+use crate::palette::recolor::recolor_image;
+use crate::palette::sort::{sort_by_contrast, sort_palette};
+use crate::palette::weight::{center_weighted, saliency_weighted, MAX_CENTER_WEIGHT};
+use crate::types::color::{ColorDetails, ColorInfo, HistogramEntry, PaletteOutput};
+use crate::types::config::{
+    ColorSpace, ColorVisionDeficiency, DownscaleFilter, ErrorFormat, Orientation, OutputType,
+    PadStrategy, PaletteFill, PaletteHeight, PalettePosition, PercentageBase, QuantisationMethod,
+    SortOrder, AUTO_PALETTE_HEIGHT_PERCENTAGE, MAX_PALETTE_HEIGHT_PERCENTAGE,
+};
+use crate::types::outcome::ProcessOutcome;
+use crate::utils::color_conversion::{hex_to_rgb, linearize, rgb_to_cct, rgb_to_hex, simulate_cvd};
+use crate::utils::retry::retry;
 
 struct Example {
     description: String,
@@ -54,7 +100,7 @@ struct Example {
 }
 
 fn examples() -> String {
-    let examples = vec![
+    let examples = [
         Example {
             description: "Generate JSON containing the 8 most prevalent colors in the image:".to_string(),
             example: "colorbuddy --output-type json original-image.jpg".to_string(),
@@ -146,235 +192,2118 @@ fn about() -> String {
     )
 }
 
-#[derive(Debug, Parser)]
+#[derive(Clone, Debug, Parser)]
 #[command(author, version, about = about(), long_about = long_about(), after_help = examples())]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
-    #[arg(short = 'm', long = "quantisation-method", default_value_t = QuantisationMethod::KMeans)]
+    #[arg(short = 'm',
+          long = "quantisation-method",
+          long_help = "The quantisation method. 'uniform' picks the most common colors of a fixed grid over the RGB cube, whose size is the --number-of-colors rounded to the nearest cube (8, 27, 64, ...). 'popularity' picks the most common exact colors, which suits pixel art and logos. 'auto' picks one to suit each image: median-cut for very large images (over 16 megapixels), popularity for images with at most 256 distinct colors, and k-means otherwise; JSON output records which as quantisation_method.",
+          default_value_t = QuantisationMethod::KMeans)]
     quantisation_method: QuantisationMethod,
 
-    #[arg(short = 'n', long = "number-of-colors", default_value = "8")]
+    #[arg(short = 'n',
+          long = "number-of-colors",
+          value_parser = RangedU64ValueParser::<usize>::new().range(1..=MAX_COLORS as u64),
+          default_value = "8")]
     number_of_colors: usize,
 
     #[arg(short = 'o', long = "output", default_value = None)]
     output: Option<PathBuf>,
 
-    #[arg(short = 't', long = "output-type", default_value_t = OutputType::OriginalImage)]
+    #[arg(
+        long = "output-template",
+        value_name = "TEMPLATE",
+        help = "Name output files after a template, e.g. \"{stem}-{method}-{count}.{ext}\".",
+        long_help = "Name output files after a template rather than <stem>_palette.<ext>, e.g. \"{stem}-{method}-{count}.{ext}\". The placeholders are {stem} (the input's file name without its extension), {ext} (the output type's usual extension), {method} (the quantisation method), {count} (the number of colors requested) and {date} (today's date, as YYYY-MM-DD). The file goes where it would otherwise.",
+        value_parser = output_template_parser
+    )]
+    output_template: Option<String>,
+
+    #[arg(short = 't',
+          long = "output-type",
+          help = "The type of output. Without this, it's inferred from the extension of --output (e.g. .json for json-file), or is original-image.",
+          default_value_t = OutputType::OriginalImage)]
     output_type: OutputType,
 
-    #[arg(short = 'p',
-          long = "palette-height",
-          help = "e.g. 100, 100px, 50%",
-          long_help = "Specify the height in pixels or as a percentage of the image height (e.g. 100, 100px, 50%)",
-          value_parser = palette_height_parser,
-          default_value = "256")]
-    palette_height: PaletteHeight,
+    #[arg(
+        short = 'p',
+        long = "palette-height",
+        id = "palette_height",
+        value_name = "PALETTE_HEIGHT",
+        help = "e.g. 100, 100px, 50%, auto",
+        long_help = "Specify the height in pixels or as a percentage of the image height (e.g. 100, 100px, 50%), or auto for 15% of the image height. Percentages above 100% need --allow-large-palette.",
+        default_value = "256"
+    )]
+    palette_height_arg: String,
+
+    #[arg(
+        long = "allow-large-palette",
+        help = "Allow percentage palette heights above 100% (up to 1000%), e.g. for banners taller than the image."
+    )]
+    allow_large_palette: bool,
+
+    #[arg(
+        long = "decimal-comma",
+        help = "Accept a comma as the decimal separator in --palette-height (e.g. 50,5%)."
+    )]
+    decimal_comma: bool,
 
     #[arg(short = 'w',
           long = "palette-width",
-          help = "Used only when generating a standalone palette. Provide a width in pixels. (e.g. 100, 500)",
+          help = "Used only when generating a standalone or SVG palette. Provide a width in pixels. (e.g. 100, 500)",
           default_value = None)]
     palette_width: Option<u32>,
 
+    #[arg(
+        long = "aspect",
+        value_name = "W:H",
+        help = "Size standalone palettes to this aspect ratio (e.g. 3:1), working out the height from --palette-width, or otherwise the width from --palette-height.",
+        value_parser = aspect_parser
+    )]
+    aspect: Option<(u32, u32)>,
+
+    #[arg(
+        long = "palette-position",
+        help = "The edge of the original image the palette strip is attached to.",
+        long_help = "The edge of the original image the palette strip is attached to. At the left or right, the strip spans the height of the image and --palette-height sets its width (a percentage is then relative to the image width).",
+        default_value_t = PalettePosition::Bottom
+    )]
+    palette_position: PalettePosition,
+
+    #[arg(
+        long = "percentage-base",
+        help = "What a percentage --palette-height of a standalone palette is relative to: the height of the image, or the palette's own width.",
+        long_help = "What a percentage --palette-height of a standalone (or SVG) palette is relative to: the height of the source image, or the palette's own width (--palette-width, or the width of the image), so that e.g. 20% makes a palette five times as wide as it is tall. Ignored by the original-image output, whose strip is always relative to the image.",
+        default_value_t = PercentageBase::Height
+    )]
+    percentage_base: PercentageBase,
+
+    #[arg(
+        long = "dither",
+        help = "In the original-image output, replace the image with a Floyd–Steinberg dithered version using only the palette colors."
+    )]
+    dither: bool,
+
+    #[arg(
+        long = "stdout",
+        help = "Write the original-image or standalone-palette output to stdout rather than a file. Only one image may be given."
+    )]
+    stdout: bool,
+
+    #[arg(
+        long = "unique-names",
+        help = "Number the outputs of inputs that would otherwise be written to the same file (e.g. a/photo.jpg and b/photo.jpg): photo_palette.png, photo_palette_1.png, ..."
+    )]
+    unique_names: bool,
+
+    #[arg(
+        long = "no-clobber",
+        help = "Don't overwrite output files that already exist; skip them, with a notice on stderr."
+    )]
+    no_clobber: bool,
+
+    #[arg(
+        long = "create-dirs",
+        help = "Create the directories outputs are written to when they don't exist, rather than failing."
+    )]
+    create_dirs: bool,
+
+    #[arg(
+        long = "cache-dir",
+        value_name = "DIR",
+        help = "Cache extracted palettes in this directory, reusing them when an image is extracted again with the same options.",
+        long_help = "Cache extracted palettes in this directory, as small JSON files, and reuse them when an image is extracted again. Palettes are keyed by a hash of the pixels they're extracted from and every option that affects extraction, so changing either extracts afresh."
+    )]
+    cache_dir: Option<PathBuf>,
+
+    #[arg(
+        long = "overwrite",
+        help = "Overwrite output files that already exist (the default).",
+        overrides_with = "no_clobber"
+    )]
+    overwrite: bool,
+
+    #[arg(
+        long = "preview",
+        help = "Open the image output in the default image viewer once it's written. Only applies when a single image is processed."
+    )]
+    preview: bool,
+
+    #[arg(
+        long = "dry-run",
+        help = "Extract the palettes, but rather than writing anything, print where each output would be written and how many colors it would have.",
+        conflicts_with_all = ["preview", "tui"]
+    )]
+    dry_run: bool,
+
+    #[arg(
+        long = "frame",
+        help = "Surround the original-image output with a frame this many pixels wide, in the --background color.",
+        default_value = "0"
+    )]
+    frame: u32,
+
+    #[arg(
+        long = "swatch-border",
+        value_name = "PX",
+        help = "Outline each swatch with a border this many pixels wide, drawn inside its edges, in the --swatch-border-color.",
+        default_value = "0"
+    )]
+    swatch_border: u32,
+
+    #[arg(
+        long = "swatch-border-color",
+        help = "The color of the --swatch-border (e.g. #000000).",
+        value_parser = hex_to_rgb,
+        default_value = "#000000"
+    )]
+    swatch_border_color: [u8; 3],
+
+    #[arg(
+        long = "palette-margin",
+        value_name = "PX",
+        help = "Separate the image from the palette strip with a band this many pixels wide, in the --background color.",
+        default_value = "0"
+    )]
+    palette_margin: u32,
+
+    #[arg(
+        long = "clut-scale",
+        value_name = "FACTOR",
+        help = "Enlarge each pixel of a clut output to a FACTOR x FACTOR block.",
+        value_parser = clap::value_parser!(u32).range(1..),
+        default_value = "1"
+    )]
+    clut_scale: u32,
+
+    #[arg(
+        long = "palette-fill",
+        help = "What fills the end of the palette strip when the swatches don't reach it: the --background color, or the last swatch extended.",
+        long_help = "What fills the end of the palette strip when the swatches don't reach it, e.g. when its length isn't a multiple of the number of colors: the --background color, or the last swatch extended to the end.",
+        default_value_t = PaletteFill::Background
+    )]
+    palette_fill: PaletteFill,
+
+    #[arg(
+        long = "repeat",
+        value_name = "N",
+        help = "Repeat the palette N times along a standalone palette, e.g. for a seamless tile.",
+        long_help = "Repeat the palette N times along a standalone palette, e.g. for a seamless background tile: the strip is divided into N times as many swatches, cycling through the same colors.",
+        value_parser = clap::value_parser!(u32).range(1..),
+        default_value = "1"
+    )]
+    repeat: u32,
+
+    #[arg(
+        long = "palette-rows",
+        value_name = "N",
+        help = "Arrange the swatches of a standalone palette in a grid of N rows, rather than a single strip.",
+        long_help = "Arrange the swatches of a standalone palette in a grid of N rows, filled left to right and top to bottom, with as many columns as it takes to fit every color. Each swatch is --palette-width divided by the number of columns wide, and --palette-height divided by N high; any of the last row that's left over is the --background color. Swatches in a grid are all the same size, so --orientation, --proportional and --palette-fill extend don't apply, and --gradient draws a single strip.",
+        value_parser = clap::value_parser!(u32).range(1..),
+        default_value = "1"
+    )]
+    palette_rows: u32,
+
+    #[arg(
+        long = "transparent-swatches",
+        help = "Keep the transparency of the image in the palette: each swatch takes the alpha of the pixels its color stands for. PNG outputs only.",
+        long_help = "Keep the transparency of the image in the palette: each color takes the mean alpha of the pixels nearest to it, rather than being opaque. Original-image and standalone-palette PNGs are written as RGBA, with each swatch drawn in its color's alpha (the rest of the image stays opaque), and JSON output reports it as a. Other image formats can't store the alpha, so their swatches stay opaque.",
+        conflicts_with_all = ["combine", "tui"]
+    )]
+    transparent_swatches: bool,
+
+    #[arg(
+        long = "title",
+        help = "A caption to draw in a band across the top of the original-image and standalone-palette outputs."
+    )]
+    title: Option<String>,
+
+    #[arg(long = "background",
+          help = "The background color, used e.g. for the frame and the title band (e.g. #ffffff).",
+          value_parser = hex_to_rgb,
+          default_value = "#ffffff")]
+    background: [u8; 3],
+
+    #[arg(long = "jpeg-quality",
+          help = "The quality (1-100) to encode .jpg image outputs at. Ignored for other formats.",
+          value_parser = RangedU64ValueParser::<u8>::new().range(1..=100),
+          default_value_t = DEFAULT_JPEG_QUALITY)]
+    jpeg_quality: u8,
+
+    #[arg(
+        long = "embed-metadata",
+        help = "Embed the palette (as a comma-separated list of hex codes) and the quantisation method in tEXt chunks of PNG outputs."
+    )]
+    embed_metadata: bool,
+
+    #[arg(
+        long = "also-json",
+        help = "Also write the palette as JSON, next to the output with a .json extension.",
+        long_help = "Also write the palette as JSON, whatever the --output-type, next to the output with its extension swapped for .json (e.g. photo_palette.png and photo_palette.json). The JSON is sorted by --sort-json, and has the same details as --output-type json-file would.",
+        conflicts_with = "stdout"
+    )]
+    also_json: bool,
+
+    #[arg(
+        long = "layout-json",
+        help = "Also write where each swatch was drawn in the output image, next to it with a .layout.json extension.",
+        long_help = "Also write where each swatch was drawn in an original-image or standalone-palette output, e.g. to build an image map over it. The JSON has the size of the image, and the x, y, width, height and hex color of each swatch (the palette's, then any --simulate strip's), and is written next to the output with its extension swapped for .layout.json.",
+        conflicts_with = "stdout"
+    )]
+    layout_json: bool,
+
+    #[arg(
+        long = "orientation",
+        help = "Lay the swatches of a standalone or SVG palette out left to right (horizontal) or top to bottom (vertical).",
+        default_value_t = Orientation::Horizontal
+    )]
+    orientation: Orientation,
+
+    #[arg(
+        long = "labels",
+        help = "Label each swatch with its hex value in SVG output."
+    )]
+    labels: bool,
+
+    #[arg(
+        long = "color-space",
+        help = "The color space K-Means measures color differences in.",
+        long_help = "The color space K-Means measures color differences in. 'lab' clusters in CIELAB so that distances reflect human perception. Ignored by Median Cut.",
+        default_value_t = ColorSpace::Rgb
+    )]
+    color_space: ColorSpace,
+
+    #[arg(
+        long = "color-spaces",
+        help = "Include CIELAB, LCh and OKLCH values for each color in JSON output. OKLCH lightness is 0-1 and its hue is in degrees."
+    )]
+    color_spaces: bool,
+
+    #[arg(
+        long = "named-colors",
+        help = "Include the name of the nearest CSS named color (e.g. \"firebrick\") for each color in JSON output."
+    )]
+    named_colors: bool,
+
+    #[arg(
+        long = "simulate",
+        help = "Show how the palette appears with a color vision deficiency: a simulated variant of each color in JSON output, and a second palette strip in image output."
+    )]
+    simulate: Option<ColorVisionDeficiency>,
+
+    #[arg(
+        long = "max-dimension",
+        value_name = "PX",
+        help = "Reject images wider or taller than this many pixels, before loading them. 0 disables the check.",
+        default_value_t = DEFAULT_MAX_DIMENSION
+    )]
+    max_dimension: u32,
+
+    #[arg(
+        long = "ignore-border",
+        value_name = "PX",
+        help = "Leave a border this many pixels wide (e.g. the noisy edge of a scan) out of the palette. Output images still include it.",
+        default_value = "0",
+        conflicts_with = "all_frames"
+    )]
+    ignore_border: u32,
+
+    #[arg(long = "sample-size",
+          help = "Downsample the image so its longest edge is at most this many pixels before extracting the palette. Output images stay full size.",
+          value_parser = clap::value_parser!(u32).range(1..))]
+    sample_size: Option<u32>,
+
+    #[arg(
+        long = "downscale-filter",
+        help = "The resampling filter --sample-size downsamples with; quicker filters are less accurate.",
+        long_help = "The resampling filter --sample-size downsamples with. 'nearest' is quickest but can skip over fine detail (e.g. thin lines or dithering) entirely, skewing the palette; 'triangle' and 'gaussian' average neighbouring pixels; 'lanczos3' is slowest but keeps the most detail. Has no effect without --sample-size.",
+        default_value_t = DownscaleFilter::Triangle,
+        requires = "sample_size"
+    )]
+    downscale_filter: DownscaleFilter,
+
+    #[arg(long = "exclude-color",
+          help = "Ignore pixels of this color (e.g. a plain background) when extracting the palette. Can be repeated.",
+          value_parser = hex_to_rgb)]
+    exclude_color: Vec<[u8; 3]>,
+
+    #[arg(
+        long = "include-color",
+        value_name = "HEX",
+        help = "Make sure this color is in the palette (e.g. a required text color). Can be repeated.",
+        long_help = "Make sure this color is in the palette, e.g. a required text color. Each one takes the place of the extracted color covering the least of the image, so the palette still has --number-of-colors colors. Can be repeated.",
+        value_parser = hex_to_rgb,
+        conflicts_with_all = ["auto_colors", "average", "combine", "tui"]
+    )]
+    include_color: Vec<[u8; 3]>,
+
+    #[arg(
+        long = "exclude-tolerance",
+        help = "How far (RGB distance, 0-441) a pixel may be from an --exclude-color and still be ignored.",
+        default_value = "10"
+    )]
+    exclude_tolerance: f32,
+
+    #[arg(long = "center-weight",
+          help = "Weight pixels by their closeness to the center of the image, up to this many times (1-16) at the center, so central colors dominate.",
+          value_parser = center_weight_parser,
+          default_value = "1.0",
+          conflicts_with = "all_frames")]
+    center_weight: f32,
+
+    #[arg(
+        long = "saliency-weight",
+        help = "Weight pixels by the strength of the edges around them, up to 16 times, so the colors of detailed regions dominate flat backgrounds.",
+        conflicts_with_all = ["center_weight", "all_frames"]
+    )]
+    saliency_weight: bool,
+
+    #[arg(long = "min-saturation",
+          help = "Drop palette colors whose HSL saturation (0-100) is below this. The palette may end up with fewer colors than requested.",
+          value_parser = saturation_parser)]
+    min_saturation: Option<f32>,
+
+    #[arg(
+        long = "min-luminance",
+        help = "Drop palette colors whose relative luminance (0 for black to 1 for white) is below this. The palette may end up with fewer colors than requested.",
+        value_parser = luminance_parser
+    )]
+    min_luminance: Option<f64>,
+
+    #[arg(
+        long = "max-luminance",
+        help = "Drop palette colors whose relative luminance (0 for black to 1 for white) is above this, e.g. 0.5 for a dark theme. The palette may end up with fewer colors than requested.",
+        value_parser = luminance_parser
+    )]
+    max_luminance: Option<f64>,
+
+    #[arg(
+        long = "chroma-boost",
+        help = "Multiply the saturation of every palette color by this, for a more vivid palette (1 for no change, below 1 to mute it). Hue and lightness are kept.",
+        value_parser = chroma_boost_parser
+    )]
+    chroma_boost: Option<f32>,
+
+    #[arg(
+        long = "sort",
+        help = "The order to list the palette colors in, in every output (unless overridden by --sort-json or --sort-image).",
+        default_value_t = SortOrder::None
+    )]
+    sort: SortOrder,
+
+    #[arg(
+        long = "sort-json",
+        help = "The order to list the palette colors in, in JSON output. Defaults to --sort."
+    )]
+    sort_json: Option<SortOrder>,
+
+    #[arg(
+        long = "sort-image",
+        help = "The order to draw the palette swatches in, in image and SVG output. Defaults to --sort."
+    )]
+    sort_image: Option<SortOrder>,
+
+    #[arg(
+        long = "sort-contrast-against",
+        value_name = "HEX",
+        help = "List the palette colors by WCAG contrast against this color (e.g. a page background), highest first. Overrides --sort.",
+        value_parser = hex_to_rgb,
+        conflicts_with_all = ["sort_json", "sort_image"]
+    )]
+    sort_contrast_against: Option<[u8; 3]>,
+
+    #[arg(
+        long = "reverse",
+        help = "Reverse the order of the palette colors (after any --sort)."
+    )]
+    reverse: bool,
+
+    #[arg(
+        long = "uppercase",
+        help = "Use uppercase hex digits (e.g. #1A6B3F) in text output."
+    )]
+    uppercase: bool,
+
+    #[arg(
+        long = "gradient",
+        help = "Blend the colors of a standalone palette into a smooth gradient, rather than drawing swatches."
+    )]
+    gradient: bool,
+
+    #[arg(
+        long = "proportional",
+        help = "Size each swatch of the palette strip by how much of the image its color covers, rather than equally.",
+        conflicts_with = "gradient"
+    )]
+    proportional: bool,
+
+    #[arg(
+        long = "gradient-space",
+        value_name = "SPACE",
+        help = "The color space --gradient interpolates in.",
+        default_value_t = ColorSpace::Rgb,
+        requires = "gradient"
+    )]
+    gradient_space: ColorSpace,
+
+    #[arg(
+        long = "kmeans-iterations",
+        value_name = "N",
+        help = "Run K-Means on every core, making at most N refinement passes (16 by default).",
+        long_help = "Run K-Means with colorbuddy's own multi-threaded implementation rather than exoquant's, making at most N refinement passes (16 when no count is given, e.g. --kmeans-iterations=8). This is much quicker on large images; fewer passes are quicker still but may stop before the clusters settle. Ignored by Median Cut and Uniform.",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "16",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        conflicts_with_all = ["auto_colors", "average", "high_precision"]
+    )]
+    kmeans_iterations: Option<usize>,
+
+    #[arg(
+        long = "seed-palette",
+        value_name = "HEX,HEX,...",
+        help = "Start K-Means from these colors (e.g. brand colors), so the palette stays close to them.",
+        long_help = "Start K-Means from these colors (e.g. --seed-palette '#1a6b3f,#f2c14e'), so the palette drifts as little as possible from them. With fewer seeds than --number-of-colors, the remaining clusters are started as usual. Uses colorbuddy's own K-Means (see --kmeans-iterations); ignored by Median Cut and Uniform.",
+        value_delimiter = ',',
+        value_parser = hex_to_rgb,
+        conflicts_with_all = ["auto_colors", "average", "high_precision"]
+    )]
+    seed_palette: Vec<[u8; 3]>,
+
+    #[arg(
+        long = "palette-in",
+        value_name = "FILE",
+        help = "Use the palette in this file rather than extracting one from each image.",
+        long_help = "Use the palette in this file, a GIMP palette (.gpl) or a palette saved by colorbuddy as JSON, rather than extracting one from each image. Every output type can be produced from it, e.g. to convert a palette to another format, or to recolor images to it with --output-type posterize."
+    )]
+    palette_in: Option<PathBuf>,
+
+    #[arg(
+        long = "pad-to-count",
+        value_name = "STRATEGY",
+        help = "Pad palettes that come out with fewer colors than requested up to --number-of-colors, by interpolating between colors (the default) or repeating the last (--pad-to-count=repeat).",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "interpolate",
+        conflicts_with = "auto_colors"
+    )]
+    pad_to_count: Option<PadStrategy>,
+
+    #[arg(
+        long = "strict-count",
+        help = "Fail, rather than carry on with a shorter palette, when fewer colors than --number-of-colors are extracted.",
+        conflicts_with_all = ["pad_to_count", "auto_colors", "average"]
+    )]
+    strict_count: bool,
+
+    #[arg(
+        long = "pad-palette",
+        help = "Pad Paint.NET palettes with white up to the 96 colors Paint.NET expects."
+    )]
+    pad_palette: bool,
+
+    #[arg(
+        long = "group-by-hue",
+        help = "Group the colors into hue families (reds, oranges, ..., grays) in JSON output."
+    )]
+    group_by_hue: bool,
+
+    #[arg(
+        long = "json-flat",
+        help = "Write JSON output as a bare array of colors, without the color_N keys or any extras."
+    )]
+    json_flat: bool,
+
+    #[arg(
+        long = "json-compact",
+        help = "Write JSON output on a single line, without whitespace, rather than pretty-printed."
+    )]
+    json_compact: bool,
+
+    #[arg(
+        long = "ndjson",
+        help = "Write the JSON output of each image as a single line, so a batch of images forms one stream of newline-delimited JSON.",
+        conflicts_with = "json_flat"
+    )]
+    ndjson: bool,
+
+    #[arg(
+        long = "thumbnail",
+        value_name = "PX",
+        help = "Embed a thumbnail of the source image, this many pixels along its longest edge, in JSON output.",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    thumbnail: Option<u32>,
+
+    #[arg(
+        long = "checksum",
+        help = "Include the SHA-256 digest of the source image file in JSON output, as source_sha256.",
+        conflicts_with = "combine"
+    )]
+    checksum: bool,
+
+    #[arg(
+        long = "temperature",
+        help = "Include the color temperature of the image's average color in JSON output, as color_temperature_k.",
+        long_help = "Include the correlated color temperature of the image's average color in JSON output, as color_temperature_k: low (around 2000K) for warm, reddish images, and high for cool, bluish ones. It's estimated with McCamy's approximation, so is only meaningful for images with a roughly neutral cast, and is left out for an all-black image.",
+        conflicts_with = "combine"
+    )]
+    temperature: bool,
+
+    #[arg(
+        long = "histogram",
+        help = "Include the image's most common exact colors, and how many pixels are each, in JSON output, as histogram.",
+        long_help = "Include the image's exact colors, before quantisation, in JSON output, as a histogram of {\"hex\", \"count\"} entries, most common first. Only the --histogram-top most common are listed, as photos can have hundreds of thousands.",
+        conflicts_with = "combine"
+    )]
+    histogram: bool,
+
+    #[arg(
+        long = "histogram-top",
+        value_name = "K",
+        help = "The number of colors to list in the --histogram.",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        default_value = "256",
+        requires = "histogram"
+    )]
+    histogram_top: usize,
+
+    #[arg(
+        long = "scss-variable",
+        help = "The name of the SCSS variable holding the palette map in scss output.",
+        default_value = "palette"
+    )]
+    scss_variable: String,
+
+    #[arg(
+        long = "summary",
+        help = "Print a summary table of every processed image to stderr once all images are done."
+    )]
+    summary: bool,
+
+    #[arg(
+        long = "all-frames",
+        help = "For animated GIFs, extract one palette representative of every frame rather than just the first."
+    )]
+    all_frames: bool,
+
+    #[arg(
+        long = "page",
+        value_name = "N",
+        help = "For PDFs, the page to extract the palette from.",
+        long_help = "For PDFs, the page to extract the palette from, counting from 1. PDFs need colorbuddy to be built with the pdf feature, and the PDFium library to be installed.",
+        value_parser = clap::value_parser!(u16).range(1..),
+        default_value = "1"
+    )]
+    page: u16,
+
+    #[arg(
+        long = "all-pages",
+        help = "For PDFs, extract one palette representative of every page rather than just one.",
+        conflicts_with = "page"
+    )]
+    all_pages: bool,
+
+    #[arg(
+        long = "combine",
+        help = "Extract a single palette from all of the images combined, written to the --output path."
+    )]
+    combine: bool,
+
+    #[arg(
+        long = "auto-colors",
+        help = "Choose the number of colors automatically, up to 256, rather than using --number-of-colors.",
+        long_help = "Choose the number of colors automatically, up to 256, rather than using --number-of-colors. Palettes with more and more colors are extracted until another color stops making much difference to how closely the palette matches the image. The number chosen is included in JSON output as extracted_colors.",
+        conflicts_with_all = ["number_of_colors", "high_precision", "combine"]
+    )]
+    auto_colors: bool,
+
+    #[arg(
+        long = "average",
+        help = "Output only the mean color of the image, which is much quicker than extracting a palette.",
+        long_help = "Output only the mean color of the image: the average of each channel across every pixel. This is much quicker than extracting a palette, but note it's not the dominant (most common) color, and may not appear in the image at all; an image that's half black and half white averages to mid-gray.",
+        conflicts_with_all = ["number_of_colors", "auto_colors", "high_precision", "pad_to_count"]
+    )]
+    average: bool,
+
+    #[arg(
+        long = "high-precision",
+        help = "Cluster 16-bit images at full precision rather than truncating them to 8 bits first.",
+        long_help = "Cluster 16-bit images (e.g. high-bit-depth TIFFs and PNGs) at full precision rather than truncating them to 8 bits first; only the final swatches are 8-bit. Always uses K-Means in RGB.",
+        conflicts_with_all = ["sample_size", "exclude_color", "center_weight", "saliency_weight", "all_frames", "all_pages", "combine", "ignore_border"]
+    )]
+    high_precision: bool,
+
+    #[arg(
+        long = "linear",
+        help = "Average and cluster colors in linear light, rather than their gamma-encoded sRGB values, which darkens mixes.",
+        long_help = "Average and cluster colors in linear light, rather than their gamma-encoded sRGB values: the pixels are linearised before extraction (or --average), and the colors extracted are gamma-encoded again. Mixes then come out as the eye sees them; without it they're too dark, e.g. half black and half white averages to #808080 rather than #bcbcbc. The linearised pixels are stored at 8 bits, so the darkest shades are merged together. Can't be used with --color-space lab, which is already computed from linear light.",
+        conflicts_with = "high_precision"
+    )]
+    linear: bool,
+
+    #[arg(
+        long = "timeout",
+        value_name = "SECS",
+        help = "Give up fetching an image from an http(s):// URL after this many seconds (30 by default).",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    timeout: Option<u64>,
+
+    #[arg(
+        long = "timeout-per-image",
+        value_name = "SECS",
+        help = "Give up extracting the palette of an image after this many seconds (e.g. 2.5), reporting it as failed and moving on.",
+        long_help = "Give up extracting the palette of an image after this many seconds (e.g. 2.5), reporting it as failed and moving on to the next image. Extraction can't be interrupted, so an abandoned extraction carries on in the background, using a core, until it finishes or colorbuddy exits.",
+        value_parser = seconds_parser
+    )]
+    timeout_per_image: Option<Duration>,
+
+    #[arg(
+        long = "retry",
+        value_name = "N",
+        help = "Retry reading an image or writing its output up to N times after a transient IO error (e.g. on a flaky network mount).",
+        default_value_t = 0
+    )]
+    retry: u32,
+
+    #[arg(
+        long = "strict",
+        help = "Stop at the first image that can't be processed, rather than carrying on with the rest."
+    )]
+    strict: bool,
+
+    #[arg(
+        long = "error-format",
+        help = "How to report images that couldn't be processed on stderr.",
+        long_help = "How to report images that couldn't be processed on stderr. 'json' writes one {\"file\": ..., \"error\": ...} object per line.",
+        default_value_t = ErrorFormat::Text
+    )]
+    error_format: ErrorFormat,
+
+    #[arg(
+        long = "tui",
+        help = "Tune the palette interactively in the terminal, then write the output from there.",
+        long_help = "Tune the palette interactively in the terminal. Up and down change the number of colors, left and right the quantisation method, and the palette is re-extracted as they change. Press w (or Enter) to write the output with the current settings, and q (or Esc) to quit. Takes a single image.",
+        conflicts_with_all = ["combine", "stdout", "auto_colors", "average", "high_precision"]
+    )]
+    tui: bool,
+
+    #[arg(short = 'v',
+          long = "verbose",
+          action = ArgAction::Count,
+          help = "Log timings for each stage to stderr. Repeat (-vv) for more detail. RUST_LOG (e.g. RUST_LOG=debug or RUST_LOG=error) takes precedence.")]
+    verbose: u8,
+
+    #[arg(
+        long = "from-file",
+        value_name = "LIST",
+        help = "Also process the images listed in this file, one per line.",
+        long_help = "Also process the images listed in this file, one path (or URL) per line, after any given on the command line. Blank lines and lines starting with # are skipped. Relative paths are relative to the directory the list is in, not the current directory."
+    )]
+    from_file: Option<PathBuf>,
+
     #[arg(help = "Any number of images to process.")]
     images: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Whether `quantisation_method` was picked by `--quantisation-method auto`, rather than given.
+    #[arg(skip)]
+    auto_method: bool,
+
+    /// `--palette-height`, parsed by `resolve_palette_height` once `--decimal-comma` is known.
+    #[arg(skip = PaletteHeight::Absolute(256))]
+    palette_height: PaletteHeight,
 }
 
-fn main() -> Result<()> {
-    let matches = Args::parse();
+fn main() -> Result<ExitCode> {
+    let arg_matches = Args::command().get_matches();
+    let mut matches = Args::from_arg_matches(&arg_matches).unwrap_or_else(|e| e.exit());
+    init_logging(matches.verbose);
+
+    // An explicit --output-type wins; otherwise the --output extension decides
+    if arg_matches.value_source("output_type") != Some(ValueSource::CommandLine) {
+        if let Some(output_type) = matches.output.as_deref().and_then(output_type_from_path) {
+            matches.output_type = output_type;
+        }
+    }
+    let mut outcomes: Vec<ProcessOutcome> = Vec::new();
+
+    if let Err(e) = resolve_palette_height(&mut matches) {
+        Args::command()
+            .error(
+                ErrorKind::ValueValidation,
+                format!(
+                    "invalid value '{}' for '--palette-height <PALETTE_HEIGHT>': {e}",
+                    matches.palette_height_arg
+                ),
+            )
+            .exit();
+    }
+
+    if matches.include_color.len() > matches.number_of_colors {
+        return Err(anyhow!(
+            "--include-color was given {} times, but only {} colors were asked for with --number-of-colors",
+            matches.include_color.len(),
+            matches.number_of_colors
+        ));
+    }
+    if matches.seed_palette.len() > matches.number_of_colors {
+        return Err(anyhow!(
+            "--seed-palette has {} colors, but only {} were asked for with --number-of-colors",
+            matches.seed_palette.len(),
+            matches.number_of_colors
+        ));
+    }
+
+    if let (Some(min), Some(max)) = (matches.min_luminance, matches.max_luminance) {
+        if min > max {
+            return Err(anyhow!(
+                "--min-luminance ({min}) is above --max-luminance ({max}), so every color would be dropped"
+            ));
+        }
+    }
+
+    if let Some(list_file) = &matches.from_file {
+        let listed = read_input_list(list_file)?;
+        matches.images.extend(listed);
+    }
+
+    let (images, duplicates) = deduplicate_inputs(&matches.images);
+    for duplicate in &duplicates {
+        info!("{}: skipped, as it was already given", duplicate.display());
+    }
+    matches.images = images;
+
+    // CIELAB is computed from linear light, so linearising first would do it twice
+    if matches.linear && matches.color_space == ColorSpace::Lab {
+        return Err(anyhow!(
+            "--linear can't be used with --color-space lab, which already measures colors in linear light"
+        ));
+    }
+
+    if matches.ndjson && matches.output_type != OutputType::Json {
+        return Err(anyhow!("--ndjson only applies to the json output type"));
+    }
+
+    if matches.stdout {
+        if !matches!(
+            matches.output_type,
+            OutputType::OriginalImage | OutputType::StandalonePalette | OutputType::Posterize
+        ) {
+            return Err(anyhow!(
+                "--stdout only applies to the original-image, standalone-palette and posterize output types"
+            ));
+        }
+        if matches.images.len() > 1 && !matches.combine {
+            return Err(anyhow!("--stdout can only be used with a single image"));
+        }
+    }
+
+    match &matches.command {
+        Some(Command::Recolor(recolor_args)) => {
+            recolor(recolor_args)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Bench(bench_args)) => {
+            run_bench(bench_args)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        None => {}
+    }
+
+    if matches.tui {
+        let [image] = matches.images.as_slice() else {
+            return Err(anyhow!("--tui takes a single image"));
+        };
+        tui(image, &matches)?;
+        return Ok(ExitCode::SUCCESS);
+    }
 
-    for image in &matches.images {
-        let output_file_name =
-            output_file_name(image, matches.output.as_ref(), matches.output_type);
+    let mut written_outputs: Vec<PathBuf> = Vec::new();
+    let mut used_output_names: HashSet<PathBuf> = HashSet::new();
+    let date = today();
+    let method = matches.quantisation_method.to_string();
+    let template = matches
+        .output_template
+        .as_deref()
+        .map(|template| TemplateFields {
+            template,
+            method: &method,
+            count: matches.number_of_colors,
+            date: &date,
+        });
+    if matches.combine {
+        combine_images(&matches, template.as_ref(), &mut outcomes)?;
+    } else {
+        for image in &matches.images {
+            let mut output_file_name = output_file_name(
+                image,
+                matches.output.as_ref(),
+                matches.output_type,
+                template.as_ref(),
+            );
+            if matches.unique_names {
+                output_file_name = unique_output_path(output_file_name, &mut used_output_names);
+            }
+
+            let result = process_image(image, &matches, &output_file_name);
+            // Only outputs written by this run are previewed, not any --no-clobber kept
+            let written = result.as_ref().is_ok_and(|processed| processed.written);
+            let result = result.map(|processed| processed.palette);
+            match &result {
+                // Written directly, not logged, so no logging filter can hide a failure
+                Err(e) => eprintln!("{}", format_error(image, e, matches.error_format)),
+                Ok(_) if written && !matches.stdout => written_outputs.push(output_file_name),
+                Ok(_) => {}
+            }
 
-        process_image(
-            image,
-            matches.number_of_colors,
-            matches.quantisation_method,
-            matches.palette_height,
-            matches.palette_width,
+            outcomes.push(ProcessOutcome::new(image, &result));
+            if result.is_err() && matches.strict {
+                break;
+            }
+        }
+    }
+
+    if matches.preview {
+        let preview = preview_output(
+            &written_outputs,
             matches.output_type,
-            &output_file_name,
+            is_headless(),
+            |output| open::that(output),
         );
+        if let Err(warning) = preview {
+            warn!("{warning}");
+        }
     }
 
-    Ok(())
+    if matches.summary {
+        eprint!("{}", format_summary(&outcomes));
+    }
+
+    // Every image is processed (unless --strict), but scripts still need to know if any failed
+    if outcomes.iter().any(|o| !o.is_success()) {
+        return Ok(ExitCode::FAILURE);
+    }
+
+    Ok(ExitCode::SUCCESS)
 }
 
 /**
- * Internally we deal with a Vector<Color> (`Color` provided by the exoquant crate).
- * This helper function converts a Vector of MCQ `ColorNode`s into a Vector of exoquant `Color`s.
+ * Tunes the palette for an image interactively, writing the output for whichever settings the
+ * user picks.
+ *
+ * [&Path] The image to tune the palette for.
+ * [&Args] The options the tool was invoked with, which the TUI starts from.
  */
-fn mcq_color_nodes_to_exoquant_colors(mcq_color_nodes: Vec<ColorNode>) -> Vec<Color> {
-    mcq_color_nodes
-        .iter()
-        .map(|c| Color {
-            r: c.red,
-            g: c.grn,
-            b: c.blu,
-            a: 0xff,
-        })
-        .collect()
+fn tui(file: &Path, args: &Args) -> Result<()> {
+    let dynamic_image = load_image(file, args)?;
+    let source_color_type = dynamic_image.color();
+    let input_image = dynamic_image.to_rgb8();
+    let extraction_image = extraction_image(file, &input_image, args)?;
+    let state = TuiState::new(
+        args.number_of_colors,
+        args.quantisation_method,
+        args.color_space,
+    );
+
+    run_tui(
+        file,
+        extraction_image.as_ref().unwrap_or(&input_image),
+        state,
+        |state| {
+            let mut args = args.clone();
+            args.number_of_colors = state.number_of_colors;
+            args.quantisation_method = state.quantisation_method;
+            let date = today();
+            let method = args.quantisation_method.to_string();
+            let template = args
+                .output_template
+                .as_deref()
+                .map(|template| TemplateFields {
+                    template,
+                    method: &method,
+                    count: args.number_of_colors,
+                    date: &date,
+                });
+            let output_file_name = output_file_name(
+                file,
+                args.output.as_ref(),
+                args.output_type,
+                template.as_ref(),
+            );
+            write_output(
+                file,
+                &input_image,
+                Some(source_color_type),
+                &state.palette,
+                &args,
+                &output_file_name,
+            )?;
+            Ok(output_file_name)
+        },
+    )
 }
 
 /**
- * This function abstracts the extraction of the Vector of `Color`s depending on the chosen
- * quantisation method.
+ * Remaps an image to a palette previously saved as JSON, and saves the result.
  *
- * [&RgbImage] The image to be processed.
- * [usize] The number of colors required for the palette.
- * [QuantisationMethod] The quantisation method to be used.
- **/
-fn extract_palette(
-    input_image: &RgbImage,
-    number_of_colors: usize,
-    quantisation_method: QuantisationMethod,
-) -> Vec<Color> {
-    match quantisation_method {
-        QuantisationMethod::MedianCut => {
-            let data = input_image.clone().into_vec();
-            let mcq =
-                MMCQ::from_pixels_u8_rgba(data.as_slice(), number_of_colors.try_into().unwrap());
+ * [&RecolorArgs] The options the recolor subcommand was invoked with.
+ */
+fn recolor(args: &RecolorArgs) -> Result<()> {
+    let input_image = image::open(&args.image)
+        .map_err(|_| anyhow!("Error opening image: {}", args.image.display()))?
+        .to_rgb8();
+    let color_palette = read_palette(&args.palette)?;
+
+    let recolored_image = if args.dither {
+        dither(&input_image, &color_palette)
+    } else {
+        recolor_image(&input_image, &color_palette)
+    };
+
+    let output_file_name = args.output.clone().unwrap_or_else(|| {
+        let stem = args.image.file_stem().unwrap().to_str().unwrap();
+        let extension = args
+            .image
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("png");
+        args.image
+            .with_file_name(format!("{stem}_recolored.{extension}"))
+    });
+
+    recolored_image
+        .save(&output_file_name)
+        .with_context(|| format!("Failed to save: {}", output_file_name.display()))
+}
+
+/**
+ * Reads a palette file: a GIMP palette when it has a `.gpl` extension, and otherwise a palette
+ * saved by colorbuddy as JSON.
+ *
+ * [&Path] The palette file.
+ */
+fn read_palette(file: &Path) -> Result<Vec<Color>> {
+    match file.extension().and_then(|e| e.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("gpl") => read_gpl_palette(file),
+        _ => read_json_palette(file),
+    }
+}
+
+/**
+ * Times every quantisation method on an image, printing how long each took and the palette it
+ * extracted.
+ *
+ * [&BenchArgs] The options the bench subcommand was invoked with.
+ */
+fn run_bench(args: &BenchArgs) -> Result<()> {
+    let input_image = image::open(&args.image)
+        .map_err(|_| anyhow!("Error opening image: {}", args.image.display()))?
+        .to_rgb8();
+
+    let results = bench(&input_image, args.number_of_colors, args.color_space);
+    if args.json {
+        println!("{}", format_bench_json(&results));
+    } else {
+        print!("{}", format_bench_table(&results));
+    }
+
+    Ok(())
+}
 
-            mcq_color_nodes_to_exoquant_colors(mcq.get_quantized_colors().to_vec())
+/**
+ * Builds a single palette from the pixels of every input image, rather than one palette per
+ * image, and writes one output to the `--output` path.
+ *
+ * Images that fail to load are reported and skipped (with `--strict`, the first one stops the
+ * run). Percentage palette heights and the default standalone palette width are taken from the
+ * first image that loads.
+ *
+ * [&Args] The options the tool was invoked with.
+ * [Option<&TemplateFields>] The `--output-template` to name the output after, if any.
+ * [&mut Vec<ProcessOutcome>] The outcomes of loading each image are appended here.
+ */
+fn combine_images(
+    args: &Args,
+    template: Option<&TemplateFields>,
+    outcomes: &mut Vec<ProcessOutcome>,
+) -> Result<()> {
+    let output = args
+        .output
+        .as_ref()
+        .ok_or_else(|| anyhow!("--combine requires an --output path"))?;
+    if matches!(
+        args.output_type,
+        OutputType::OriginalImage | OutputType::Posterize
+    ) {
+        return Err(anyhow!(
+            "--combine can't produce an {} output; choose another --output-type",
+            args.output_type
+        ));
+    }
+
+    let mut first_image: Option<RgbImage> = None;
+    let mut combined_pixels: Vec<u8> = Vec::new();
+
+    for image in &args.images {
+        let result = load_image(image, args).and_then(|dynamic_image| {
+            let input_image = dynamic_image.to_rgb8();
+            let extraction_image = extraction_image(image, &input_image, args)?;
+            combined_pixels
+                .extend_from_slice(extraction_image.as_ref().unwrap_or(&input_image).as_raw());
+            first_image.get_or_insert(input_image);
+            Ok(())
+        });
+
+        if let Err(e) = &result {
+            eprintln!("{}", format_error(image, e, args.error_format));
         }
-        QuantisationMethod::KMeans => {
-            let histogram: Histogram = input_image
-                .pixels()
-                .map(|p| Color {
-                    r: p[0],
-                    g: p[1],
-                    b: p[2],
-                    a: 0xff,
-                })
-                .collect();
-            generate_palette(
-                &histogram,
-                &SimpleColorSpace::default(),
-                &optimizer::KMeans,
-                number_of_colors,
-            )
+        let failed = result.is_err();
+        outcomes.push(ProcessOutcome::new(image, &result.map(|_| Vec::new())));
+        if failed && args.strict {
+            return Err(anyhow!("Stopped after {} failed to load", image.display()));
         }
     }
+
+    let first_image = first_image.ok_or_else(|| anyhow!("No images could be loaded to combine"))?;
+
+    // All of the pixels are laid out in a single row; only the histogram matters for extraction.
+    let combined_image = RgbImage::from_raw((combined_pixels.len() / 3) as u32, 1, combined_pixels)
+        .expect("combined pixel buffer holds whole RGB pixels");
+
+    let output_file_name = if is_output_directory(output) {
+        output_file_name(
+            Path::new("combined"),
+            Some(output),
+            args.output_type,
+            template,
+        )
+    } else {
+        output.clone()
+    };
+
+    let label = Path::new("combined");
+    let color_palette = match &args.palette_in {
+        Some(palette_file) => read_palette(palette_file)?,
+        None => extract(label, &combined_image, None, args),
+    };
+    if args.dry_run {
+        print_dry_run(label, &color_palette, args, &output_file_name);
+        return Ok(());
+    }
+    write_output(
+        label,
+        &first_image,
+        None,
+        &color_palette,
+        args,
+        &output_file_name,
+    )?;
+    Ok(())
 }
 
 /**
- * This is the meat of the tool. Opens the image, gets the palette of colors, and outputs the
- * requested artifact (either a copy of the original image with the palette along the bottom, or a
- * JSON file with the palette details.)
+ * Opens an image. Callers convert it to 8-bit RGB (or 16-bit, for `--high-precision`).
  *
- * [&PathBuf] file, the image to process.
- * [usize] Number of colors to pick for the palette.
- * [QuantisationMethod] The quantisation method to use.
- * [PaletteHeight] The height of the palette.
- * [OutputType] The type of output requested.
- * [&PathBuf] The output file name.
+ * [&Path] The image to open.
+ * [&Args] The options the tool was invoked with.
  */
-fn process_image(
-    file: &PathBuf,
-    number_of_colors: usize,
-    quantisation_method: QuantisationMethod,
-    palette_height: PaletteHeight,
-    palette_width: Option<u32>,
-    output_type: OutputType,
-    output_file_name: &PathBuf,
-) {
-    let dynamic_image: DynamicImage;
+fn load_image(file: &Path, args: &Args) -> Result<DynamicImage> {
+    let load_start = Instant::now();
+    if is_url(file) {
+        return load_url_image(file, args, load_start);
+    }
+    if is_pdf(file) {
+        let page = retry(args.retry, file, || render_pdf_pages(file, Some(args.page)))?.remove(0);
+        info!(
+            "{}: rendered page {} in {:.2?}",
+            file.display(),
+            args.page,
+            load_start.elapsed()
+        );
+        return Ok(DynamicImage::ImageRgb8(page));
+    }
+
+    check_image_dimensions(file, args.max_dimension)?;
+    let input_image: DynamicImage =
+        retry(args.retry, file, || Ok(image::open(file)?)).map_err(|e| {
+            match (e.downcast_ref::<ImageError>(), file.extension()) {
+                // Name the format, rather than leaving the user to wonder what's wrong with the
+                // file
+                (Some(ImageError::Unsupported(_)), Some(extension)) => {
+                    ColorBuddyError::UnsupportedFormat(extension.to_string_lossy().into_owned())
+                        .into()
+                }
+                _ => anyhow!("Error opening image: {}", file.to_str().unwrap()),
+            }
+        })?;
+
+    info!("{}: loaded in {:.2?}", file.display(), load_start.elapsed());
+    debug!(
+        "{}: dimensions {}x{}",
+        file.display(),
+        input_image.width(),
+        input_image.height()
+    );
+
+    Ok(input_image)
+}
+
+/**
+ * Downloads and decodes an image given as an `http(s)://` URL, bounded by `--timeout`. The
+ * dimensions are checked against `--max-dimension` from the downloaded header, before the pixels
+ * are decoded.
+ *
+ * [&Path] The URL, as given on the command line.
+ * [&Args] The options the tool was invoked with.
+ * [Instant] When loading started, for logging.
+ */
+fn load_url_image(file: &Path, args: &Args, load_start: Instant) -> Result<DynamicImage> {
+    let url = file.to_string_lossy();
+    let timeout = args.timeout.map(Duration::from_secs);
+    let bytes = retry(args.retry, file, || Ok(fetch_image(&url, timeout)?))?;
+
+    let reader = image::io::Reader::new(io::Cursor::new(&bytes)).with_guessed_format()?;
+    if let Ok((width, height)) = reader.into_dimensions() {
+        check_dimensions(width, height, args.max_dimension)?;
+    }
+
+    let input_image =
+        image::load_from_memory(&bytes).map_err(|_| anyhow!("Error decoding image: {url}"))?;
+
+    info!(
+        "{url}: fetched ({} bytes) and loaded in {:.2?}",
+        bytes.len(),
+        load_start.elapsed()
+    );
+    debug!(
+        "{url}: dimensions {}x{}",
+        input_image.width(),
+        input_image.height()
+    );
+
+    Ok(input_image)
+}
 
-    if let Ok(img) = image::open(file) {
-        dynamic_image = img;
+/**
+ * Works out which pixels the palette should be extracted from, when that differs from the loaded
+ * image: the image without its border (with `--ignore-border`), every frame of an animated GIF
+ * (with `--all-frames`), a downsampled copy (with
+ * `--sample-size`), the pixels weighted towards the center (with `--center-weight`), and/or only
+ * the pixels not matching an `--exclude-color`. The output is always built from the loaded,
+ * full-size image.
+ *
+ * Returns `None` when the palette should be extracted from the loaded image as-is.
+ *
+ * [&Path] The image file.
+ * [&RgbImage] The loaded image.
+ * [&Args] The options the tool was invoked with.
+ */
+fn extraction_image(file: &Path, input_image: &RgbImage, args: &Args) -> Result<Option<RgbImage>> {
+    let cropped_image = crop_border(input_image, args.ignore_border)
+        .with_context(|| format!("Invalid --ignore-border for {}", file.display()))?;
+    let input_image = cropped_image.as_ref().unwrap_or(input_image);
+
+    // Every frame of an animated GIF, or every page of a PDF
+    let all_frames_image = match ImageFormat::from_path(file) {
+        Ok(ImageFormat::Gif) if args.all_frames && !is_url(file) => {
+            Some(stack_frames(&read_gif_frames(file)?))
+        }
+        _ if args.all_pages && is_pdf(file) => Some(stack_frames(&render_pdf_pages(file, None)?)),
+        _ => None,
+    };
+    let base_image = all_frames_image.as_ref().unwrap_or(input_image);
+
+    let sampled_image = args
+        .sample_size
+        .and_then(|sample_size| downsample(base_image, sample_size, args.downscale_filter));
+    let base_image = sampled_image.as_ref().unwrap_or(base_image);
+
+    let weighted_image = if args.saliency_weight {
+        saliency_weighted(base_image)
     } else {
-        eprintln!("Error opening image: {}", file.to_str().unwrap());
-        return;
+        center_weighted(base_image, args.center_weight)
     };
+    let base_image = weighted_image.as_ref().unwrap_or(base_image);
+
+    let filtered_image = exclude_colors(base_image, &args.exclude_color, args.exclude_tolerance);
+    if filtered_image.as_ref().is_some_and(|f| f.width() == 0) {
+        return Err(anyhow!(
+            "Every pixel in {} matched an --exclude-color",
+            file.display()
+        ));
+    }
 
-    let input_image = dynamic_image.to_rgb8();
-    let (input_image_width, input_image_height) = input_image.dimensions();
+    Ok(filtered_image
+        .or(weighted_image)
+        .or(sampled_image)
+        .or(all_frames_image)
+        .or(cropped_image))
+}
+
+/**
+ * Extracts the palette using the requested method, logging how long it took when verbose.
+ *
+ * [&Path] The image the palette is for, used when logging.
+ * [&RgbImage] The pixels to extract the palette from.
+ * [Option<&Rgb16Image>] The same pixels at 16 bits per channel, to extract from instead (with
+ * `--high-precision`).
+ * [&Args] The options the tool was invoked with.
+ */
+fn extract(
+    file: &Path,
+    extraction_image: &RgbImage,
+    high_precision_image: Option<&Rgb16Image>,
+    args: &Args,
+) -> Vec<Color> {
+    let extraction_start = Instant::now();
+    if args.average {
+        let color = if args.linear {
+            mean_color_linear(extraction_image)
+        } else {
+            mean_color(extraction_image)
+        };
+        info!(
+            "{}: averaged to {} in {:.2?}",
+            file.display(),
+            rgb_to_hex(color.r, color.g, color.b),
+            extraction_start.elapsed()
+        );
+        return vec![color];
+    }
+
+    // With --linear, the palette is extracted from (and seeded with) linearised colors, and
+    // gamma-encoded again afterwards
+    let linear_image = args.linear.then(|| linearize_image(extraction_image));
+    let extraction_image = linear_image.as_ref().unwrap_or(extraction_image);
+    let seed_palette: Vec<[u8; 3]> = if args.linear {
+        args.seed_palette
+            .iter()
+            .map(|&seed| linearize(seed))
+            .collect()
+    } else {
+        args.seed_palette.clone()
+    };
 
-    let total_height = match (output_type, palette_height) {
-        (OutputType::OriginalImage, PaletteHeight::Absolute(a)) => a + input_image_height,
-        (OutputType::OriginalImage, PaletteHeight::Percentage(a)) => {
-            input_image_height + (a / 100.0 * input_image_height as f32).round() as u32
+    let mut extraction = match high_precision_image {
+        Some(image) => extract_palette_high_precision(image, args.number_of_colors),
+        None if args.auto_colors => {
+            let colors = extract_palette_auto(
+                extraction_image,
+                args.quantisation_method,
+                args.color_space,
+                MAX_COLORS,
+            );
+            ExtractionResult {
+                counts: count_pixels(extraction_image, &colors),
+                colors,
+                method: args.quantisation_method,
+            }
         }
-        (OutputType::StandalonePalette, PaletteHeight::Absolute(a)) => a,
-        (OutputType::StandalonePalette, PaletteHeight::Percentage(a)) => {
-            (a / 100.0 * input_image_height as f32).round() as u32
+        None if args.quantisation_method == QuantisationMethod::KMeans
+            && (args.kmeans_iterations.is_some() || !args.seed_palette.is_empty()) =>
+        {
+            let colors = extract_palette_kmeans(
+                extraction_image,
+                args.number_of_colors,
+                args.color_space,
+                args.kmeans_iterations.unwrap_or(MAX_ITERATIONS),
+                &seed_palette,
+            );
+            ExtractionResult {
+                counts: count_pixels(extraction_image, &colors),
+                colors,
+                method: args.quantisation_method,
+            }
         }
-        (OutputType::Json, _) => input_image_height,
+        None => extract_palette_detailed(
+            extraction_image,
+            args.number_of_colors,
+            args.quantisation_method,
+            args.color_space,
+        ),
     };
 
-    let color_palette: Vec<Color> =
-        extract_palette(&input_image, number_of_colors, quantisation_method);
+    if args.linear {
+        extraction.colors = delinearize_palette(extraction.colors);
+    }
 
-    /*
-     *  Output to the original image: */
-    if OutputType::OriginalImage == output_type {
-        // Create an image buffer big enough to hold the output image
-        let mut imgbuf = image::ImageBuffer::new(input_image_width, total_height);
+    info!(
+        "{}: extracted {} colors using {} in {:.2?}",
+        file.display(),
+        extraction.colors.len(),
+        extraction.method,
+        extraction_start.elapsed()
+    );
+    if log_enabled!(Level::Debug) {
+        for (color, count) in extraction.colors.iter().zip(&extraction.counts) {
+            debug!(
+                "{}: {} covers {count} pixels",
+                file.display(),
+                rgb_to_hex(color.r, color.g, color.b)
+            );
+        }
+    }
 
-        // The width of each color in the palette strip
-        let color_width = input_image_width / number_of_colors as u32;
+    let mut color_palette = extraction.colors;
 
-        // This clones the image we're processing into the output buffer
-        for x in 0..input_image_width {
-            for y in 0..input_image_height {
-                imgbuf.put_pixel(x, y, *input_image.get_pixel(x, y));
-            }
+    if let Some(min_saturation) = args.min_saturation {
+        color_palette = filter_by_saturation(color_palette, min_saturation);
+    }
+
+    if args.min_luminance.is_some() || args.max_luminance.is_some() {
+        color_palette = filter_by_luminance(color_palette, args.min_luminance, args.max_luminance);
+    }
+
+    if let Some(factor) = args.chroma_boost {
+        color_palette = boost_chroma(color_palette, factor);
+    }
+
+    if let Some(strategy) = args.pad_to_count {
+        color_palette = pad_palette(color_palette, args.number_of_colors, strategy);
+    }
+
+    color_palette
+}
+
+/**
+ * Extracts the palette like `extract`, but on a worker thread, giving up once `--timeout-per-image`
+ * has passed. The worker can't be interrupted, so when it's abandoned it carries on until it
+ * finishes (or the process exits), and its palette is dropped.
+ *
+ * [&Path] The image the palette is for, used when logging.
+ * [&RgbImage] The pixels to extract the palette from.
+ * [Option<&Rgb16Image>] The same pixels at 16 bits per channel, with `--high-precision`.
+ * [&Args] The options the tool was invoked with.
+ * [Duration] How long to wait for the palette.
+ */
+fn extract_with_timeout(
+    file: &Path,
+    extraction_image: &RgbImage,
+    high_precision_image: Option<&Rgb16Image>,
+    args: &Args,
+    timeout: Duration,
+) -> Result<Vec<Color>> {
+    let (sender, receiver) = mpsc::channel();
+    let worker_file = file.to_path_buf();
+    let worker_image = extraction_image.clone();
+    let worker_high_precision_image = high_precision_image.cloned();
+    let worker_args = args.clone();
+    thread::spawn(move || {
+        let color_palette = extract(
+            &worker_file,
+            &worker_image,
+            worker_high_precision_image.as_ref(),
+            &worker_args,
+        );
+        // Nobody is listening any more if extraction timed out
+        let _ = sender.send(color_palette);
+    });
+
+    receiver.recv_timeout(timeout).map_err(|e| match e {
+        RecvTimeoutError::Timeout => ColorBuddyError::ExtractionTimedOut(timeout).into(),
+        RecvTimeoutError::Disconnected => {
+            anyhow!("Error extracting palette: {}", file.display())
         }
+    })
+}
+
+/**
+ * The key a palette is cached under with `--cache-dir`: a hash of the pixels it's extracted from
+ * (after any cropping, sampling, weighting and excluding, so the options for those are covered)
+ * and every option `extract` uses. The version is included too, in case extraction changes.
+ *
+ * [&RgbImage] The pixels the palette is extracted from.
+ * [Option<&Rgb16Image>] The same pixels at 16 bits per channel, with `--high-precision`.
+ * [&Args] The options the tool was invoked with.
+ */
+fn extraction_cache_key(
+    extraction_image: &RgbImage,
+    high_precision_image: Option<&Rgb16Image>,
+    args: &Args,
+) -> String {
+    let dimensions = format!("{}x{}", extraction_image.width(), extraction_image.height());
+    let high_precision_pixels: Vec<u8> = high_precision_image
+        .map(|image| {
+            image
+                .as_raw()
+                .iter()
+                .flat_map(|c| c.to_le_bytes())
+                .collect()
+        })
+        .unwrap_or_default();
+    let options = format!(
+        "{} {:?} {} {:?} {:?} {:?} {} {} {} {:?} {:?} {:?} {:?} {:?}",
+        env!("CARGO_PKG_VERSION"),
+        args.quantisation_method,
+        args.number_of_colors,
+        args.color_space,
+        args.kmeans_iterations,
+        args.seed_palette,
+        args.auto_colors,
+        args.average,
+        args.linear,
+        args.min_saturation,
+        args.min_luminance,
+        args.max_luminance,
+        args.chroma_boost,
+        args.pad_to_count,
+    );
+
+    palette_cache_key(&[
+        dimensions.as_bytes(),
+        extraction_image.as_raw(),
+        &high_precision_pixels,
+        options.as_bytes(),
+    ])
+}
+
+/**
+ * What processing an image produced.
+ */
+struct ProcessedImage {
+    /// The extracted palette, in the `--sort` order.
+    palette: Vec<Color>,
+    /// Whether the output was written; not for a `--dry-run`, or when `--no-clobber` skipped it.
+    written: bool,
+}
+
+/**
+ * This is the meat of the tool. Opens the image, gets the palette of colors, and outputs the
+ * requested artifact (either a copy of the original image with the palette along the bottom, or a
+ * JSON file with the palette details.)
+ *
+ * [&Path] file, the image to process.
+ * [&Args] The options the tool was invoked with.
+ * [&Path] The output file name.
+ *
+ * Returns the extracted palette and whether the output was written, or an error describing why the
+ * image couldn't be processed.
+ */
+fn process_image(file: &Path, args: &Args, output_file_name: &Path) -> Result<ProcessedImage> {
+    let dynamic_image = load_image(file, args)?;
+    let input_image = dynamic_image.to_rgb8();
+    let extraction_image = extraction_image(file, &input_image, args)?;
+    if let Some(extraction_image) = &extraction_image {
+        debug!(
+            "{}: extracting from {} prepared pixels rather than the loaded image",
+            file.display(),
+            extraction_image.width() * extraction_image.height()
+        );
+    }
+    let high_precision_image = args.high_precision.then(|| dynamic_image.to_rgb16());
+
+    // Everything downstream (the cache, the JSON and the PNG metadata) sees the method picked
+    let auto_args;
+    let args = if args.quantisation_method == QuantisationMethod::Auto {
+        let method = choose_method(extraction_image.as_ref().unwrap_or(&input_image));
+        info!(
+            "{}: picked {method} for --quantisation-method auto",
+            file.display()
+        );
+        auto_args = Args {
+            quantisation_method: method,
+            auto_method: true,
+            ..args.clone()
+        };
+        &auto_args
+    } else {
+        args
+    };
 
-        for y in (input_image_height)..(total_height) {
-            for (x0, q) in color_palette.iter().enumerate().take(number_of_colors) {
-                let x1 = x0 as u32 * color_width;
-                for x2 in 0..color_width {
-                    imgbuf.put_pixel(x1 + x2, y, image::Rgb([q.r, q.g, q.b]));
+    let cache_key = args
+        .cache_dir
+        .as_ref()
+        .filter(|_| args.palette_in.is_none())
+        .map(|_| {
+            extraction_cache_key(
+                extraction_image.as_ref().unwrap_or(&input_image),
+                high_precision_image.as_ref(),
+                args,
+            )
+        });
+    let cached_palette = args
+        .cache_dir
+        .as_deref()
+        .zip(cache_key.as_deref())
+        .and_then(|(cache_dir, key)| read_cached_palette(cache_dir, key));
+    let color_palette = match (&args.palette_in, cached_palette) {
+        (Some(palette_file), _) => {
+            let color_palette = read_palette(palette_file)?;
+            info!(
+                "{}: using the {} colors in {} rather than extracting",
+                file.display(),
+                color_palette.len(),
+                palette_file.display()
+            );
+            color_palette
+        }
+        (None, Some(cached_palette)) => {
+            info!("{}: reusing the cached palette", file.display());
+            cached_palette
+        }
+        (None, None) => {
+            let extraction_image = extraction_image.as_ref().unwrap_or(&input_image);
+            let color_palette = match args.timeout_per_image {
+                Some(timeout) => extract_with_timeout(
+                    file,
+                    extraction_image,
+                    high_precision_image.as_ref(),
+                    args,
+                    timeout,
+                )?,
+                None => extract(file, extraction_image, high_precision_image.as_ref(), args),
+            };
+            let cache = args
+                .cache_dir
+                .as_deref()
+                .zip(cache_key.as_deref())
+                .filter(|_| !args.dry_run);
+            if let Some((cache_dir, key)) = cache {
+                if let Err(e) = write_cached_palette(cache_dir, key, &color_palette) {
+                    warn!("{}: {e:#}", file.display());
                 }
             }
+            color_palette
         }
+    };
+    if args.strict_count && args.palette_in.is_none() && color_palette.len() < args.number_of_colors
+    {
+        return Err(ColorBuddyError::TooFewColors {
+            extracted: color_palette.len(),
+            requested: args.number_of_colors,
+        }
+        .into());
+    }
+    let color_palette = if args.include_color.is_empty() {
+        color_palette
+    } else {
+        let weights = count_pixels(
+            extraction_image.as_ref().unwrap_or(&input_image),
+            &color_palette,
+        );
+        include_colors(
+            color_palette,
+            &weights,
+            &args.include_color,
+            args.number_of_colors,
+        )
+    };
+    let color_palette = if args.transparent_swatches {
+        with_source_alpha(color_palette, &dynamic_image.to_rgba8())
+    } else {
+        color_palette
+    };
+    if args.dry_run {
+        print_dry_run(file, &color_palette, args, output_file_name);
+        return Ok(ProcessedImage {
+            palette: order_palette(color_palette, args.sort, args),
+            written: false,
+        });
+    }
+    let written = retry(args.retry, file, || {
+        write_output(
+            file,
+            &input_image,
+            Some(dynamic_image.color()),
+            &color_palette,
+            args,
+            output_file_name,
+        )
+    })?;
+
+    Ok(ProcessedImage {
+        palette: order_palette(color_palette, args.sort, args),
+        written,
+    })
+}
+
+/**
+ * The palette height in pixels, working out a percentage `--palette-height` from what it's
+ * relative to: for the original-image output, the edge of the image the strip runs across; for a
+ * standalone palette, the height of the image, or with `--percentage-base width` the palette's own
+ * width.
+ *
+ * [&Args] The options the tool was invoked with.
+ * [(u32, u32)] The width and height of the source image.
+ */
+fn palette_height_pixels(args: &Args, (image_width, image_height): (u32, u32)) -> u32 {
+    let reference_size = match (
+        args.output_type,
+        args.palette_position,
+        args.percentage_base,
+    ) {
+        (OutputType::OriginalImage, PalettePosition::Left | PalettePosition::Right, _) => {
+            image_width
+        }
+        (OutputType::StandalonePalette | OutputType::Svg, _, PercentageBase::Width) => {
+            args.palette_width.unwrap_or(image_width)
+        }
+        _ => image_height,
+    };
+    match args.palette_height {
+        PaletteHeight::Absolute(a) => a,
+        PaletteHeight::Percentage(a) => (a / 100.0 * reference_size as f32).round() as u32,
+    }
+}
+
+/**
+ * Whether the output goes to stdout rather than a file: JSON always does, text and SCSS do unless
+ * an output path was given, and image outputs do with `--stdout`.
+ *
+ * [&Args] The options the tool was invoked with.
+ */
+fn writes_to_stdout(args: &Args) -> bool {
+    args.output_type == OutputType::Json
+        || args.stdout
+        || (matches!(args.output_type, OutputType::Text | OutputType::Scss)
+            && args.output.is_none())
+}
+
+/**
+ * Prints what `--dry-run` would have written for a palette, instead of writing it: a line for the
+ * output and each of its sidecars, with where it would go and how many colors it would have.
+ *
+ * [&Path] The image the palette is for.
+ * [&[Color]] The palette.
+ * [&Args] The options the tool was invoked with.
+ * [&Path] The output file name.
+ */
+fn print_dry_run(file: &Path, color_palette: &[Color], args: &Args, output_file_name: &Path) {
+    let mut outputs = vec![if writes_to_stdout(args) {
+        "stdout".to_owned()
+    } else {
+        output_file_name.display().to_string()
+    }];
+    if args.also_json && args.output_type != OutputType::JsonFile {
+        outputs.push(
+            output_file_name
+                .with_extension("json")
+                .display()
+                .to_string(),
+        );
+    }
+    if args.layout_json {
+        outputs.push(layout_file_name(output_file_name).display().to_string());
+    }
+
+    for output in outputs {
+        println!(
+            "{}: would write {} ({} colors)",
+            file.display(),
+            output,
+            color_palette.len()
+        );
+    }
+}
+
+/**
+ * Puts a palette in the order an output lists it in: by contrast against the
+ * `--sort-contrast-against` color when given, and otherwise by the given sort order.
+ *
+ * [Vec<Color>] The palette.
+ * [SortOrder] The sort order for the output.
+ * [&Args] The options the tool was invoked with.
+ */
+fn order_palette(colors: Vec<Color>, order: SortOrder, args: &Args) -> Vec<Color> {
+    match args.sort_contrast_against {
+        Some(background) => sort_by_contrast(colors, background, args.reverse),
+        None => sort_palette(colors, order, args.reverse),
+    }
+}
+
+/**
+ * Outputs the requested artifact for a palette.
+ *
+ * [&Path] The image the palette is for, used when logging.
+ * [&RgbImage] The source image. Used for the original-image output, and to size percentage
+ * palette heights and the default standalone palette width.
+ * [Option<ColorType>] How the source image's pixels were stored before being converted to RGB,
+ * recorded in JSON output. `None` for combined palettes.
+ * [&[Color]] The palette.
+ * [&Args] The options the tool was invoked with.
+ * [&Path] The output file name.
+ *
+ * Returns whether the output was written, as opposed to skipped because it already exists (with
+ * `--no-clobber`).
+ */
+fn write_output(
+    file: &Path,
+    input_image: &RgbImage,
+    source_color_type: Option<ColorType>,
+    color_palette: &[Color],
+    args: &Args,
+    output_file_name: &Path,
+) -> Result<bool> {
+    // Auto mode's swatches are sized to however many colors it settled on, --average's to its
+    // single color, and --palette-in's to the colors in the file
+    let number_of_colors = if args.auto_colors || args.average || args.palette_in.is_some() {
+        color_palette.len()
+    } else {
+        args.number_of_colors
+    };
+    let output_type = args.output_type;
+    let input_image_width = input_image.width();
+
+    // Sorted here, rather than once after extraction, as JSON and image outputs can be sorted
+    // differently
+    let sort_order = match output_type {
+        OutputType::Json | OutputType::JsonFile => args.sort_json.unwrap_or(args.sort),
+        OutputType::OriginalImage
+        | OutputType::StandalonePalette
+        | OutputType::Svg
+        | OutputType::Clut => args.sort_image.unwrap_or(args.sort),
+        _ => args.sort,
+    };
+    let json_sidecar_palette = args.also_json.then(|| {
+        order_palette(
+            color_palette.to_vec(),
+            args.sort_json.unwrap_or(args.sort),
+            args,
+        )
+    });
+    let color_palette = &order_palette(color_palette.to_vec(), sort_order, args);
+
+    let palette_height = palette_height_pixels(args, input_image.dimensions());
+
+    let wrote_to_stdout = writes_to_stdout(args);
+    let overwrite_policy = if args.no_clobber {
+        OverwritePolicy::NoClobber
+    } else {
+        OverwritePolicy::Overwrite
+    };
+    if !wrote_to_stdout && !prepare_output_path(output_file_name, overwrite_policy) {
+        warn!(
+            "{}: skipped, as {} already exists (see --no-clobber)",
+            file.display(),
+            output_file_name.display()
+        );
+        return Ok(false);
+    }
+
+    if !wrote_to_stdout || args.also_json {
+        ensure_output_directory(output_file_name, args.create_dirs)?;
+    }
 
-        let save_result = imgbuf.save(&output_file_name);
+    let write_start = Instant::now();
 
-        assert!(
-            save_result.is_ok(),
-            "Failed to save: {:?}",
-            output_file_name.canonicalize().unwrap()
+    /*
+     *  Output to the original image: */
+    // Image outputs are encoded in the format their file name implies, even on stdout
+    let image_format = output_format(
+        ImageFormat::from_path(output_file_name).unwrap_or(ImageFormat::Png),
+        args.jpeg_quality,
+    );
+
+    // Only PNGs can store the swatches' alpha
+    let transparent_swatches = args.transparent_swatches && image_format == ImageOutputFormat::Png;
+    if args.transparent_swatches
+        && !transparent_swatches
+        && matches!(
+            output_type,
+            OutputType::OriginalImage | OutputType::StandalonePalette
+        )
+    {
+        warn!(
+            "{}: --transparent-swatches only applies to PNGs, so the swatches are opaque",
+            file.display()
         );
+    }
+
+    let simulated_palette: Option<Vec<Color>> = args.simulate.map(|kind| {
+        color_palette
+            .iter()
+            .map(|color| simulate_cvd(color, kind))
+            .collect()
+    });
+    let simulated_palette = simulated_palette.as_deref();
+
+    // How many pixels each color drawn covers, to size --proportional swatches by
+    let weights = args.proportional.then(|| {
+        count_pixels(
+            input_image,
+            &color_palette[..number_of_colors.min(color_palette.len())],
+        )
+    });
+
+    // Where the swatches were drawn, for --layout-json
+    let mut swatch_layout = None;
+
+    if OutputType::OriginalImage == output_type {
+        let dithered_image = args.dither.then(|| dither(input_image, color_palette));
+        let input_image = dithered_image.as_ref().unwrap_or(input_image);
+        let layout = CompositeLayout {
+            position: args.palette_position,
+            palette_size: palette_height,
+            frame: args.frame,
+            margin: args.palette_margin,
+            background: args.background,
+            title: args.title.as_deref(),
+            weights: weights.as_deref(),
+            fill: args.palette_fill,
+            transparent_swatches,
+            swatch_border: args.swatch_border,
+            swatch_border_color: args.swatch_border_color,
+        };
+        swatch_layout = Some(if args.stdout {
+            write_original_with_palette(
+                input_image,
+                color_palette,
+                simulated_palette,
+                number_of_colors,
+                &layout,
+                image_format,
+                &mut io::stdout().lock(),
+            )?
+        } else {
+            save_original_with_palette(
+                input_image,
+                color_palette,
+                simulated_palette,
+                number_of_colors,
+                &layout,
+                output_file_name,
+                args.jpeg_quality,
+            )?
+        });
     } else if OutputType::StandalonePalette == output_type {
-        let standalone_palette_width = match palette_width {
-            Some(w) => w,
-            None => input_image_width,
+        let (width, height) = match args.aspect {
+            Some(aspect) => aspect_dimensions(args.palette_width, palette_height, aspect),
+            None => (
+                args.palette_width.unwrap_or(input_image_width),
+                palette_height,
+            ),
+        };
+        let layout = StandaloneLayout {
+            width,
+            height,
+            orientation: args.orientation,
+            title: args.title.as_deref(),
+            background: args.background,
+            gradient: args.gradient.then_some(args.gradient_space),
+            weights: weights.as_deref(),
+            fill: args.palette_fill,
+            repeat: args.repeat,
+            rows: args.palette_rows,
+            transparent_swatches,
+            swatch_border: args.swatch_border,
+            swatch_border_color: args.swatch_border_color,
         };
-        let mut imgbuf = image::ImageBuffer::new(standalone_palette_width, total_height);
+        swatch_layout = Some(if args.stdout {
+            write_standalone_palette(
+                color_palette,
+                simulated_palette,
+                number_of_colors,
+                &layout,
+                image_format,
+                &mut io::stdout().lock(),
+            )?
+        } else {
+            save_standalone_palette(
+                color_palette,
+                simulated_palette,
+                number_of_colors,
+                &layout,
+                output_file_name,
+                args.jpeg_quality,
+            )?
+        });
+    } else if OutputType::Posterize == output_type {
+        // The image alone, each pixel remapped to the nearest palette color (or dithered)
+        let posterized_image = if args.dither {
+            dither(input_image, color_palette)
+        } else {
+            recolor_image(input_image, color_palette)
+        };
+        if args.stdout {
+            write_image(&posterized_image, image_format, &mut io::stdout().lock())?;
+        } else {
+            save_image(&posterized_image, output_file_name, args.jpeg_quality)?;
+        }
+    } else if OutputType::Svg == output_type {
+        write_svg_palette(
+            color_palette,
+            args.palette_width.unwrap_or(input_image_width),
+            palette_height,
+            output_file_name,
+            args.orientation,
+            args.labels,
+        )?;
+    } else if OutputType::Json == output_type {
+        println!(
+            "{}",
+            generate_palette_json(&palette_output(
+                file,
+                input_image,
+                source_color_type,
+                color_palette,
+                args
+            )?)
+        );
+    } else if OutputType::JsonFile == output_type {
+        write_json_palette_to_file(
+            &palette_output(file, input_image, source_color_type, color_palette, args)?,
+            output_file_name,
+        )?;
+    } else if OutputType::Text == output_type {
+        // Like JSON, the hex codes go to stdout unless an output path was given
+        let output = args.output.as_ref().map(|_| output_file_name);
+        write_text_palette(color_palette, args.uppercase, output)?;
+    } else if OutputType::Scss == output_type {
+        let output = args.output.as_ref().map(|_| output_file_name);
+        write_scss_palette(color_palette, &args.scss_variable, output)?;
+    } else if OutputType::PaintNet == output_type {
+        write_paintnet_palette(color_palette, args.pad_palette, output_file_name)?;
+    } else if OutputType::Gpl == output_type {
+        let name = file.file_stem().unwrap_or_default().to_string_lossy();
+        write_gpl_palette(color_palette, &name, output_file_name)?;
+    } else if OutputType::Clut == output_type {
+        write_clut_palette(
+            &color_palette[..number_of_colors.min(color_palette.len())],
+            args.clut_scale,
+            args.orientation,
+            output_file_name,
+        )?;
+    }
 
-        let color_width = standalone_palette_width / number_of_colors as u32;
+    if args.embed_metadata {
+        let is_png_image = matches!(
+            output_type,
+            OutputType::OriginalImage
+                | OutputType::StandalonePalette
+                | OutputType::Posterize
+                | OutputType::Clut
+        ) && !wrote_to_stdout
+            && ImageFormat::from_path(output_file_name).ok() == Some(ImageFormat::Png);
+        if is_png_image {
+            let drawn_colors = &color_palette[..number_of_colors.min(color_palette.len())];
+            embed_png_text(
+                output_file_name,
+                &[
+                    (PALETTE_KEYWORD, palette_text(drawn_colors)),
+                    (METHOD_KEYWORD, args.quantisation_method.to_string()),
+                ],
+            )?;
+        } else {
+            warn!(
+                "{}: --embed-metadata only applies to PNG image files, so was ignored",
+                file.display()
+            );
+        }
+    }
 
-        for y in 0..total_height {
-            for (x0, q) in color_palette.iter().enumerate().take(number_of_colors) {
-                let x1 = x0 as u32 * color_width;
-                for x2 in 0..color_width {
-                    imgbuf.put_pixel(x1 + x2, y, image::Rgb([q.r, q.g, q.b]));
-                }
+    if args.layout_json {
+        match swatch_layout {
+            Some(swatch_layout) => {
+                let layout_file = layout_file_name(output_file_name);
+                write_layout_json(&swatch_layout, &layout_file)?;
+                debug!("{}: layout written to {}", file.display(), layout_file.display());
             }
+            None => warn!(
+                "{}: --layout-json only applies to original-image and standalone-palette outputs, so was ignored",
+                file.display()
+            ),
         }
+    }
 
-        let save_result = imgbuf.save(&output_file_name);
+    // A json-file output would be its own sidecar
+    if let Some(json_palette) = json_sidecar_palette.filter(|_| output_type != OutputType::JsonFile)
+    {
+        let sidecar = output_file_name.with_extension("json");
+        write_json_palette_to_file(
+            &palette_output(file, input_image, source_color_type, &json_palette, args)?,
+            &sidecar,
+        )?;
+        debug!("{}: JSON written to {}", file.display(), sidecar.display());
+    }
 
-        assert!(
-            save_result.is_ok(),
-            "Failed to save: {:?}",
-            output_file_name.canonicalize().unwrap()
+    info!(
+        "{}: wrote {} output in {:.2?}",
+        file.display(),
+        output_type,
+        write_start.elapsed()
+    );
+    if !wrote_to_stdout {
+        debug!(
+            "{}: output written to {}",
+            file.display(),
+            output_file_name.display()
         );
-    } else if OutputType::Json == output_type {
-        println!("{{");
-        for (i, color) in color_palette.iter().enumerate() {
-            println!("\t\"color_{}\": {{", i + 1);
-            println!("\t\t\"r\":\t{},\n\t\t\"g\":\t{},\n\t\t\"b\":\t{},\n\t\t\"a\":\t{},\n\t\t\"hex\":\t\"{}\"", color.r, color.g, color.b, color.a, rgb_to_hex(color.r, color.g, color.b));
-            if color_palette.len() - 1 != i {
-                println!("\t}},");
-            } else {
-                println!("\t}}");
-            }
-        }
-        println!("}}");
     }
+
+    Ok(true)
+}
+
+/**
+ * Builds the JSON document for a palette, with the details and extras requested.
+ *
+ * [&Path] The source image file, used for the checksum and recorded as the source (unless the
+ * palette was combined from several images).
+ * [&RgbImage] The source image, used for the thumbnail and to find the dominant color.
+ * [Option<ColorType>] How the source image's pixels were stored, if there was a single source.
+ * [&[Color]] The palette.
+ * [&Args] The options the tool was invoked with.
+ */
+fn palette_output(
+    file: &Path,
+    input_image: &RgbImage,
+    source_color_type: Option<ColorType>,
+    color_palette: &[Color],
+    args: &Args,
+) -> Result<PaletteOutput> {
+    let details = ColorDetails {
+        color_spaces: args.color_spaces,
+        named_colors: args.named_colors,
+        simulate: args.simulate,
+    };
+
+    Ok(PaletteOutput {
+        thumbnail: args
+            .thumbnail
+            .map(|longest_edge| thumbnail_data_uri(input_image, longest_edge))
+            .transpose()?,
+        groups: args.group_by_hue.then(|| group_by_hue(color_palette)),
+        extracted_colors: args.auto_colors.then_some(color_palette.len()),
+        quantisation_method: args
+            .auto_method
+            .then(|| args.quantisation_method.to_string()),
+        source_sha256: args.checksum.then(|| sha256_file(file)).transpose()?,
+        source_path: (!args.combine).then(|| source_path(file).display().to_string()),
+        source_modified: (!args.combine).then(|| modified_timestamp(file)).flatten(),
+        source_color_type: source_color_type.map(|color_type| format!("{color_type:?}")),
+        color_temperature_k: args
+            .temperature
+            .then(|| {
+                let average = mean_color(input_image);
+                rgb_to_cct(average.r, average.g, average.b)
+            })
+            .flatten()
+            .map(|kelvin| kelvin.round() as u32),
+        // The combined image isn't at hand, so combined palettes fall back to their first color
+        dominant_color: dominant_color(
+            color_palette,
+            &if args.combine {
+                Vec::new()
+            } else {
+                count_pixels(input_image, color_palette)
+            },
+        )
+        .map(|color| ColorInfo::new(color, details)),
+        histogram: args.histogram.then(|| {
+            distinct_colors(input_image)
+                .into_iter()
+                .take(args.histogram_top)
+                .map(|(rgb, count)| HistogramEntry::new(rgb, count))
+                .collect()
+        }),
+        flat: args.json_flat,
+        compact: args.json_compact || args.ndjson,
+        ..PaletteOutput::new(color_palette, details)
+    })
 }
 
 /**
@@ -387,6 +2316,8 @@ fn process_image(
  * - `original_file`: A reference to the original file path.
  * - `output`: An optional reference to the output file path.
  * - `output_type`: The type of output to generate.
+ * - `template`: An `--output-template` to name the file after, rather than
+ *   `<stem>_palette.<ext>`.
  *
  * Returns:
  * - A `PathBuf` representing the new output file path.
@@ -395,62 +2326,234 @@ fn output_file_name(
     original_file: &Path,
     output: Option<&PathBuf>,
     output_type: OutputType,
+    template: Option<&TemplateFields>,
 ) -> PathBuf {
+    // URL outputs are named after the last segment of the URL's path, in the current directory
+    let url_file_name =
+        is_url(original_file).then(|| url_file_name(&original_file.to_string_lossy()));
+    let original_file = url_file_name.as_deref().unwrap_or(original_file);
     let original_image_stem = original_file.file_stem().unwrap().to_str().unwrap();
+    // Image outputs keep the input's format, except for PDFs, whose pages are rendered to PNGs
+    let image_extension = match original_file.extension() {
+        Some(ext) if !is_pdf(original_file) => ext.to_str().unwrap(),
+        _ => "png",
+    };
     let new_extension = match output_type {
-        OutputType::OriginalImage => match original_file.extension() {
-            Some(ext) => ext.to_str().unwrap(),
-            None => "png",
-        },
-        OutputType::StandalonePalette => match original_file.extension() {
-            Some(ext) => ext.to_str().unwrap(),
-            None => "png",
-        },
-        OutputType::Json => "json",
+        OutputType::OriginalImage | OutputType::Posterize | OutputType::StandalonePalette => {
+            image_extension
+        }
+        OutputType::Json | OutputType::JsonFile => "json",
+        OutputType::Text | OutputType::PaintNet => "txt",
+        OutputType::Gpl => "gpl",
+        OutputType::Scss => "scss",
+        OutputType::Svg => "svg",
+        OutputType::Clut => "png",
+    };
+    let suffix = match output_type {
+        OutputType::Posterize => "posterized",
+        OutputType::Clut => "clut",
+        _ => "palette",
+    };
+    let file_name = match template {
+        Some(template) => expand_output_template(template, original_image_stem, new_extension)
+            .expect("the template's placeholders were checked when it was parsed"),
+        None => format!("{original_image_stem}_{suffix}.{new_extension}"),
     };
-    let file_name = format!("{original_image_stem}_palette.{new_extension}");
 
     match output {
-        Some(p) if !p.is_dir() => PathBuf::from(p).with_file_name(file_name),
-        Some(p) if p.is_dir() => PathBuf::from(p).join(file_name),
+        Some(p) if is_output_directory(p) => PathBuf::from(p).join(file_name),
+        Some(p) => PathBuf::from(p).with_file_name(file_name),
         _ => PathBuf::from(original_file).with_file_name(file_name),
     }
 }
 
 /**
- * This helper function just converts a color from RGB values to a hex string.
- */
-fn rgb_to_hex(red: u8, green: u8, blue: u8) -> String {
-    format!("#{red:02x}{green:02x}{blue:02x}")
-}
-
-/**
- * This helper function is used by clap when handling the palette-height option.
- * It parses a string and returns a palette height.
+ * This helper function parses the value of the palette-height option, and returns a palette
+ * height.
  *
  * The palette height can be provided:
  *  - as a percentage of the original image (a number followed by '%')
  *  - as a number of pixels (a number followed by the string 'px')
  *  - as a number of pixels (a number by itself)
+ *  - as `auto`, meaning `AUTO_PALETTE_HEIGHT_PERCENTAGE` of the original image
+ *
+ * It's forgiving of the way values tend to be pasted in: whitespace around the value and before
+ * the unit is ignored (e.g. " 50 %"), and so is a fractional part of zero on a number of pixels
+ * (e.g. "100.0px"). Anything else, like "100%%" or "100PX", is still rejected.
+ *
+ * Percentages up to `MAX_PALETTE_HEIGHT_PERCENTAGE` are accepted here; whether those above 100%
+ * are allowed is checked afterwards, by `check_palette_height`.
  */
 fn palette_height_parser(s: &str) -> Result<PaletteHeight, String> {
-    if s.ends_with('%') {
-        let percentage = &s[0..s.len() - 1];
-        match percentage.parse::<f32>() {
-            Ok(n) if n <= 100.0 => Ok(PaletteHeight::Percentage(n)),
-            _ => Err("Percentage must be between 0 and 100".to_owned()),
+    let s = s.trim();
+    if s == "auto" {
+        Ok(PaletteHeight::Percentage(AUTO_PALETTE_HEIGHT_PERCENTAGE))
+    } else if let Some(percentage) = s.strip_suffix('%') {
+        match percentage.trim_end().parse::<f32>() {
+            Ok(n) if (0.0..=MAX_PALETTE_HEIGHT_PERCENTAGE).contains(&n) => {
+                Ok(PaletteHeight::Percentage(n))
+            }
+            _ => Err(format!(
+                "Percentage must be between 0 and {MAX_PALETTE_HEIGHT_PERCENTAGE}"
+            )),
         }
-    } else if s.ends_with("px") {
-        let pixels = &s[0..s.len() - 2];
+    } else {
+        let pixels = s.strip_suffix("px").unwrap_or(s).trim_end();
+        // A whole number of pixels may be written with a fractional part of zero, e.g. 100.0
+        let pixels = match pixels.split_once('.') {
+            Some((whole, fraction))
+                if !fraction.is_empty() && fraction.bytes().all(|b| b == b'0') =>
+            {
+                whole
+            }
+            _ => pixels,
+        };
         match pixels.parse::<u32>() {
             Ok(n) => Ok(PaletteHeight::Absolute(n)),
             _ => Err("Pixels must be a positive integer".to_owned()),
         }
+    }
+}
+
+/**
+ * Parses the value of `--palette-height` (see `palette_height_parser`). Clap hands it over as it
+ * was given, so that it can be parsed knowing whether `--decimal-comma` was given too, in which
+ * case a comma is read as the decimal separator (e.g. "50,5%"), as it's written in much of the
+ * world.
+ *
+ * [&str] The value of `--palette-height`.
+ * [bool] Whether `--decimal-comma` was given.
+ */
+fn parse_palette_height(s: &str, decimal_comma: bool) -> Result<PaletteHeight, String> {
+    if decimal_comma {
+        palette_height_parser(&s.replace(',', "."))
     } else {
-        match s.parse::<u32>() {
-            Ok(n) => Ok(PaletteHeight::Absolute(n)),
-            _ => Err("Pixels must be a positive integer".to_owned()),
-        }
+        palette_height_parser(s)
+    }
+}
+
+/**
+ * Parses and checks the `--palette-height` the tool was invoked with, and stores it in
+ * `args.palette_height`.
+ *
+ * [&mut Args] The options the tool was invoked with.
+ */
+fn resolve_palette_height(args: &mut Args) -> Result<(), String> {
+    let palette_height = parse_palette_height(&args.palette_height_arg, args.decimal_comma)?;
+    check_palette_height(palette_height, args.allow_large_palette)?;
+    args.palette_height = palette_height;
+    Ok(())
+}
+
+/**
+ * Rejects percentage palette heights above 100% unless `--allow-large-palette` was given.
+ *
+ * [PaletteHeight] The parsed palette height.
+ * [bool] Whether `--allow-large-palette` was given.
+ */
+fn check_palette_height(
+    palette_height: PaletteHeight,
+    allow_large_palette: bool,
+) -> Result<(), String> {
+    match palette_height {
+        PaletteHeight::Percentage(n) if n > 100.0 && !allow_large_palette => Err(
+            "Percentage must be between 0 and 100 (or up to 1000 with --allow-large-palette)"
+                .to_owned(),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/**
+ * This helper function is used by clap when handling the center-weight option. It parses a weight
+ * between 1 and `MAX_CENTER_WEIGHT`.
+ */
+fn center_weight_parser(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(n) if (1.0..=MAX_CENTER_WEIGHT).contains(&n) => Ok(n),
+        _ => Err(format!(
+            "Center weight must be between 1 and {MAX_CENTER_WEIGHT}"
+        )),
+    }
+}
+
+/**
+ * This helper function is used by clap when handling the aspect option. It parses a ratio of two
+ * non-zero whole numbers, e.g. `3:1`.
+ */
+fn aspect_parser(s: &str) -> Result<(u32, u32), String> {
+    let parse = |n: &str| n.trim().parse::<u32>().ok().filter(|n| *n > 0);
+    match s.split_once(':') {
+        Some((width, height)) => parse(width).zip(parse(height)),
+        None => None,
+    }
+    .ok_or_else(|| format!("'{s}' is not an aspect ratio of two positive numbers (e.g. 3:1)"))
+}
+
+/**
+ * Works out the size of a standalone palette with the given aspect ratio. A palette width, when
+ * given, is kept and the height worked out from it; otherwise the width is worked out from the
+ * palette height. Neither is ever less than a pixel.
+ *
+ * [Option<u32>] The `--palette-width`, if given.
+ * [u32] The palette height, in pixels.
+ * [(u32, u32)] The aspect ratio, as width and height.
+ */
+fn aspect_dimensions(
+    width: Option<u32>,
+    height: u32,
+    (ratio_width, ratio_height): (u32, u32),
+) -> (u32, u32) {
+    let scale = |length: u32, to: u32, from: u32| {
+        ((length as f64 * to as f64 / from as f64).round() as u32).max(1)
+    };
+    match width {
+        Some(width) => (width, scale(width, ratio_height, ratio_width)),
+        None => (scale(height, ratio_width, ratio_height), height),
+    }
+}
+
+/**
+ * This helper function is used by clap when handling the min-luminance and max-luminance options.
+ * It parses a relative luminance between 0 and 1.
+ */
+fn luminance_parser(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(n) if (0.0..=1.0).contains(&n) => Ok(n),
+        _ => Err("Luminance must be between 0 and 1".to_owned()),
+    }
+}
+
+/**
+ * This helper function is used by clap when handling the chroma-boost option. It parses a factor
+ * that isn't negative.
+ */
+fn chroma_boost_parser(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(n) if n.is_finite() && n >= 0.0 => Ok(n),
+        _ => Err("Chroma boost must be a number of at least 0".to_owned()),
+    }
+}
+
+/**
+ * This helper function is used by clap when handling the min-saturation option. It parses a
+ * percentage between 0 and 100.
+ */
+fn saturation_parser(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(n) if (0.0..=100.0).contains(&n) => Ok(n),
+        _ => Err("Saturation must be between 0 and 100".to_owned()),
+    }
+}
+
+/**
+ * This helper function is used by clap when handling the timeout-per-image option. It parses a
+ * positive number of seconds, which may be fractional.
+ */
+fn seconds_parser(s: &str) -> Result<Duration, String> {
+    match s.parse::<f64>() {
+        Ok(n) if n.is_finite() && n > 0.0 => Ok(Duration::from_secs_f64(n)),
+        _ => Err("Timeout must be a positive number of seconds".to_owned()),
     }
 }
 
@@ -465,28 +2568,83 @@ mod tests {
         // Test case 1: Output path provided
         let output_path = PathBuf::from("path/to/output/something.jpg");
         let output_type = OutputType::OriginalImage;
-        let result = output_file_name(&original_file, Some(&output_path), output_type);
+        let result = output_file_name(original_file, Some(&output_path), output_type, None);
         let expected_result = PathBuf::from("path/to/output/some_file_palette.png");
         assert_eq!(result, expected_result);
 
         // Test case 2: Output path not provided
         let output_type = OutputType::OriginalImage;
-        let result = output_file_name(&original_file, None, output_type);
+        let result = output_file_name(original_file, None, output_type, None);
         let expected_result = PathBuf::from("path/to/original/some_file_palette.png");
         assert_eq!(result, expected_result);
 
         // Test case 3: Output path provided and OutputType is json
         let output_path = PathBuf::from("path/to/output/something.jpg");
         let output_type = OutputType::Json;
-        let result = output_file_name(&original_file, Some(&output_path), output_type);
+        let result = output_file_name(original_file, Some(&output_path), output_type, None);
         let expected_result = PathBuf::from("path/to/output/some_file_palette.json");
         assert_eq!(result, expected_result);
 
         // Test case 4: Output path not provided and OutputType is json
         let output_type = OutputType::Json;
-        let result = output_file_name(&original_file, None, output_type);
+        let result = output_file_name(original_file, None, output_type, None);
         let expected_result = PathBuf::from("path/to/original/some_file_palette.json");
         assert_eq!(result, expected_result);
+
+        // Test case 5: Output path not provided and OutputType is paint-net
+        let output_type = OutputType::PaintNet;
+        let result = output_file_name(original_file, None, output_type, None);
+        let expected_result = PathBuf::from("path/to/original/some_file_palette.txt");
+        assert_eq!(result, expected_result);
+
+        // Test case 6: Output path not provided and OutputType is posterize
+        let output_type = OutputType::Posterize;
+        let result = output_file_name(original_file, None, output_type, None);
+        let expected_result = PathBuf::from("path/to/original/some_file_posterized.png");
+        assert_eq!(result, expected_result);
+
+        // Test case 7: A clut is always a PNG
+        let output_type = OutputType::Clut;
+        let result = output_file_name(&PathBuf::from("photo.jpg"), None, output_type, None);
+        let expected_result = PathBuf::from("photo_clut.png");
+        assert_eq!(result, expected_result);
+
+        // Test case 8: Image outputs for a PDF are PNGs
+        let output_type = OutputType::OriginalImage;
+        let result = output_file_name(&PathBuf::from("brand.pdf"), None, output_type, None);
+        let expected_result = PathBuf::from("brand_palette.png");
+        assert_eq!(result, expected_result);
+
+        // Test case 9: An output template replaces the usual name, in the same place
+        let template = TemplateFields {
+            template: "{stem}-{method}-{count}.{ext}",
+            method: "k-means",
+            count: 8,
+            date: "2024-01-01",
+        };
+        let output_type = OutputType::Json;
+        let result = output_file_name(original_file, None, output_type, Some(&template));
+        let expected_result = PathBuf::from("path/to/original/some_file-k-means-8.json");
+        assert_eq!(result, expected_result);
+    }
+
+    #[test]
+    fn test_aspect() {
+        // Test case 1: Valid ratios
+        assert_eq!(aspect_parser("3:1"), Ok((3, 1)));
+        assert_eq!(aspect_parser("16:9"), Ok((16, 9)));
+
+        // Test case 2: Malformed ratios and zero components are rejected
+        for invalid in ["3", "3:", "a:1", "0:1", "3:0", "-3:1", "3:1:2"] {
+            assert!(aspect_parser(invalid).is_err(), "{invalid}");
+        }
+
+        // Test case 3: A width of 300 at 3:1 is 100 high
+        assert_eq!(aspect_dimensions(Some(300), 256, (3, 1)), (300, 100));
+
+        // Test case 4: Without a width, it's worked out from the height
+        assert_eq!(aspect_dimensions(None, 90, (16, 9)), (160, 90));
+        assert_eq!(aspect_dimensions(Some(2), 256, (3, 1)), (2, 1));
     }
 
     #[test]
@@ -509,66 +2667,473 @@ mod tests {
         let expected_result = Ok(PaletteHeight::Percentage(50.0));
         assert_eq!(result, expected_result);
 
-        // Test case 3: Invalid percentage value
+        // Test case 3: Percentages over 100 parse, but are rejected without --allow-large-palette
         let input = "150%";
         let result = palette_height_parser(input);
-        let expected_result = Err(String::from("Percentage must be between 0 and 100"));
+        let expected_result = Ok(PaletteHeight::Percentage(150.0));
+        assert_eq!(result, expected_result);
+        assert_eq!(
+            check_palette_height(result.clone().unwrap(), false),
+            Err(String::from(
+                "Percentage must be between 0 and 100 (or up to 1000 with --allow-large-palette)"
+            ))
+        );
+        assert_eq!(check_palette_height(result.clone().unwrap(), true), Ok(()));
+
+        // Test case 4: Invalid percentage values
+        let input = "1500%";
+        let result = palette_height_parser(input);
+        let expected_result = Err(String::from("Percentage must be between 0 and 1000"));
         assert_eq!(result, expected_result);
+        assert_eq!(palette_height_parser("-50%"), expected_result);
 
-        // Test case 4: Invalid input
+        // Test case 5: Invalid input
         let input = "foo";
         let result = palette_height_parser(input);
         let expected_result = Err(String::from("Pixels must be a positive integer"));
         assert_eq!(result, expected_result);
 
-        // Test case 5: Invalid input
+        // Test case 6: Invalid input
         let input = "-100";
         let result = palette_height_parser(input);
         let expected_result = Err(String::from("Pixels must be a positive integer"));
         assert_eq!(result, expected_result);
+
+        // Test case 7: auto is a percentage of the image
+        let input = "auto";
+        let result = palette_height_parser(input);
+        let expected_result = Ok(PaletteHeight::Percentage(AUTO_PALETTE_HEIGHT_PERCENTAGE));
+        assert_eq!(result, expected_result);
+        assert_eq!(check_palette_height(result.clone().unwrap(), false), Ok(()));
+
+        // Test case 8: Lenient forms of valid values
+        assert_eq!(
+            palette_height_parser("50 %"),
+            Ok(PaletteHeight::Percentage(50.0))
+        );
+        assert_eq!(
+            palette_height_parser("50.0%"),
+            Ok(PaletteHeight::Percentage(50.0))
+        );
+        assert_eq!(
+            palette_height_parser(" 100px"),
+            Ok(PaletteHeight::Absolute(100))
+        );
+        assert_eq!(
+            palette_height_parser("100 px "),
+            Ok(PaletteHeight::Absolute(100))
+        );
+        assert_eq!(
+            palette_height_parser("100.0"),
+            Ok(PaletteHeight::Absolute(100))
+        );
+        assert_eq!(
+            palette_height_parser("100.00px"),
+            Ok(PaletteHeight::Absolute(100))
+        );
+        assert_eq!(
+            palette_height_parser(" auto "),
+            Ok(PaletteHeight::Percentage(AUTO_PALETTE_HEIGHT_PERCENTAGE))
+        );
+
+        // Test case 9: Values that are still invalid
+        let percentage_error = Err(String::from("Percentage must be between 0 and 1000"));
+        let pixels_error = Err(String::from("Pixels must be a positive integer"));
+        assert_eq!(palette_height_parser("100%%"), percentage_error);
+        assert_eq!(palette_height_parser("% 50"), pixels_error);
+        assert_eq!(palette_height_parser("100PX"), pixels_error);
+        assert_eq!(palette_height_parser("100.5px"), pixels_error);
+        assert_eq!(palette_height_parser("100."), pixels_error);
+        assert_eq!(palette_height_parser("1 00"), pixels_error);
+        assert_eq!(palette_height_parser("px"), pixels_error);
+        assert_eq!(palette_height_parser(""), pixels_error);
     }
 
     #[test]
-    fn test_rgb_to_hex() {
-        // Test case 1: All zeros
-        assert_eq!(rgb_to_hex(0, 0, 0), "#000000");
+    fn test_decimal_comma_palette_height() {
+        let parse = |args: &[&str]| {
+            let mut args = Args::parse_from(args);
+            resolve_palette_height(&mut args).unwrap();
+            args.palette_height
+        };
+
+        // Test case 1: A decimal comma is accepted with --decimal-comma
+        assert_eq!(
+            parse_palette_height("50,5%", true),
+            Ok(PaletteHeight::Percentage(50.5))
+        );
+        assert_eq!(
+            parse(&["colorbuddy", "--decimal-comma", "-p", "50,5%", "a.png"]),
+            PaletteHeight::Percentage(50.5)
+        );
+        assert_eq!(
+            parse_palette_height("100,0px", true),
+            Ok(PaletteHeight::Absolute(100))
+        );
 
-        // Test case 2: All max values
-        assert_eq!(rgb_to_hex(255, 255, 255), "#ffffff");
+        // Test case 2: Without it, a decimal comma is rejected
+        assert_eq!(
+            parse_palette_height("50,5%", false),
+            Err(String::from("Percentage must be between 0 and 1000"))
+        );
 
-        // Test case 3: Random values
-        assert_eq!(rgb_to_hex(128, 64, 32), "#804020");
+        // Test case 3: A decimal point still parses with --decimal-comma
+        assert_eq!(
+            parse(&["colorbuddy", "--decimal-comma", "-p", "50.5%", "a.png"]),
+            PaletteHeight::Percentage(50.5)
+        );
+
+        // Test case 4: An input named like the flag doesn't turn it on
+        let mut args = Args::parse_from(["colorbuddy", "-p", "50,5%", "--", "--decimal-comma"]);
+        assert!(!args.decimal_comma);
+        assert!(resolve_palette_height(&mut args).is_err());
     }
 
     #[test]
-    fn test_mcq_color_nodes_to_exoquant_colors() {
-        let mcq_colors = vec![
-            ColorNode {
-                red: 32,
-                grn: 64,
-                blu: 128,
-                rgb: 0,
-                cnt: 0,
-            },
-            ColorNode {
-                red: 133,
-                grn: 78,
-                blu: 232,
-                rgb: 0,
-                cnt: 0,
-            },
-        ];
+    fn test_process_image_json_output_destinations() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("red.png");
+        image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]))
+            .save(&input)
+            .unwrap();
+
+        // Test case 1: JsonFile writes the palette to disk
+        let args = Args::parse_from(["colorbuddy", "-t", "json-file", "-n", "1"]);
+        let output = output_file_name(&input, None, args.output_type, None);
+        process_image(&input, &args, &output).unwrap();
+        assert!(output.is_file());
+        std::fs::remove_file(&output).unwrap();
+
+        // Test case 2: Json writes to stdout only
+        let args = Args::parse_from(["colorbuddy", "-t", "json", "-n", "1"]);
+        let output = output_file_name(&input, None, args.output_type, None);
+        process_image(&input, &args, &output).unwrap();
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_process_image_reports_skipped_outputs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("red.png");
+        image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]))
+            .save(&input)
+            .unwrap();
+        let output = dir.path().join("red_palette.json");
+
+        // Test case 1: A new output is written
+        let args = Args::parse_from(["colorbuddy", "-t", "json-file", "-n", "1", "--no-clobber"]);
+        assert!(process_image(&input, &args, &output).unwrap().written);
+
+        // Test case 2: With --no-clobber, an existing one is kept, and reported as not written
+        let processed = process_image(&input, &args, &output).unwrap();
+        assert!(!processed.written);
+        assert_eq!(processed.palette.len(), 1);
+    }
+
+    #[test]
+    fn test_process_image_invalid_output_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("red.png");
+        image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]))
+            .save(&input)
+            .unwrap();
+        let missing = dir.path().join("missing/dir");
+        let output = missing.join("red_palette.png");
+
+        // Test case 1: The missing directory is named, rather than failing deep in the encoder
+        let args = Args::parse_from(["colorbuddy", "-n", "1"]);
+        let error = process_image(&input, &args, &output).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Output directory doesn't exist: {} (create it, or pass --create-dirs)",
+                missing.display()
+            )
+        );
+        assert!(!missing.exists());
+
+        // Test case 2: With --create-dirs, the directory is created and the output written
+        let args = Args::parse_from(["colorbuddy", "-n", "1", "--create-dirs"]);
+        process_image(&input, &args, &output).unwrap();
+        assert!(output.is_file());
+    }
+
+    #[test]
+    fn test_process_image_timeout_per_image() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("noise.png");
+        image::RgbImage::from_fn(1000, 1000, |x, y| {
+            let n = x.wrapping_mul(2_654_435_761) ^ y.wrapping_mul(40_503);
+            image::Rgb([n as u8, (n >> 8) as u8, (n >> 16) as u8])
+        })
+        .save(&input)
+        .unwrap();
+        let output = dir.path().join("noise_palette.json");
+
+        // Test case 1: Extraction that runs past the timeout is abandoned with an error
+        let args = Args::parse_from([
+            "colorbuddy",
+            "-t",
+            "json-file",
+            "-n",
+            "64",
+            "--timeout-per-image",
+            "0.000001",
+        ]);
+        let error = process_image(&input, &args, &output).err().unwrap();
+        assert!(error
+            .to_string()
+            .starts_with("Extraction timed out: gave up after"));
+        assert!(!output.exists());
+
+        // Test case 2: A generous timeout doesn't get in the way
+        let small = dir.path().join("red.png");
+        image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]))
+            .save(&small)
+            .unwrap();
+        let args = Args::parse_from([
+            "colorbuddy",
+            "-t",
+            "json-file",
+            "-n",
+            "1",
+            "--timeout-per-image",
+            "60",
+        ]);
+        process_image(&small, &args, &output).unwrap();
+        assert!(output.is_file());
+
+        // Test case 3: Timeouts must be positive
+        assert_eq!(seconds_parser("0.5"), Ok(Duration::from_millis(500)));
+        assert!(seconds_parser("0").is_err());
+        assert!(seconds_parser("-1").is_err());
+        assert!(seconds_parser("soon").is_err());
+    }
+
+    #[test]
+    fn test_palette_height_pixels() {
+        let height = |args: &[&str]| {
+            let mut args = Args::parse_from(["colorbuddy"].iter().chain(args));
+            resolve_palette_height(&mut args).unwrap();
+            palette_height_pixels(&args, (400, 300))
+        };
+
+        // Test case 1: Standalone percentages are of the image height by default
+        assert_eq!(height(&["-t", "standalone-palette", "-p", "20%"]), 60);
+        assert_eq!(
+            height(&[
+                "-t",
+                "standalone-palette",
+                "-p",
+                "20%",
+                "--percentage-base",
+                "height"
+            ]),
+            60
+        );
+
+        // Test case 2: Or of the palette's own width, which defaults to the image width
+        assert_eq!(
+            height(&[
+                "-t",
+                "standalone-palette",
+                "-p",
+                "20%",
+                "--percentage-base",
+                "width"
+            ]),
+            80
+        );
+        assert_eq!(
+            height(&[
+                "-t",
+                "standalone-palette",
+                "-p",
+                "20%",
+                "-w",
+                "1000",
+                "--percentage-base",
+                "width"
+            ]),
+            200
+        );
+
+        // Test case 3: The original-image strip ignores the base, and pixels are pixels
+        assert_eq!(height(&["-p", "20%", "--percentage-base", "width"]), 60);
+        assert_eq!(
+            height(&[
+                "-p",
+                "20%",
+                "--palette-position",
+                "left",
+                "--percentage-base",
+                "width"
+            ]),
+            80
+        );
+        assert_eq!(
+            height(&[
+                "-t",
+                "standalone-palette",
+                "-p",
+                "50px",
+                "--percentage-base",
+                "width"
+            ]),
+            50
+        );
+    }
+
+    #[test]
+    fn test_palette_output_source() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("red.png");
+        let image = image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]));
+        image.save(&input).unwrap();
+        let colors = vec![Color::new(255, 0, 0, 255)];
+
+        // Test case 1: The absolute path and modification time of the file are recorded
+        let args = Args::parse_from(["colorbuddy", "-t", "json"]);
+        let json: serde_json::Value = serde_json::from_str(&generate_palette_json(
+            &palette_output(&input, &image, None, &colors, &args).unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(
+            json["source_path"],
+            std::fs::canonicalize(&input).unwrap().display().to_string()
+        );
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let modified = json["source_modified"].as_u64().unwrap();
+        assert!(now.abs_diff(modified) < 60);
+
+        // Test case 2: Neither is recorded for a combined palette
+        let args = Args::parse_from(["colorbuddy", "-t", "json-file", "--combine", "-o", "x"]);
+        let output = palette_output(&input, &image, None, &colors, &args).unwrap();
+        assert_eq!(output.source_path, None);
+        assert_eq!(output.source_modified, None);
+    }
+
+    #[test]
+    fn test_process_image_ignore_border() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("scan.png");
+        image::RgbImage::from_fn(20, 16, |x, y| {
+            if x < 3 || y < 3 || x >= 17 || y >= 13 {
+                image::Rgb([255, 0, 0])
+            } else {
+                image::Rgb([0, 0, 255])
+            }
+        })
+        .save(&input)
+        .unwrap();
+        let output = dir.path().join("scan_palette.json");
+
+        // Test case 1: Without --ignore-border, the border is in the palette
+        let args = Args::parse_from(["colorbuddy", "-t", "json-file", "-n", "2"]);
+        let palette = process_image(&input, &args, &output).unwrap().palette;
+        assert!(palette.iter().any(|c| c.r > 200 && c.b < 50));
+
+        // Test case 2: With it, only the interior is
+        let args = Args::parse_from([
+            "colorbuddy",
+            "-t",
+            "json-file",
+            "-n",
+            "2",
+            "--ignore-border",
+            "3",
+        ]);
+        let palette = process_image(&input, &args, &output).unwrap().palette;
+        assert!(palette.iter().all(|c| c.r < 50));
+
+        // Test case 3: A border that leaves nothing is an error
+        let args = Args::parse_from(["colorbuddy", "-t", "json-file", "--ignore-border", "8"]);
+        let error = process_image(&input, &args, &output).err().unwrap();
+        assert!(format!("{error:#}").contains("leave nothing to extract from"));
+    }
+
+    thread_local! {
+        static LOG_LEVEL: std::cell::Cell<log::LevelFilter> =
+            const { std::cell::Cell::new(log::LevelFilter::Off) };
+        static LOG_RECORDS: std::cell::RefCell<Vec<(Level, String)>> =
+            const { std::cell::RefCell::new(Vec::new()) };
+    }
 
-        let result = mcq_color_nodes_to_exoquant_colors(mcq_colors);
+    /**
+     * Captures the records logged on each test's own thread, at the level that thread has set, so
+     * that tests running in parallel don't see each other's records.
+     */
+    struct TestLogger;
 
-        assert_eq!(result.len(), 2);
+    impl log::Log for TestLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= LOG_LEVEL.with(|level| level.get())
+        }
 
-        assert_eq!(result.get(0).unwrap().r, 32);
-        assert_eq!(result.get(0).unwrap().g, 64);
-        assert_eq!(result.get(0).unwrap().b, 128);
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                LOG_RECORDS.with(|records| {
+                    records
+                        .borrow_mut()
+                        .push((record.level(), record.args().to_string()))
+                });
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn capture_logs(level: log::LevelFilter, f: impl FnOnce()) -> Vec<(Level, String)> {
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&TestLogger).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+
+        LOG_LEVEL.with(|l| l.set(level));
+        f();
+        LOG_LEVEL.with(|l| l.set(log::LevelFilter::Off));
+        LOG_RECORDS.with(|records| records.take())
+    }
+
+    #[test]
+    fn test_process_image_logs_stages() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("red.png");
+        image::RgbImage::from_pixel(4, 4, image::Rgb([255, 0, 0]))
+            .save(&input)
+            .unwrap();
+        let args = Args::parse_from(["colorbuddy", "-t", "json-file", "-n", "1"]);
+        let output = output_file_name(&input, None, args.output_type, None);
+        let run = || {
+            process_image(&input, &args, &output).unwrap();
+        };
 
-        assert_eq!(result.get(1).unwrap().r, 133);
-        assert_eq!(result.get(1).unwrap().g, 78);
-        assert_eq!(result.get(1).unwrap().b, 232);
+        // Test case 1: Info logs each stage's timing, but none of the details
+        let records = capture_logs(log::LevelFilter::Info, run);
+        let messages: Vec<&str> = records.iter().map(|(_, m)| m.as_str()).collect();
+        assert!(messages.iter().any(|m| m.contains("loaded in")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("extracted 1 colors using")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("wrote json-file output")));
+        assert!(records.iter().all(|(level, _)| *level == Level::Info));
+
+        // Test case 2: Debug adds the details
+        let records = capture_logs(log::LevelFilter::Debug, run);
+        assert!(records
+            .iter()
+            .any(|(level, m)| *level == Level::Debug && m.contains("dimensions 4x4")));
+        assert!(records
+            .iter()
+            .any(|(level, m)| *level == Level::Debug && m.contains("#ff0000 covers 16 pixels")));
+
+        // Test case 3: Error silences them all
+        assert!(capture_logs(log::LevelFilter::Error, run).is_empty());
     }
 }
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use exoquant::Color;
+
+use crate::error::ColorBuddyError;
+use crate::types::config::Orientation;
+
+/**
+ * The most colors an 8-bit indexed PNG can hold.
+ */
+const MAX_CLUT_COLORS: usize = 256;
+
+/**
+ * The dimensions of a CLUT image, and the palette index of each of its pixels in row order: one
+ * pixel per color (enlarged to a `scale` × `scale` block), laid out left to right (horizontal) or
+ * top to bottom (vertical), so that pixel `i` holds color `i` when `scale` is 1.
+ *
+ * [usize] The number of colors.
+ * [u32] How many pixels wide and high each color is.
+ * [Orientation] The direction the colors are laid out in.
+ */
+pub fn clut_indices(
+    number_of_colors: usize,
+    scale: u32,
+    orientation: Orientation,
+) -> (u32, u32, Vec<u8>) {
+    let length = number_of_colors as u32 * scale;
+    let (width, height) = match orientation {
+        Orientation::Horizontal => (length, scale),
+        Orientation::Vertical => (scale, length),
+    };
+
+    let indices = (0..height)
+        .flat_map(|y| {
+            (0..width).map(move |x| match orientation {
+                Orientation::Horizontal => (x / scale) as u8,
+                Orientation::Vertical => (y / scale) as u8,
+            })
+        })
+        .collect();
+
+    (width, height, indices)
+}
+
+/**
+ * Writes a palette as a color lookup table: an indexed-color PNG whose PLTE chunk is the palette
+ * and whose pixels are the palette indices in order (see `clut_indices`), ready for tools that
+ * load a CLUT directly. Fails with `ColorBuddyError::InvalidPalette` when there are no colors, or
+ * more than an 8-bit PNG palette can hold.
+ *
+ * [&[Color]] The palette colors.
+ * [u32] How many pixels wide and high each color is.
+ * [Orientation] The direction the colors are laid out in.
+ * [&Path] The file to write.
+ */
+pub fn write_clut_palette(
+    colors: &[Color],
+    scale: u32,
+    orientation: Orientation,
+    output_file_name: &Path,
+) -> Result<()> {
+    if colors.is_empty() || colors.len() > MAX_CLUT_COLORS {
+        return Err(ColorBuddyError::InvalidPalette(format!(
+            "a CLUT holds 1 to {MAX_CLUT_COLORS} colors, not {}",
+            colors.len()
+        ))
+        .into());
+    }
+
+    let (width, height, indices) = clut_indices(colors.len(), scale, orientation);
+    let palette: Vec<u8> = colors.iter().flat_map(|c| [c.r, c.g, c.b]).collect();
+
+    let file = File::create(output_file_name)
+        .with_context(|| format!("Failed to save: {}", output_file_name.display()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(palette);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&indices)?;
+    writer
+        .finish()
+        .with_context(|| format!("Failed to save: {}", output_file_name.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors() -> Vec<Color> {
+        vec![
+            Color::new(255, 0, 0, 255),
+            Color::new(0, 255, 0, 255),
+            Color::new(0, 0, 255, 255),
+        ]
+    }
+
+    #[test]
+    fn test_clut_indices() {
+        // Test case 1: One pixel per color
+        assert_eq!(
+            clut_indices(3, 1, Orientation::Horizontal),
+            (3, 1, vec![0, 1, 2])
+        );
+        assert_eq!(
+            clut_indices(3, 1, Orientation::Vertical),
+            (1, 3, vec![0, 1, 2])
+        );
+
+        // Test case 2: Scaled up, each color is a block
+        assert_eq!(
+            clut_indices(2, 2, Orientation::Horizontal),
+            (4, 2, vec![0, 0, 1, 1, 0, 0, 1, 1])
+        );
+        assert_eq!(
+            clut_indices(2, 2, Orientation::Vertical),
+            (2, 4, vec![0, 0, 0, 0, 1, 1, 1, 1])
+        );
+    }
+
+    #[test]
+    fn test_write_clut_palette() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("clut.png");
+        let colors = colors();
+
+        // Test case 1: The image is one pixel per color, each pixel its palette color
+        write_clut_palette(&colors, 1, Orientation::Horizontal, &path).unwrap();
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let reader = decoder.read_info().unwrap();
+        assert_eq!(reader.info().color_type, png::ColorType::Indexed);
+
+        let clut = ::image::open(&path).unwrap().to_rgb8();
+        assert_eq!(clut.dimensions(), (colors.len() as u32, 1));
+        for (x, c) in colors.iter().enumerate() {
+            assert_eq!(clut.get_pixel(x as u32, 0).0, [c.r, c.g, c.b]);
+        }
+
+        // Test case 2: Too many colors for an 8-bit palette
+        let too_many = vec![Color::new(0, 0, 0, 255); MAX_CLUT_COLORS + 1];
+        assert!(write_clut_palette(&too_many, 1, Orientation::Horizontal, &path).is_err());
+    }
+}
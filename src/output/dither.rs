@@ -0,0 +1,91 @@
+use exoquant::Color;
+use image::{Rgb, RgbImage};
+
+use crate::palette::kmeans::nearest;
+
+/**
+ * Remaps an image to the palette using Floyd–Steinberg error diffusion, to preview how the
+ * palette looks applied to the image. Each pixel becomes the nearest palette color (Euclidean
+ * distance in RGB), and the difference is spread over the neighbouring pixels not yet visited.
+ *
+ * An empty palette leaves the image unchanged.
+ *
+ * [&RgbImage] The image to be remapped.
+ * [&[Color]] The palette colors.
+ */
+pub fn dither(input_image: &RgbImage, colors: &[Color]) -> RgbImage {
+    if colors.is_empty() {
+        return input_image.clone();
+    }
+
+    let (width, height) = input_image.dimensions();
+    let palette: Vec<[f64; 3]> = colors
+        .iter()
+        .map(|c| [c.r as f64, c.g as f64, c.b as f64])
+        .collect();
+
+    // The pixels, with the error diffused into them so far
+    let mut pixels: Vec<[f64; 3]> = input_image
+        .pixels()
+        .map(|p| p.0.map(|c| c as f64))
+        .collect();
+    let mut output = RgbImage::new(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = pixels[(y * width + x) as usize];
+            let index = nearest(&pixel, &palette);
+            let chosen = palette[index];
+            output.put_pixel(
+                x,
+                y,
+                Rgb([colors[index].r, colors[index].g, colors[index].b]),
+            );
+
+            let error = [0, 1, 2].map(|c| pixel[c] - chosen[c]);
+            for (dx, dy, weight) in [(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)] {
+                let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                if nx < 0 || nx >= width as i64 || ny >= height as i64 {
+                    continue;
+                }
+                let neighbour = &mut pixels[(ny * width as i64 + nx) as usize];
+                for c in 0..3 {
+                    neighbour[c] += error[c] * weight / 16.0;
+                }
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dither_uses_only_palette_colors() {
+        let gradient = RgbImage::from_fn(64, 8, |x, _| Rgb([x as u8 * 4, x as u8 * 4, 128]));
+        let colors = vec![
+            Color::new(0, 0, 128, 255),
+            Color::new(128, 128, 128, 255),
+            Color::new(252, 252, 128, 255),
+        ];
+
+        let result = dither(&gradient, &colors);
+
+        assert_eq!(result.dimensions(), gradient.dimensions());
+        let allowed: Vec<[u8; 3]> = colors.iter().map(|c| [c.r, c.g, c.b]).collect();
+        assert!(result.pixels().all(|p| allowed.contains(&p.0)));
+
+        // The middle of the gradient mixes neighbouring palette colors rather than banding
+        let middle: Vec<[u8; 3]> = (20..28).map(|x| result.get_pixel(x, 4).0).collect();
+        assert!(middle.iter().any(|p| *p != middle[0]));
+    }
+
+    #[test]
+    fn test_dither_empty_palette() {
+        let image = RgbImage::from_pixel(2, 2, Rgb([1, 2, 3]));
+        assert_eq!(dither(&image, &[]), image);
+    }
+}
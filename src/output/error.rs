@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::types::config::ErrorFormat;
+
+/**
+ * Formats the error for an image that couldn't be processed, for printing to stderr: as the error
+ * message and its causes, or as a single-line JSON object with `file` and `error` fields.
+ *
+ * [&Path] The image that couldn't be processed.
+ * [&anyhow::Error] The error.
+ * [ErrorFormat] The format to use.
+ */
+pub fn format_error(file: &Path, error: &anyhow::Error, format: ErrorFormat) -> String {
+    match format {
+        ErrorFormat::Text => format!("{error:#}"),
+        ErrorFormat::Json => json!({
+            "file": file.display().to_string(),
+            "error": format!("{error:#}"),
+        })
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_format_error() {
+        let file = Path::new("path/to/missing.png");
+        let error = anyhow!("No such file").context("Error opening image: path/to/missing.png");
+
+        // Test case 1: Text
+        assert_eq!(
+            format_error(file, &error, ErrorFormat::Text),
+            "Error opening image: path/to/missing.png: No such file"
+        );
+
+        // Test case 2: JSON, on a single line
+        let line = format_error(file, &error, ErrorFormat::Json);
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["file"], "path/to/missing.png");
+        assert_eq!(
+            value["error"],
+            "Error opening image: path/to/missing.png: No such file"
+        );
+    }
+}
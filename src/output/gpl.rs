@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use exoquant::Color;
+
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * Formats a palette as a GIMP palette (`.gpl`): a `GIMP Palette` header naming the palette, then
+ * one line per color of its red, green and blue values, followed by its hex code as the color's
+ * name.
+ *
+ * [&[Color]] The palette colors.
+ * [&str] The name GIMP shows for the palette.
+ */
+pub fn generate_gpl_palette(colors: &[Color], name: &str) -> String {
+    let mut palette = format!("GIMP Palette\nName: {name}\nColumns: 0\n#\n");
+
+    for c in colors {
+        palette.push_str(&format!(
+            "{:3} {:3} {:3}\t{}\n",
+            c.r,
+            c.g,
+            c.b,
+            rgb_to_hex(c.r, c.g, c.b)
+        ));
+    }
+
+    palette
+}
+
+/**
+ * Writes a palette to a GIMP palette file.
+ *
+ * [&[Color]] The palette colors.
+ * [&str] The name GIMP shows for the palette.
+ * [&Path] The file to write.
+ */
+pub fn write_gpl_palette(colors: &[Color], name: &str, output_file_name: &Path) -> Result<()> {
+    fs::write(output_file_name, generate_gpl_palette(colors, name))
+        .with_context(|| format!("Failed to save: {}", output_file_name.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_gpl_palette() {
+        let colors = vec![Color::new(26, 107, 63, 255), Color::new(171, 205, 239, 255)];
+
+        // Test case 1: A header naming the palette, then a line per color
+        let palette = generate_gpl_palette(&colors, "photo");
+        assert_eq!(
+            palette,
+            "GIMP Palette\nName: photo\nColumns: 0\n#\n 26 107  63\t#1a6b3f\n171 205 239\t#abcdef\n"
+        );
+
+        // Test case 2: An empty palette is just the header
+        assert_eq!(generate_gpl_palette(&[], "empty").lines().count(), 4);
+    }
+}
@@ -0,0 +1,714 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use exoquant::Color;
+use image::{ImageOutputFormat, Rgb, RgbImage};
+
+use crate::error::ColorBuddyError;
+use crate::output::layout::{LayoutInfo, SwatchRect};
+use crate::output::title::{add_title, title_band_height};
+use crate::output::{
+    draw_swatch_borders, extend_last_swatch, save_swatch_image, swatch_spans, write_swatch_image,
+};
+use crate::types::config::{PaletteFill, PalettePosition};
+
+/**
+ * How the original image, the palette strip and the frame are arranged in the composite.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct CompositeLayout<'a> {
+    /// The edge of the original image the palette strip is attached to.
+    pub position: PalettePosition,
+    /// The thickness of the palette strip: its height at the top or bottom, its width at the left
+    /// or right.
+    pub palette_size: u32,
+    /// The width of the frame around the whole composite, in pixels (0 for no frame).
+    pub frame: u32,
+    /// The width of the band between the image and the palette strip, in pixels (0 for none).
+    pub margin: u32,
+    /// The color of the frame, the margin, the title band, and (see `fill`) the end of the strip.
+    pub background: [u8; 3],
+    /// A caption to draw in a band across the top of the composite.
+    pub title: Option<&'a str>,
+    /// How much of the image each color covers, to size the swatches in proportion to; `None`
+    /// for equal swatches.
+    pub weights: Option<&'a [u64]>,
+    /// What fills the end of the strip when the swatches don't reach it.
+    pub fill: PaletteFill,
+    /// Give each swatch its color's alpha, saving the composite as RGBA (see `save_swatch_image`).
+    pub transparent_swatches: bool,
+    /// The width of the border drawn inside each swatch, in pixels (0 for none).
+    pub swatch_border: u32,
+    /// The color of the border drawn inside each swatch.
+    pub swatch_border_color: [u8; 3],
+}
+
+/**
+ * Builds and saves a copy of the original image with the palette in a strip along one of its
+ * edges, optionally surrounded by a frame (see `build_original_with_palette`). Returns where
+ * the swatches were drawn.
+ *
+ * [&RgbImage] The original image.
+ * [&[Color]] The palette colors.
+ * [Option<&[Color]>] The palette as seen with a color vision deficiency, drawn as a second strip.
+ * [usize] The number of swatches to divide the strip into.
+ * [&CompositeLayout] Where the palette strip goes, how thick it is, the frame, and the title.
+ * [&Path] The output file name.
+ * [u8] The quality to encode JPEG outputs at, from 1 to 100. Ignored for other formats.
+ */
+pub fn save_original_with_palette(
+    input_image: &RgbImage,
+    colors: &[Color],
+    simulated_colors: Option<&[Color]>,
+    number_of_colors: usize,
+    layout: &CompositeLayout,
+    output_file_name: &Path,
+    jpeg_quality: u8,
+) -> Result<LayoutInfo> {
+    let (imgbuf, swatch_layout) = build_original_with_palette(
+        input_image,
+        colors,
+        simulated_colors,
+        number_of_colors,
+        layout,
+    )?;
+    save_swatch_image(
+        &imgbuf,
+        &swatch_layout,
+        layout.transparent_swatches,
+        output_file_name,
+        jpeg_quality,
+    )?;
+    Ok(swatch_layout)
+}
+
+/**
+ * Builds a copy of the original image with the palette in a strip along one of its edges, and
+ * writes it, encoded in the given format, to `writer` (e.g. stdout) rather than a file. Returns
+ * where the swatches were drawn.
+ *
+ * [&RgbImage] The original image.
+ * [&[Color]] The palette colors.
+ * [Option<&[Color]>] The palette as seen with a color vision deficiency, drawn as a second strip.
+ * [usize] The number of swatches to divide the strip into.
+ * [&CompositeLayout] Where the palette strip goes, how thick it is, the frame, and the title.
+ * [ImageOutputFormat] The format to encode the image in.
+ * [&mut W] Where to write the encoded image.
+ */
+pub fn write_original_with_palette<W: Write>(
+    input_image: &RgbImage,
+    colors: &[Color],
+    simulated_colors: Option<&[Color]>,
+    number_of_colors: usize,
+    layout: &CompositeLayout,
+    format: ImageOutputFormat,
+    writer: &mut W,
+) -> Result<LayoutInfo> {
+    let (composite, swatch_layout) = build_original_with_palette(
+        input_image,
+        colors,
+        simulated_colors,
+        number_of_colors,
+        layout,
+    )?;
+    write_swatch_image(
+        &composite,
+        &swatch_layout,
+        layout.transparent_swatches,
+        format,
+        writer,
+    )?;
+    Ok(swatch_layout)
+}
+
+/**
+ * Builds, in memory, a copy of the original image with the palette in a strip along one of its
+ * edges, optionally surrounded by a frame. A strip at the top or bottom spans the width of the
+ * image, with the swatches laid out left to right; a strip at the left or right spans its height,
+ * with the swatches laid out top to bottom. The swatches are the same length unless weights are
+ * given, in which case they're sized in proportion to them (see `swatch_spans`). A margin of the
+ * background color can separate the image from the strip. Any of the strip the swatches don't
+ * reach is left in the background color, or covered by stretching the last swatch. Each swatch can
+ * be outlined with a border drawn inside its edges. A title is drawn in a band across the top,
+ * above everything else.
+ *
+ * A simulated palette is drawn as a second strip of the same thickness, on the far side of the
+ * first from the image.
+ *
+ * Alongside the image, returns where each swatch was drawn in it (after any stretching of the last
+ * swatch, and below any title), e.g. to build an image map over the composite.
+ *
+ * The save and write functions wrap this; call it directly to encode or serve the image yourself.
+ * Fails with `ColorBuddyError::InvalidPalette` when the edge is too short for every swatch to be at
+ * least one pixel long.
+ *
+ * [&RgbImage] The original image.
+ * [&[Color]] The palette colors.
+ * [Option<&[Color]>] The palette as seen with a color vision deficiency, drawn as a second strip.
+ * [usize] The number of swatches to divide the strip into.
+ * [&CompositeLayout] Where the palette strip goes, how thick it is, the frame, and the title.
+ */
+pub fn build_original_with_palette(
+    input_image: &RgbImage,
+    colors: &[Color],
+    simulated_colors: Option<&[Color]>,
+    number_of_colors: usize,
+    layout: &CompositeLayout,
+) -> Result<(RgbImage, LayoutInfo)> {
+    let (input_image_width, input_image_height) = input_image.dimensions();
+    let CompositeLayout {
+        position,
+        palette_size,
+        frame,
+        margin,
+        background,
+        title,
+        weights,
+        fill,
+        transparent_swatches: _,
+        swatch_border,
+        swatch_border_color,
+    } = *layout;
+    let vertical_strip = matches!(position, PalettePosition::Left | PalettePosition::Right);
+
+    // The length of the edge the palette strip runs along
+    let strip_length = if vertical_strip {
+        input_image_height
+    } else {
+        input_image_width
+    };
+    if number_of_colors == 0 || strip_length < number_of_colors as u32 {
+        return Err(ColorBuddyError::InvalidPalette(format!(
+            "{number_of_colors} swatches don't fit along the {strip_length}px edge of the image"
+        ))
+        .into());
+    }
+
+    let strips: Vec<&[Color]> = std::iter::once(colors).chain(simulated_colors).collect();
+    // The margin is counted in with the strips, as it sits between them and the image
+    let strips_size = palette_size * strips.len() as u32 + margin;
+
+    let (total_width, total_height) = if vertical_strip {
+        (input_image_width + strips_size, input_image_height)
+    } else {
+        (input_image_width, input_image_height + strips_size)
+    };
+    let (total_width, total_height) = (total_width + 2 * frame, total_height + 2 * frame);
+
+    // Where the image and the strip start, relative to the inside of the frame
+    let (image_x, image_y) = match position {
+        PalettePosition::Top => (0, strips_size),
+        PalettePosition::Left => (strips_size, 0),
+        PalettePosition::Bottom | PalettePosition::Right => (0, 0),
+    };
+    // Each strip is further from the image than the last, beyond the margin
+    let strip_origin = |strip: u32| {
+        let offset = margin + strip * palette_size;
+        match position {
+            PalettePosition::Top => (0, strips_size - palette_size - offset),
+            PalettePosition::Left => (strips_size - palette_size - offset, 0),
+            PalettePosition::Bottom => (0, input_image_height + offset),
+            PalettePosition::Right => (input_image_width + offset, 0),
+        }
+    };
+    // The margin and the strips make a band along the edge of the image: (x, y, width, height)
+    let (band_x, band_y, band_width, band_height) = match position {
+        PalettePosition::Top => (0, 0, input_image_width, strips_size),
+        PalettePosition::Left => (0, 0, strips_size, input_image_height),
+        PalettePosition::Bottom => (0, input_image_height, input_image_width, strips_size),
+        PalettePosition::Right => (input_image_width, 0, strips_size, input_image_height),
+    };
+
+    // Create an image buffer big enough to hold the output image
+    let mut imgbuf = RgbImage::new(total_width, total_height);
+
+    // Paint the frame around the edges, and the band the margin and the strips sit in; the swatches
+    // are drawn over the band, leaving the background wherever they don't reach
+    for (x, y, pixel) in imgbuf.enumerate_pixels_mut() {
+        let in_frame =
+            x < frame || y < frame || x >= total_width - frame || y >= total_height - frame;
+        let in_band = (band_x + frame..band_x + frame + band_width).contains(&x)
+            && (band_y + frame..band_y + frame + band_height).contains(&y);
+        if in_frame || in_band {
+            *pixel = Rgb(background);
+        }
+    }
+
+    // This clones the image we're processing into the output buffer
+    for x in 0..input_image_width {
+        for y in 0..input_image_height {
+            imgbuf.put_pixel(
+                x + image_x + frame,
+                y + image_y + frame,
+                *input_image.get_pixel(x, y),
+            );
+        }
+    }
+
+    // Where each color starts along the palette strip, and its length
+    let spans = swatch_spans(strip_length, number_of_colors, weights);
+    let mut swatch_layout = LayoutInfo {
+        width: total_width,
+        height: total_height,
+        swatches: Vec::new(),
+    };
+
+    for (strip, strip_colors) in strips.iter().enumerate() {
+        let (strip_x, strip_y) = strip_origin(strip as u32);
+        let mut spans = spans.clone();
+        if fill == PaletteFill::Extend {
+            extend_last_swatch(&mut spans, strip_colors.len(), strip_length);
+        }
+        for (q, &(start, color_length)) in strip_colors.iter().zip(&spans) {
+            let (x, y, width, height) = if vertical_strip {
+                (0, start, palette_size, color_length)
+            } else {
+                (start, 0, color_length, palette_size)
+            };
+            swatch_layout.swatches.push(SwatchRect::new(
+                q,
+                (x + strip_x + frame, y + strip_y + frame),
+                (width, height),
+            ));
+            for along in start..start + color_length {
+                for across in 0..palette_size {
+                    let (x, y) = if vertical_strip {
+                        (across, along)
+                    } else {
+                        (along, across)
+                    };
+                    imgbuf.put_pixel(
+                        x + strip_x + frame,
+                        y + strip_y + frame,
+                        Rgb([q.r, q.g, q.b]),
+                    );
+                }
+            }
+        }
+    }
+
+    draw_swatch_borders(
+        &mut imgbuf,
+        &swatch_layout,
+        swatch_border,
+        swatch_border_color,
+    );
+
+    let imgbuf = match title {
+        Some(title) => {
+            let band_height = title_band_height(total_width);
+            swatch_layout.height += band_height;
+            swatch_layout.shift_down(band_height);
+            add_title(&imgbuf, title, background)
+        }
+        None => imgbuf,
+    };
+    Ok((imgbuf, swatch_layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::DEFAULT_JPEG_QUALITY;
+
+    fn layout(position: PalettePosition) -> CompositeLayout<'static> {
+        CompositeLayout {
+            position,
+            palette_size: 4,
+            frame: 0,
+            margin: 0,
+            background: [255, 255, 255],
+            title: None,
+            weights: None,
+            fill: PaletteFill::Background,
+            transparent_swatches: false,
+            swatch_border: 0,
+            swatch_border_color: [0, 0, 0],
+        }
+    }
+
+    #[test]
+    fn test_build_original_with_palette() {
+        let input_image = RgbImage::from_pixel(10, 8, Rgb([90, 90, 90]));
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+        let layout = CompositeLayout {
+            frame: 1,
+            background: [12, 34, 56],
+            ..layout(PalettePosition::Bottom)
+        };
+
+        let composite = build_original_with_palette(&input_image, &colors, None, 2, &layout)
+            .unwrap()
+            .0;
+        assert_eq!(composite.dimensions(), (12, 14));
+        assert_eq!(composite.get_pixel(0, 0).0, [12, 34, 56]);
+        assert_eq!(composite.get_pixel(1, 1).0, [90, 90, 90]);
+        assert_eq!(composite.get_pixel(1, 9).0, [255, 0, 0]);
+        assert_eq!(composite.get_pixel(10, 12).0, [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_build_original_with_palette_margin() {
+        let input_image = RgbImage::from_pixel(10, 8, Rgb([90, 90, 90]));
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+        let layout = CompositeLayout {
+            margin: 3,
+            ..layout(PalettePosition::Bottom)
+        };
+
+        // Test case 1: The margin rows are the background color, with the strip below them
+        let composite = build_original_with_palette(&input_image, &colors, None, 2, &layout)
+            .unwrap()
+            .0;
+        assert_eq!(composite.dimensions(), (10, 15));
+        assert_eq!(composite.get_pixel(0, 7).0, [90, 90, 90]);
+        for y in 8..11 {
+            for x in 0..10 {
+                assert_eq!(composite.get_pixel(x, y).0, [255, 255, 255]);
+            }
+        }
+        assert_eq!(composite.get_pixel(0, 11).0, [255, 0, 0]);
+        assert_eq!(composite.get_pixel(9, 14).0, [0, 0, 255]);
+
+        // Test case 2: On the left, the margin is a column between the strip and the image
+        let layout = CompositeLayout {
+            position: PalettePosition::Left,
+            ..layout
+        };
+        let composite = build_original_with_palette(&input_image, &colors, None, 2, &layout)
+            .unwrap()
+            .0;
+        assert_eq!(composite.dimensions(), (17, 8));
+        assert_eq!(composite.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(composite.get_pixel(4, 0).0, [255, 255, 255]);
+        assert_eq!(composite.get_pixel(6, 7).0, [255, 255, 255]);
+        assert_eq!(composite.get_pixel(7, 0).0, [90, 90, 90]);
+    }
+
+    #[test]
+    fn test_build_original_with_palette_fill() {
+        // Three swatches along a 10px edge leave the last column over
+        let input_image = RgbImage::from_pixel(10, 8, Rgb([90, 90, 90]));
+        let colors = vec![
+            Color::new(255, 0, 0, 255),
+            Color::new(0, 255, 0, 255),
+            Color::new(0, 0, 255, 255),
+        ];
+        let layout = CompositeLayout {
+            background: [250, 240, 230],
+            ..layout(PalettePosition::Bottom)
+        };
+
+        // Test case 1: The leftover column is the background color, not black
+        let composite = build_original_with_palette(&input_image, &colors, None, 3, &layout)
+            .unwrap()
+            .0;
+        assert_eq!(composite.get_pixel(8, 8).0, [0, 0, 255]);
+        for y in 8..12 {
+            assert_eq!(composite.get_pixel(9, y).0, [250, 240, 230]);
+        }
+
+        // Test case 2: Extending the last swatch covers it instead
+        let layout = CompositeLayout {
+            fill: PaletteFill::Extend,
+            ..layout
+        };
+        let composite = build_original_with_palette(&input_image, &colors, None, 3, &layout)
+            .unwrap()
+            .0;
+        for y in 8..12 {
+            assert_eq!(composite.get_pixel(9, y).0, [0, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn test_save_original_with_palette_frame() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let unframed_path = dir.path().join("unframed.png");
+        let framed_path = dir.path().join("framed.png");
+
+        let input_image = RgbImage::from_fn(10, 10, |x, y| Rgb([x as u8 * 20, y as u8 * 20, 90]));
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+        let frame_color = [12, 34, 56];
+
+        let mut layout = CompositeLayout {
+            background: frame_color,
+            ..layout(PalettePosition::Bottom)
+        };
+        save_original_with_palette(
+            &input_image,
+            &colors,
+            None,
+            2,
+            &layout,
+            &unframed_path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        layout.frame = 3;
+        save_original_with_palette(
+            &input_image,
+            &colors,
+            None,
+            2,
+            &layout,
+            &framed_path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+
+        let unframed = image::open(&unframed_path).unwrap().to_rgb8();
+        let framed = image::open(&framed_path).unwrap().to_rgb8();
+        assert_eq!(framed.dimensions(), (16, 20));
+
+        // Test case 1: The outermost pixels are the frame color
+        for (x, y, pixel) in framed.enumerate_pixels() {
+            if x < 3 || y < 3 || x >= 13 || y >= 17 {
+                assert_eq!(pixel.0, frame_color, "pixel at ({x}, {y})");
+            }
+        }
+
+        // Test case 2: The inner region is the unframed composite
+        for (x, y, pixel) in unframed.enumerate_pixels() {
+            assert_eq!(framed.get_pixel(x + 3, y + 3), pixel);
+        }
+    }
+
+    #[test]
+    fn test_save_original_with_palette_positions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input_image = RgbImage::from_pixel(10, 8, Rgb([90, 90, 90]));
+        let red = [255, 0, 0];
+        let blue = [0, 0, 255];
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+
+        // (position, expected dimensions, a pixel in the first swatch, a pixel in the second
+        // swatch, a pixel of the original image)
+        let cases = [
+            (PalettePosition::Top, (10, 12), (0, 0), (9, 3), (0, 4)),
+            (PalettePosition::Bottom, (10, 12), (0, 8), (9, 11), (0, 0)),
+            (PalettePosition::Left, (14, 8), (0, 0), (3, 7), (4, 0)),
+            (PalettePosition::Right, (14, 8), (10, 0), (13, 7), (0, 0)),
+        ];
+
+        for (position, dimensions, first, second, original) in cases {
+            let path = dir.path().join(format!("{position}.png"));
+            let layout = layout(position);
+            save_original_with_palette(
+                &input_image,
+                &colors,
+                None,
+                2,
+                &layout,
+                &path,
+                DEFAULT_JPEG_QUALITY,
+            )
+            .unwrap();
+
+            let result = image::open(&path).unwrap().to_rgb8();
+            assert_eq!(result.dimensions(), dimensions, "{position}");
+            assert_eq!(result.get_pixel(first.0, first.1).0, red, "{position}");
+            assert_eq!(result.get_pixel(second.0, second.1).0, blue, "{position}");
+            assert_eq!(
+                result.get_pixel(original.0, original.1).0,
+                [90, 90, 90],
+                "{position}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_save_original_with_palette_too_many_colors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("composite.png");
+        let input_image = RgbImage::new(10, 10);
+        let colors: Vec<Color> = (0..=255).map(|i| Color::new(i, i, i, 255)).collect();
+        let layout = layout(PalettePosition::Bottom);
+
+        let error = save_original_with_palette(
+            &input_image,
+            &colors,
+            None,
+            256,
+            &layout,
+            &path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap_err()
+        .downcast::<ColorBuddyError>()
+        .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Invalid palette: 256 swatches don't fit along the 10px edge of the image"
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_save_original_with_palette_simulated_strip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input_image = RgbImage::from_pixel(10, 8, Rgb([90, 90, 90]));
+        let colors = vec![Color::new(255, 0, 0, 255)];
+        let simulated = vec![Color::new(94, 94, 13, 255)];
+
+        // (position, expected dimensions, a pixel in the palette strip, a pixel in the simulated
+        // strip, a pixel of the original image)
+        let cases = [
+            (PalettePosition::Top, (10, 16), (0, 4), (0, 3), (0, 8)),
+            (PalettePosition::Bottom, (10, 16), (0, 11), (0, 12), (0, 7)),
+            (PalettePosition::Left, (18, 8), (4, 0), (3, 0), (8, 0)),
+            (PalettePosition::Right, (18, 8), (13, 0), (14, 0), (9, 0)),
+        ];
+
+        for (position, dimensions, palette, simulated_pixel, original) in cases {
+            let path = dir.path().join(format!("{position}.png"));
+            let layout = layout(position);
+            save_original_with_palette(
+                &input_image,
+                &colors,
+                Some(&simulated),
+                1,
+                &layout,
+                &path,
+                DEFAULT_JPEG_QUALITY,
+            )
+            .unwrap();
+
+            let result = image::open(&path).unwrap().to_rgb8();
+            assert_eq!(result.dimensions(), dimensions, "{position}");
+            assert_eq!(result.get_pixel(palette.0, palette.1).0, [255, 0, 0]);
+            assert_eq!(
+                result.get_pixel(simulated_pixel.0, simulated_pixel.1).0,
+                [94, 94, 13],
+                "{position}"
+            );
+            assert_eq!(result.get_pixel(original.0, original.1).0, [90, 90, 90]);
+        }
+    }
+
+    #[test]
+    fn test_save_original_with_palette_title() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let untitled_path = dir.path().join("untitled.png");
+        let titled_path = dir.path().join("titled.png");
+        let input_image = RgbImage::from_pixel(100, 40, Rgb([90, 90, 90]));
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+
+        let mut layout = CompositeLayout {
+            palette_size: 10,
+            background: [250, 240, 230],
+            ..layout(PalettePosition::Bottom)
+        };
+        save_original_with_palette(
+            &input_image,
+            &colors,
+            None,
+            2,
+            &layout,
+            &untitled_path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        layout.title = Some("Holiday");
+        save_original_with_palette(
+            &input_image,
+            &colors,
+            None,
+            2,
+            &layout,
+            &titled_path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+
+        let untitled = image::open(&untitled_path).unwrap().to_rgb8();
+        let titled = image::open(&titled_path).unwrap().to_rgb8();
+
+        // Test case 1: The composite grows to fit the title band
+        assert_eq!(untitled.dimensions(), (100, 50));
+        assert!(titled.height() > untitled.height());
+        let band_height = titled.height() - untitled.height();
+
+        // Test case 2: The band is the background color (with text), not a swatch
+        assert_eq!(titled.get_pixel(0, 0).0, [250, 240, 230]);
+        for (x, y, pixel) in titled
+            .enumerate_pixels()
+            .filter(|(_, y, _)| *y < band_height)
+        {
+            assert!(
+                ![[255, 0, 0], [0, 0, 255]].contains(&pixel.0),
+                "pixel at ({x}, {y})"
+            );
+        }
+
+        // Test case 3: Below the band is the untitled composite
+        for (x, y, pixel) in untitled.enumerate_pixels() {
+            assert_eq!(titled.get_pixel(x, y + band_height), pixel);
+        }
+    }
+
+    #[test]
+    fn test_build_original_with_palette_layout() {
+        let input_image = RgbImage::from_pixel(10, 8, Rgb([90, 90, 90]));
+        let colors = vec![
+            Color::new(255, 0, 0, 255),
+            Color::new(0, 255, 0, 255),
+            Color::new(0, 0, 255, 255),
+        ];
+        let layout = CompositeLayout {
+            frame: 2,
+            margin: 1,
+            fill: PaletteFill::Extend,
+            ..layout(PalettePosition::Right)
+        };
+
+        // Test case 1: The swatches tile the strip beside the image, top to bottom
+        let (composite, swatch_layout) =
+            build_original_with_palette(&input_image, &colors, None, 3, &layout).unwrap();
+        assert_eq!(
+            (swatch_layout.width, swatch_layout.height),
+            composite.dimensions()
+        );
+        let mut y = 2;
+        for swatch in &swatch_layout.swatches {
+            assert_eq!((swatch.x, swatch.y, swatch.width), (13, y, 4));
+            y += swatch.height;
+        }
+        assert_eq!(y, 2 + 8);
+
+        // Test case 2: Each rectangle is filled with the color it reports
+        let hexes: Vec<&str> = swatch_layout
+            .swatches
+            .iter()
+            .map(|s| s.hex.as_str())
+            .collect();
+        assert_eq!(hexes, ["#ff0000", "#00ff00", "#0000ff"]);
+        for (swatch, color) in swatch_layout.swatches.iter().zip(&colors) {
+            let corner =
+                composite.get_pixel(swatch.x + swatch.width - 1, swatch.y + swatch.height - 1);
+            assert_eq!(corner.0, [color.r, color.g, color.b]);
+        }
+    }
+
+    #[test]
+    fn test_build_original_with_palette_swatch_border() {
+        let input_image = RgbImage::from_pixel(10, 8, Rgb([90, 90, 90]));
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+        let layout = CompositeLayout {
+            palette_size: 5,
+            swatch_border: 1,
+            ..layout(PalettePosition::Bottom)
+        };
+
+        // The border outlines each swatch, leaving its center and the image alone
+        let composite = build_original_with_palette(&input_image, &colors, None, 2, &layout)
+            .unwrap()
+            .0;
+        assert_eq!(composite.get_pixel(0, 8).0, [0, 0, 0]);
+        assert_eq!(composite.get_pixel(4, 10).0, [0, 0, 0]);
+        assert_eq!(composite.get_pixel(2, 10).0, [255, 0, 0]);
+        assert_eq!(composite.get_pixel(7, 10).0, [0, 0, 255]);
+        assert_eq!(composite.get_pixel(0, 7).0, [90, 90, 90]);
+    }
+}
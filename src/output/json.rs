@@ -0,0 +1,265 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use exoquant::Color;
+use serde_json::{Map, Value};
+
+use crate::types::color::PaletteOutput;
+use crate::utils::color_conversion::rgb_to_hsl;
+
+/**
+ * The hue families colors are grouped into, in the order they're listed.
+ */
+pub const HUE_FAMILIES: [&str; 8] = [
+    "reds", "oranges", "yellows", "greens", "cyans", "blues", "purples", "grays",
+];
+
+/**
+ * Colors with an HSL saturation below this are counted as grays, whatever their hue.
+ */
+const GRAY_SATURATION: f64 = 0.1;
+
+/**
+ * Generates the JSON document describing a palette. Each color is keyed by its 1-based position
+ * in the palette (`color_1`, `color_2`, ...), unless the palette is `flat`, in which case the
+ * colors are written in order as a bare array, without any of the extras. The document is
+ * pretty-printed unless the palette is `compact`.
+ *
+ * [&PaletteOutput] The palette, and any extras to include.
+ */
+pub fn generate_palette_json(palette: &PaletteOutput) -> String {
+    let json = match (palette.flat, palette.compact) {
+        (true, false) => serde_json::to_string_pretty(&palette.colors.values().collect::<Vec<_>>()),
+        (true, true) => serde_json::to_string(&palette.colors.values().collect::<Vec<_>>()),
+        (false, false) => serde_json::to_string_pretty(palette),
+        (false, true) => serde_json::to_string(palette),
+    };
+    json.expect("palette JSON is always serializable")
+}
+
+/**
+ * Writes the JSON document describing a palette to a file.
+ *
+ * [&PaletteOutput] The palette, and any extras to include.
+ * [&Path] The file to write.
+ */
+pub fn write_json_palette_to_file(palette: &PaletteOutput, output_file_name: &Path) -> Result<()> {
+    fs::write(output_file_name, generate_palette_json(palette))
+        .with_context(|| format!("Failed to save: {}", output_file_name.display()))
+}
+
+/**
+ * Works out which of the `HUE_FAMILIES` a color belongs to, from its HSL hue. Colors with little
+ * saturation (including black and white) are grays.
+ */
+pub fn hue_family(color: &Color) -> &'static str {
+    let hsl = rgb_to_hsl(color.r, color.g, color.b);
+    if hsl.s < GRAY_SATURATION {
+        return "grays";
+    }
+
+    match hsl.h {
+        h if h < 15.0 => "reds",
+        h if h < 45.0 => "oranges",
+        h if h < 70.0 => "yellows",
+        h if h < 165.0 => "greens",
+        h if h < 195.0 => "cyans",
+        h if h < 255.0 => "blues",
+        h if h < 330.0 => "purples",
+        _ => "reds",
+    }
+}
+
+/**
+ * Groups the colors of a palette by hue family. Every family in `HUE_FAMILIES` is listed, mapped
+ * to the 1-based positions (matching the `color_N` keys) of the colors in it.
+ *
+ * [&[Color]] The palette colors.
+ */
+pub fn group_by_hue(colors: &[Color]) -> Map<String, Value> {
+    HUE_FAMILIES
+        .iter()
+        .map(|family| {
+            let indices: Vec<usize> = colors
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| hue_family(c) == *family)
+                .map(|(i, _)| i + 1)
+                .collect();
+            (family.to_string(), Value::from(indices))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::color::ColorDetails;
+    use crate::types::config::ColorVisionDeficiency;
+
+    #[test]
+    fn test_generate_palette_json() {
+        let colors = vec![Color::new(255, 255, 255, 255), Color::new(255, 0, 0, 255)];
+
+        let defaults = ColorDetails::default();
+        let color_spaces = ColorDetails {
+            color_spaces: true,
+            ..defaults
+        };
+
+        // Test case 1: Color spaces not requested
+        let json: Value = serde_json::from_str(&generate_palette_json(&PaletteOutput::new(
+            &colors, defaults,
+        )))
+        .unwrap();
+        assert_eq!(json["color_1"]["hex"], "#ffffff");
+        assert_eq!(json["color_2"]["r"], 255);
+        assert!(json["color_1"].get("lab").is_none());
+        assert!(json["color_1"].get("lch").is_none());
+        assert!(json["color_1"].get("oklch").is_none());
+        assert!(json["color_1"].get("name").is_none());
+
+        // Test case 2: Color spaces requested
+        let json: Value = serde_json::from_str(&generate_palette_json(&PaletteOutput::new(
+            &colors,
+            color_spaces,
+        )))
+        .unwrap();
+        assert!((json["color_1"]["lab"]["l"].as_f64().unwrap() - 100.0).abs() < 0.01);
+        assert!((json["color_2"]["lch"]["h"].as_f64().unwrap() - 40.0).abs() < 0.01);
+        assert!((json["color_2"]["oklch"]["h"].as_f64().unwrap() - 29.23).abs() < 0.01);
+        assert!(json.get("thumbnail").is_none());
+        assert!(json.get("groups").is_none());
+
+        // Test case 3: A thumbnail is embedded after the colors
+        let thumbnail = "data:image/png;base64,AAAA".to_string();
+        let json = generate_palette_json(&PaletteOutput {
+            thumbnail: Some(thumbnail.clone()),
+            ..PaletteOutput::new(&colors, defaults)
+        });
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["thumbnail"], thumbnail.as_str());
+        assert!(json.find("color_2").unwrap() < json.find("thumbnail").unwrap());
+
+        // Test case 4: Named colors requested
+        let named_colors = ColorDetails {
+            named_colors: true,
+            ..defaults
+        };
+        let json: Value = serde_json::from_str(&generate_palette_json(&PaletteOutput::new(
+            &colors,
+            named_colors,
+        )))
+        .unwrap();
+        assert_eq!(json["color_1"]["name"], "white");
+        assert_eq!(json["color_2"]["name"], "red");
+        assert!(json["color_1"].get("simulated").is_none());
+
+        // Test case 5: A color vision deficiency simulated
+        let simulate = ColorDetails {
+            simulate: Some(ColorVisionDeficiency::Protanopia),
+            ..defaults
+        };
+        let json: Value = serde_json::from_str(&generate_palette_json(&PaletteOutput::new(
+            &colors, simulate,
+        )))
+        .unwrap();
+        assert_eq!(json["color_1"]["simulated"]["hex"], "#ffffff");
+        assert_eq!(json["color_2"]["simulated"]["hex"], "#5e5e0d");
+        assert_eq!(json["color_2"]["hex"], "#ff0000");
+
+        // Test case 6: The source image's checksum follows the colors
+        let checksum = "2d4bb42890939349a6e8eb04f141571f71dbeadf1145a389e1849132bc24a87f";
+        let json: Value = serde_json::from_str(&generate_palette_json(&PaletteOutput {
+            source_sha256: Some(checksum.to_owned()),
+            ..PaletteOutput::new(&colors, defaults)
+        }))
+        .unwrap();
+        assert_eq!(json["source_sha256"], checksum);
+    }
+
+    #[test]
+    fn test_generate_palette_json_compact() {
+        let colors = vec![Color::new(255, 255, 255, 255), Color::new(255, 0, 0, 255)];
+        let pretty = PaletteOutput::new(&colors, ColorDetails::default());
+        let compact = PaletteOutput {
+            compact: true,
+            ..PaletteOutput::new(&colors, ColorDetails::default())
+        };
+
+        // Test case 1: Compact output is a single line without whitespace
+        let compact_json = generate_palette_json(&compact);
+        assert!(!compact_json.contains('\n'));
+        assert!(!compact_json.contains(' '));
+        assert!(generate_palette_json(&pretty).contains('\n'));
+
+        // Test case 2: Both parse to the same document
+        let parse = |json: &str| serde_json::from_str::<Value>(json).unwrap();
+        assert_eq!(parse(&compact_json), parse(&generate_palette_json(&pretty)));
+
+        // Test case 3: Flat output can be compact too
+        let flat = PaletteOutput {
+            flat: true,
+            compact: true,
+            ..PaletteOutput::new(&colors, ColorDetails::default())
+        };
+        let flat_json = generate_palette_json(&flat);
+        assert!(!flat_json.contains('\n'));
+        assert_eq!(parse(&flat_json).as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_generate_palette_json_flat() {
+        let colors = vec![Color::new(255, 255, 255, 255), Color::new(255, 0, 0, 255)];
+
+        let json: Value = serde_json::from_str(&generate_palette_json(&PaletteOutput {
+            groups: Some(group_by_hue(&colors)),
+            flat: true,
+            ..PaletteOutput::new(&colors, ColorDetails::default())
+        }))
+        .unwrap();
+
+        let array = json.as_array().unwrap();
+        assert_eq!(array.len(), colors.len());
+        assert_eq!(array[0]["hex"], "#ffffff");
+        assert_eq!(array[1]["hex"], "#ff0000");
+        assert!(json.get("metadata").is_none());
+        assert!(json.get("groups").is_none());
+    }
+
+    #[test]
+    fn test_group_by_hue() {
+        let colors = vec![
+            Color::new(220, 20, 30, 255),   // red
+            Color::new(240, 140, 20, 255),  // orange
+            Color::new(240, 220, 30, 255),  // yellow
+            Color::new(40, 180, 60, 255),   // green
+            Color::new(30, 210, 220, 255),  // cyan
+            Color::new(30, 60, 220, 255),   // blue
+            Color::new(150, 40, 200, 255),  // purple
+            Color::new(128, 128, 128, 255), // gray
+            Color::new(230, 30, 100, 255),  // pinkish red
+        ];
+
+        // Test case 1: Each color lands in its family
+        let groups = group_by_hue(&colors);
+        assert_eq!(groups.keys().collect::<Vec<_>>(), HUE_FAMILIES.to_vec());
+        assert_eq!(groups["reds"], serde_json::json!([1, 9]));
+        for (i, family) in HUE_FAMILIES.iter().enumerate().skip(1) {
+            assert_eq!(groups[*family], serde_json::json!([i + 1]), "{family}");
+        }
+
+        // Test case 2: Empty families are listed with no colors
+        let groups = group_by_hue(&colors[..1]);
+        assert_eq!(groups["blues"], serde_json::json!([]));
+
+        // Test case 3: The groups are added to the JSON document
+        let json: Value = serde_json::from_str(&generate_palette_json(&PaletteOutput {
+            groups: Some(group_by_hue(&colors)),
+            ..PaletteOutput::new(&colors, ColorDetails::default())
+        }))
+        .unwrap();
+        assert_eq!(json["groups"]["grays"], serde_json::json!([8]));
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use exoquant::Color;
+use image::{RgbImage, Rgba, RgbaImage};
+use serde::Serialize;
+
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * Where a single swatch was drawn in an output image, in pixels from its top-left corner.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SwatchRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub hex: String,
+    /// The color's alpha, for drawing `--transparent-swatches`. Not written to the layout JSON.
+    #[serde(skip)]
+    pub alpha: u8,
+}
+
+impl SwatchRect {
+    /**
+     * [&Color] The color the swatch is filled with.
+     * [(u32, u32)] The position of its top-left corner.
+     * [(u32, u32)] Its width and height.
+     */
+    pub fn new(color: &Color, (x, y): (u32, u32), (width, height): (u32, u32)) -> SwatchRect {
+        SwatchRect {
+            x,
+            y,
+            width,
+            height,
+            hex: rgb_to_hex(color.r, color.g, color.b),
+            alpha: color.a,
+        }
+    }
+}
+
+/**
+ * The size of an output image, and where each of its swatches was drawn, e.g. to build an image
+ * map over it. The swatches are listed strip by strip (the palette first, then any simulated
+ * strip), in the order they run along the strip.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LayoutInfo {
+    pub width: u32,
+    pub height: u32,
+    pub swatches: Vec<SwatchRect>,
+}
+
+impl LayoutInfo {
+    /**
+     * Moves every swatch down, for when a band (e.g. a title) has been added above them.
+     *
+     * [u32] How far to move the swatches, in pixels.
+     */
+    pub fn shift_down(&mut self, offset: u32) {
+        for swatch in &mut self.swatches {
+            swatch.y += offset;
+        }
+    }
+}
+
+/**
+ * Converts an output image to RGBA, giving the pixels of each swatch its color's alpha. Everything
+ * else (the original image, the frame, the title) stays opaque.
+ *
+ * [&RgbImage] The output image.
+ * [&LayoutInfo] Where its swatches were drawn.
+ */
+pub fn with_swatch_alpha(image: &RgbImage, layout: &LayoutInfo) -> RgbaImage {
+    let mut rgba = RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        Rgba([r, g, b, 255])
+    });
+    for swatch in &layout.swatches {
+        for y in swatch.y..swatch.y + swatch.height {
+            for x in swatch.x..swatch.x + swatch.width {
+                rgba.get_pixel_mut(x, y)[3] = swatch.alpha;
+            }
+        }
+    }
+    rgba
+}
+
+/**
+ * Where the layout of an image output is written: next to it, with its extension swapped for
+ * `.layout.json` (e.g. photo_palette.png and photo_palette.layout.json).
+ *
+ * [&Path] The output file name.
+ */
+pub fn layout_file_name(output_file_name: &Path) -> PathBuf {
+    output_file_name.with_extension("layout.json")
+}
+
+/**
+ * Writes the layout of an image output to a file, as pretty-printed JSON.
+ *
+ * [&LayoutInfo] The layout to write.
+ * [&Path] The file to write.
+ */
+pub fn write_layout_json(layout: &LayoutInfo, output_file_name: &Path) -> Result<()> {
+    let json = serde_json::to_string_pretty(layout).expect("LayoutInfo is always serializable");
+    fs::write(output_file_name, json)
+        .with_context(|| format!("Failed to save: {}", output_file_name.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_layout_json() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = layout_file_name(&dir.path().join("photo_palette.png"));
+        assert_eq!(output, dir.path().join("photo_palette.layout.json"));
+
+        let mut layout = LayoutInfo {
+            width: 20,
+            height: 10,
+            swatches: vec![SwatchRect::new(
+                &Color::new(255, 0, 0, 255),
+                (0, 0),
+                (20, 10),
+            )],
+        };
+        layout.shift_down(4);
+        write_layout_json(&layout, &output).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "width": 20,
+                "height": 10,
+                "swatches": [{"x": 0, "y": 4, "width": 20, "height": 10, "hex": "#ff0000"}]
+            })
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use exoquant::Color;
+
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * The keywords of the tEXt chunks `--embed-metadata` adds: the palette, as a comma-separated list
+ * of hex codes, and the quantisation method that extracted it.
+ */
+pub const PALETTE_KEYWORD: &str = "colorbuddy:palette";
+pub const METHOD_KEYWORD: &str = "colorbuddy:method";
+
+/**
+ * The palette as a comma-separated list of hex codes, e.g. `#ff0000,#0000ff`.
+ *
+ * [&[Color]] The palette colors.
+ */
+pub fn palette_text(colors: &[Color]) -> String {
+    colors
+        .iter()
+        .map(|c| rgb_to_hex(c.r, c.g, c.b))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/**
+ * Adds tEXt chunks to a PNG that's already been saved. The image is decoded and re-encoded as-is
+ * (same dimensions, color type, bit depth and, for an indexed PNG such as a CLUT, palette) with the
+ * chunks added, as the image crate's encoder can't write them. Any other ancillary chunks are
+ * dropped, which is fine for colorbuddy's own outputs as they have none.
+ *
+ * [&Path] The PNG file.
+ * [&[(&str, String)]] The keyword and text of each chunk.
+ */
+pub fn embed_png_text(path: &Path, entries: &[(&str, String)]) -> Result<()> {
+    let bytes = fs::read(path).with_context(|| format!("Error reading: {}", path.display()))?;
+    let mut decoder = png::Decoder::new(bytes.as_slice());
+    decoder.set_transformations(png::Transformations::IDENTITY);
+    let mut reader = decoder
+        .read_info()
+        .with_context(|| format!("Error decoding PNG: {}", path.display()))?;
+    let palette = reader.info().palette.as_ref().map(|p| p.to_vec());
+    let transparency = reader.info().trns.as_ref().map(|t| t.to_vec());
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let frame = reader
+        .next_frame(&mut pixels)
+        .with_context(|| format!("Error decoding PNG: {}", path.display()))?;
+
+    let file = File::create(path).with_context(|| format!("Failed to save: {}", path.display()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), frame.width, frame.height);
+    encoder.set_color(frame.color_type);
+    encoder.set_depth(frame.bit_depth);
+    if let Some(palette) = palette {
+        encoder.set_palette(palette);
+    }
+    if let Some(transparency) = transparency {
+        encoder.set_trns(transparency);
+    }
+    for (keyword, text) in entries {
+        encoder.add_text_chunk(keyword.to_string(), text.clone())?;
+    }
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels[..frame.buffer_size()])?;
+    writer
+        .finish()
+        .with_context(|| format!("Failed to save: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::clut::write_clut_palette;
+    use crate::types::config::Orientation;
+    use ::image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_embed_png_text() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("palette.png");
+        let image = RgbImage::from_fn(8, 4, |x, _| Rgb([x as u8 * 30, 0, 255]));
+        image.save(&path).unwrap();
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+
+        embed_png_text(
+            &path,
+            &[
+                (PALETTE_KEYWORD, palette_text(&colors)),
+                (METHOD_KEYWORD, "k-means".to_owned()),
+            ],
+        )
+        .unwrap();
+
+        // Test case 1: The chunks can be read back
+        let file = File::open(&path).unwrap();
+        let reader = png::Decoder::new(file).read_info().unwrap();
+        let text: Vec<(&str, &str)> = reader
+            .info()
+            .uncompressed_latin1_text
+            .iter()
+            .map(|chunk| (chunk.keyword.as_str(), chunk.text.as_str()))
+            .collect();
+        assert_eq!(
+            text,
+            vec![
+                (PALETTE_KEYWORD, "#ff0000,#0000ff"),
+                (METHOD_KEYWORD, "k-means")
+            ]
+        );
+
+        // Test case 2: The pixels are untouched
+        assert_eq!(::image::open(&path).unwrap().to_rgb8(), image);
+
+        // Test case 3: An indexed PNG keeps its palette
+        let clut_path = dir.path().join("clut.png");
+        write_clut_palette(&colors, 1, Orientation::Horizontal, &clut_path).unwrap();
+        embed_png_text(&clut_path, &[(PALETTE_KEYWORD, palette_text(&colors))]).unwrap();
+        let reader = png::Decoder::new(File::open(&clut_path).unwrap())
+            .read_info()
+            .unwrap();
+        assert_eq!(reader.info().color_type, png::ColorType::Indexed);
+        assert_eq!(
+            reader.info().palette.as_deref(),
+            Some(&[255, 0, 0, 0, 0, 255][..])
+        );
+        assert_eq!(reader.info().uncompressed_latin1_text.len(), 1);
+    }
+}
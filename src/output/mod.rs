@@ -0,0 +1,329 @@
+pub mod clut;
+pub mod dither;
+pub mod error;
+pub mod gpl;
+pub mod image;
+pub mod json;
+pub mod layout;
+pub mod metadata;
+pub mod paintnet;
+pub mod preview;
+pub mod scss;
+pub mod standalone;
+pub mod summary;
+pub mod svg;
+pub mod text;
+pub mod thumbnail;
+pub mod title;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Cursor, Write};
+use std::path::Path;
+
+use ::image::codecs::jpeg::JpegEncoder;
+use ::image::{DynamicImage, ImageError, ImageFormat, ImageOutputFormat, Rgb, RgbImage};
+use anyhow::{Context, Result};
+
+use crate::output::layout::{with_swatch_alpha, LayoutInfo};
+
+/**
+ * The quality JPEG outputs are encoded at unless told otherwise; the same as the image crate's.
+ */
+pub const DEFAULT_JPEG_QUALITY: u8 = 75;
+
+/**
+ * Writes a text output either to a file or, when no file is given, to stdout.
+ *
+ * [&str] The contents to write.
+ * [Option<&Path>] The file to write, or `None` for stdout.
+ */
+pub fn write_to_file_or_stdout(contents: &str, output: Option<&Path>) -> Result<()> {
+    match output {
+        Some(path) => File::create(path)
+            .and_then(|mut f| f.write_all(contents.as_bytes()))
+            .with_context(|| format!("Failed to save: {}", path.display())),
+        None => io::stdout()
+            .lock()
+            .write_all(contents.as_bytes())
+            .context("Failed to write to stdout"),
+    }
+}
+
+/**
+ * Encodes an image in the given format and writes it to `writer`. The image is encoded in memory
+ * first, as some encoders need to seek, which e.g. stdout can't.
+ *
+ * [&RgbImage] The image to write.
+ * [ImageOutputFormat] The format to encode the image in.
+ * [&mut W] Where to write the encoded image.
+ */
+pub fn write_image<W: Write>(
+    image: &RgbImage,
+    format: ImageOutputFormat,
+    writer: &mut W,
+) -> Result<()> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), format)
+        .context("Failed to encode the image")?;
+    writer
+        .write_all(&bytes)
+        .context("Failed to write the image")
+}
+
+/**
+ * The encoder settings to write an image format with. JPEGs are encoded at the given quality;
+ * everything else uses the encoder's defaults.
+ *
+ * [ImageFormat] The format to encode the image in.
+ * [u8] The JPEG quality, from 1 (smallest) to 100 (best).
+ */
+pub fn output_format(format: ImageFormat, jpeg_quality: u8) -> ImageOutputFormat {
+    match format {
+        ImageFormat::Jpeg => ImageOutputFormat::Jpeg(jpeg_quality),
+        format => format.into(),
+    }
+}
+
+/**
+ * Saves an image to a file, in the format its extension implies. JPEGs are encoded at the given
+ * quality; it's ignored for every other format.
+ *
+ * [&RgbImage] The image to save.
+ * [&Path] The output file name.
+ * [u8] The JPEG quality, from 1 (smallest) to 100 (best).
+ */
+pub fn save_image(image: &RgbImage, output_file_name: &Path, jpeg_quality: u8) -> Result<()> {
+    let result = match ImageFormat::from_path(output_file_name) {
+        Ok(ImageFormat::Jpeg) => File::create(output_file_name)
+            .map_err(ImageError::from)
+            .and_then(|file| {
+                JpegEncoder::new_with_quality(&mut BufWriter::new(file), jpeg_quality)
+                    .encode_image(image)
+            }),
+        _ => image.save(output_file_name),
+    };
+
+    result.with_context(|| format!("Failed to save: {}", output_file_name.display()))
+}
+
+/**
+ * Saves an image with swatches drawn in it (see `save_image`). With `transparent`, it's saved as
+ * RGBA instead, each swatch taking its color's alpha (see `with_swatch_alpha`); only some formats,
+ * e.g. PNG, can store that.
+ *
+ * [&RgbImage] The image to save.
+ * [&LayoutInfo] Where its swatches were drawn.
+ * [bool] Whether to give the swatches their colors' alpha.
+ * [&Path] The output file name.
+ * [u8] The JPEG quality, from 1 (smallest) to 100 (best).
+ */
+pub fn save_swatch_image(
+    image: &RgbImage,
+    layout: &LayoutInfo,
+    transparent: bool,
+    output_file_name: &Path,
+    jpeg_quality: u8,
+) -> Result<()> {
+    if !transparent {
+        return save_image(image, output_file_name, jpeg_quality);
+    }
+
+    with_swatch_alpha(image, layout)
+        .save(output_file_name)
+        .with_context(|| format!("Failed to save: {}", output_file_name.display()))
+}
+
+/**
+ * Writes an image with swatches drawn in it to `writer` (see `write_image`), as RGBA when
+ * `transparent` (see `save_swatch_image`).
+ *
+ * [&RgbImage] The image to write.
+ * [&LayoutInfo] Where its swatches were drawn.
+ * [bool] Whether to give the swatches their colors' alpha.
+ * [ImageOutputFormat] The format to encode the image in.
+ * [&mut W] Where to write the encoded image.
+ */
+pub fn write_swatch_image<W: Write>(
+    image: &RgbImage,
+    layout: &LayoutInfo,
+    transparent: bool,
+    format: ImageOutputFormat,
+    writer: &mut W,
+) -> Result<()> {
+    if !transparent {
+        return write_image(image, format, writer);
+    }
+
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgba8(with_swatch_alpha(image, layout))
+        .write_to(&mut Cursor::new(&mut bytes), format)
+        .context("Failed to encode the image")?;
+    writer
+        .write_all(&bytes)
+        .context("Failed to write the image")
+}
+
+/**
+ * Where each swatch starts along a palette strip, and how long it is. Without weights, every
+ * swatch is the same length, and any pixels left over at the end are not covered (see
+ * `extend_last_swatch`). With weights (e.g. how many pixels each color covers), each swatch's
+ * length is in proportion to its weight, and the pixels left over from rounding down go to the
+ * swatches that lost the most to rounding, so the swatches cover the whole strip.
+ *
+ * [u32] The length of the strip.
+ * [usize] The number of swatches.
+ * [Option<&[u64]>] The weight of each swatch.
+ */
+pub fn swatch_spans(
+    length: u32,
+    number_of_colors: usize,
+    weights: Option<&[u64]>,
+) -> Vec<(u32, u32)> {
+    let weights = weights
+        .map(|w| &w[..number_of_colors.min(w.len())])
+        .filter(|w| w.iter().sum::<u64>() > 0);
+    let Some(weights) = weights else {
+        let color_length = length / number_of_colors.max(1) as u32;
+        return (0..number_of_colors as u32)
+            .map(|i| (i * color_length, color_length))
+            .collect();
+    };
+
+    let total: u64 = weights.iter().sum();
+    let exact: Vec<u64> = weights.iter().map(|w| w * length as u64).collect();
+    let mut lengths: Vec<u32> = exact.iter().map(|e| (e / total) as u32).collect();
+    let mut leftover = length - lengths.iter().sum::<u32>();
+
+    // Largest remainder first; ties go to the earlier swatch
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+    by_remainder.sort_by_key(|&i| std::cmp::Reverse(exact[i] % total));
+    for i in by_remainder {
+        if leftover == 0 {
+            break;
+        }
+        lengths[i] += 1;
+        leftover -= 1;
+    }
+
+    let mut start = 0;
+    lengths
+        .into_iter()
+        .map(|length| {
+            let span = (start, length);
+            start += length;
+            span
+        })
+        .collect()
+}
+
+/**
+ * Stretches the last of the swatches actually drawn (a palette can have fewer colors than there
+ * are swatches) to the end of the strip, so no pixels are left over after it.
+ *
+ * [&mut [(u32, u32)]] The swatch spans, from `swatch_spans`.
+ * [usize] The number of swatches drawn.
+ * [u32] The length of the strip.
+ */
+pub fn extend_last_swatch(spans: &mut [(u32, u32)], drawn: usize, length: u32) {
+    let drawn = drawn.min(spans.len());
+    if let Some((start, span_length)) = spans[..drawn].last_mut() {
+        *span_length = length - *start;
+    }
+}
+
+/**
+ * Outlines each swatch with a border drawn inside its edges, over the swatch's own color, so the
+ * swatches keep their size. A border as wide as half a swatch fills it.
+ *
+ * [&mut RgbImage] The image the swatches were drawn in.
+ * [&LayoutInfo] Where the swatches were drawn.
+ * [u32] The width of the border, in pixels (0 for none).
+ * [[u8; 3]] The color of the border.
+ */
+pub fn draw_swatch_borders(image: &mut RgbImage, layout: &LayoutInfo, width: u32, color: [u8; 3]) {
+    if width == 0 {
+        return;
+    }
+
+    for swatch in &layout.swatches {
+        for y in 0..swatch.height {
+            for x in 0..swatch.width {
+                let from_edge = x
+                    .min(y)
+                    .min(swatch.width - 1 - x)
+                    .min(swatch.height - 1 - y);
+                if from_edge < width {
+                    image.put_pixel(swatch.x + x, swatch.y + y, Rgb(color));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swatch_spans() {
+        // Test case 1: Equal swatches, leaving the remainder unpainted
+        assert_eq!(swatch_spans(10, 3, None), vec![(0, 3), (3, 3), (6, 3)]);
+
+        // Test case 2: Proportional swatches cover the whole strip
+        assert_eq!(swatch_spans(10, 2, Some(&[70, 30])), vec![(0, 7), (7, 3)]);
+        assert_eq!(
+            swatch_spans(10, 3, Some(&[1, 1, 1])),
+            vec![(0, 4), (4, 3), (7, 3)]
+        );
+        assert_eq!(
+            swatch_spans(100, 3, Some(&[5, 1, 14]))
+                .iter()
+                .map(|(_, length)| length)
+                .sum::<u32>(),
+            100
+        );
+
+        // Test case 3: Weights that are all zero fall back to equal swatches
+        assert_eq!(swatch_spans(10, 2, Some(&[0, 0])), vec![(0, 5), (5, 5)]);
+    }
+
+    #[test]
+    fn test_extend_last_swatch() {
+        // Test case 1: The last swatch takes up the remainder of the strip
+        let mut spans = swatch_spans(10, 3, None);
+        extend_last_swatch(&mut spans, 3, 10);
+        assert_eq!(spans, vec![(0, 3), (3, 3), (6, 4)]);
+
+        // Test case 2: With fewer colors than swatches, the last drawn one is stretched
+        let mut spans = swatch_spans(10, 3, None);
+        extend_last_swatch(&mut spans, 2, 10);
+        assert_eq!(spans, vec![(0, 3), (3, 7), (6, 3)]);
+
+        // Test case 3: Nothing drawn, nothing stretched
+        let mut spans = swatch_spans(10, 3, None);
+        extend_last_swatch(&mut spans, 0, 10);
+        assert_eq!(spans, vec![(0, 3), (3, 3), (6, 3)]);
+    }
+
+    #[test]
+    fn test_save_image_jpeg_quality() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let low = dir.path().join("low.jpg");
+        let high = dir.path().join("high.jpg");
+        let image = RgbImage::from_fn(64, 16, |x, y| {
+            ::image::Rgb([(x * 4) as u8, (y * 16) as u8, ((x + y) * 3) as u8])
+        });
+
+        // Test case 1: The quality takes effect for JPEGs
+        save_image(&image, &low, 10).unwrap();
+        save_image(&image, &high, 95).unwrap();
+        let size = |path: &Path| std::fs::metadata(path).unwrap().len();
+        assert!(size(&high) > size(&low));
+
+        // Test case 2: It's ignored for other formats
+        let png = dir.path().join("palette.png");
+        save_image(&image, &png, 10).unwrap();
+        assert_eq!(::image::open(&png).unwrap().to_rgb8(), image);
+    }
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use exoquant::Color;
+
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * The number of colors in a Paint.NET palette. Paint.NET ignores any beyond this.
+ */
+pub const PAINT_NET_PALETTE_SIZE: usize = 96;
+
+/**
+ * The color Paint.NET palettes are padded with.
+ */
+const PADDING: &str = "FFFFFFFF";
+
+/**
+ * Formats a palette as a Paint.NET palette file: a header of `;` comment lines, then one
+ * uppercase `AARRGGBB` hex code per color.
+ *
+ * [&[Color]] The palette colors.
+ * [bool] Whether to pad the palette with white up to the 96 colors Paint.NET expects.
+ */
+pub fn generate_paintnet_palette(colors: &[Color], pad: bool) -> String {
+    let mut palette = String::from(
+        "; paint.net Palette File\n\
+         ; Lines that start with a semicolon are comments\n\
+         ; Colors are written as 8-digit hexadecimal numbers: aarrggbb\n",
+    );
+
+    for c in colors {
+        let rgb = rgb_to_hex(c.r, c.g, c.b);
+        palette.push_str(&format!("{:02X}{}\n", c.a, rgb[1..].to_uppercase()));
+    }
+
+    if pad {
+        for _ in colors.len()..PAINT_NET_PALETTE_SIZE {
+            palette.push_str(PADDING);
+            palette.push('\n');
+        }
+    }
+
+    palette
+}
+
+/**
+ * Writes a palette to a Paint.NET palette file.
+ *
+ * [&[Color]] The palette colors.
+ * [bool] Whether to pad the palette with white up to the 96 colors Paint.NET expects.
+ * [&Path] The file to write.
+ */
+pub fn write_paintnet_palette(colors: &[Color], pad: bool, output_file_name: &Path) -> Result<()> {
+    fs::write(output_file_name, generate_paintnet_palette(colors, pad))
+        .with_context(|| format!("Failed to save: {}", output_file_name.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color_lines(palette: &str) -> Vec<&str> {
+        palette.lines().filter(|l| !l.starts_with(';')).collect()
+    }
+
+    #[test]
+    fn test_generate_paintnet_palette() {
+        let colors = vec![Color::new(26, 107, 63, 255), Color::new(171, 205, 239, 128)];
+
+        // Test case 1: Alpha first, then red, green and blue, in uppercase
+        let palette = generate_paintnet_palette(&colors, false);
+        assert!(palette.starts_with("; paint.net Palette File\n"));
+        assert_eq!(color_lines(&palette), vec!["FF1A6B3F", "80ABCDEF"]);
+
+        // Test case 2: Padded to 96 colors with white
+        let palette = generate_paintnet_palette(&colors, true);
+        let lines = color_lines(&palette);
+        assert_eq!(lines.len(), PAINT_NET_PALETTE_SIZE);
+        assert_eq!(lines[1], "80ABCDEF");
+        assert!(lines[2..].iter().all(|l| *l == "FFFFFFFF"));
+    }
+}
@@ -0,0 +1,98 @@
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::types::config::OutputType;
+
+/**
+ * Whether there's a display to open a viewer on. Only Linux and the BSDs can tell, by the absence
+ * of both an X11 and a Wayland display; macOS and Windows always have one.
+ */
+pub fn is_headless() -> bool {
+    cfg!(all(unix, not(target_os = "macos")))
+        && env::var_os("DISPLAY").is_none()
+        && env::var_os("WAYLAND_DISPLAY").is_none()
+}
+
+/**
+ * Opens the output of a run in the default image viewer. Only image outputs (original-image,
+ * standalone-palette, svg, posterize and clut) are previewed, and only when exactly one was
+ * written so that a directory of images doesn't launch a viewer for each of them.
+ *
+ * Returns a warning describing why nothing was opened, if it wasn't.
+ *
+ * [&[PathBuf]] The output files written.
+ * [OutputType] The type of output written.
+ * [bool] Whether there's no display to open a viewer on.
+ * [FnOnce(&Path) -> io::Result<()>] Opens a file in the default viewer.
+ */
+pub fn preview_output<F>(
+    outputs: &[PathBuf],
+    output_type: OutputType,
+    headless: bool,
+    open: F,
+) -> Result<(), String>
+where
+    F: FnOnce(&Path) -> io::Result<()>,
+{
+    if !matches!(
+        output_type,
+        OutputType::OriginalImage
+            | OutputType::StandalonePalette
+            | OutputType::Svg
+            | OutputType::Posterize
+            | OutputType::Clut
+    ) {
+        return Err(format!("--preview has no effect on {output_type} output"));
+    }
+
+    let output = match outputs {
+        [output] => output,
+        [] => return Err("--preview skipped as no output file was written".to_owned()),
+        _ => {
+            return Err(
+                "--preview only opens the output when a single image is processed".to_owned(),
+            )
+        }
+    };
+
+    if headless {
+        return Err("--preview skipped as there's no display to open a viewer on".to_owned());
+    }
+
+    open(output).map_err(|e| format!("--preview couldn't open {}: {e}", output.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_preview_output() {
+        let opened = Cell::new(0);
+        let open = |_: &Path| {
+            opened.set(opened.get() + 1);
+            Ok(())
+        };
+        let one = vec![PathBuf::from("red_palette.png")];
+        let many = vec![PathBuf::from("a.png"), PathBuf::from("b.png")];
+
+        // Test case 1: A single image output is opened exactly once
+        assert!(preview_output(&one, OutputType::StandalonePalette, false, open).is_ok());
+        assert_eq!(opened.get(), 1);
+
+        // Test case 2: Nothing is opened for several images, JSON output, or without a display
+        assert!(preview_output(&many, OutputType::OriginalImage, false, open).is_err());
+        assert!(preview_output(&one, OutputType::Json, false, open).is_err());
+        assert!(preview_output(&one, OutputType::Svg, true, open).is_err());
+        assert_eq!(opened.get(), 1);
+
+        // Test case 3: A failure to open the viewer becomes a warning
+        let warning = preview_output(&one, OutputType::Svg, false, |_| {
+            Err(io::Error::new(io::ErrorKind::NotFound, "no viewer"))
+        })
+        .unwrap_err();
+        assert!(warning.contains("red_palette.png: no viewer"));
+    }
+}
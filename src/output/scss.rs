@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use anyhow::Result;
+use exoquant::Color;
+
+use crate::output::write_to_file_or_stdout;
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * Formats a palette as an SCSS map, e.g.
+ *
+ * ```scss
+ * $palette: (
+ *   "color-1": #1a6b3f,
+ *   "color-2": #abcdef
+ * );
+ * ```
+ *
+ * Keys use the color's 1-based position in the palette, zero-padded to the width of the largest
+ * index so that they sort naturally (`color-01` ... `color-12`). An empty palette produces an
+ * empty map.
+ *
+ * [&[Color]] The palette colors.
+ * [&str] The name of the SCSS variable, with or without the leading `$`.
+ */
+pub fn generate_scss_palette(colors: &[Color], variable: &str) -> String {
+    let variable = variable.trim_start_matches('$');
+    if colors.is_empty() {
+        return format!("${variable}: ();\n");
+    }
+
+    let index_width = colors.len().to_string().len();
+    let entries = colors
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            format!(
+                "  \"color-{:0index_width$}\": {}",
+                i + 1,
+                rgb_to_hex(c.r, c.g, c.b)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",\n");
+
+    format!("${variable}: (\n{entries}\n);\n")
+}
+
+/**
+ * Writes a palette as an SCSS map, either to a file or, when no file is given, to stdout.
+ *
+ * [&[Color]] The palette colors.
+ * [&str] The name of the SCSS variable.
+ * [Option<&Path>] The file to write, or `None` for stdout.
+ */
+pub fn write_scss_palette(colors: &[Color], variable: &str, output: Option<&Path>) -> Result<()> {
+    write_to_file_or_stdout(&generate_scss_palette(colors, variable), output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_scss_palette() {
+        let colors = vec![Color::new(26, 107, 63, 255), Color::new(171, 205, 239, 255)];
+
+        // Test case 1: Default variable name
+        let expected = "$palette: (\n  \"color-1\": #1a6b3f,\n  \"color-2\": #abcdef\n);\n";
+        assert_eq!(generate_scss_palette(&colors, "palette"), expected);
+
+        // Test case 2: Custom variable name, given with its `$`
+        let expected = "$brand: (\n  \"color-1\": #1a6b3f,\n  \"color-2\": #abcdef\n);\n";
+        assert_eq!(generate_scss_palette(&colors, "$brand"), expected);
+
+        // Test case 3: Empty palette is still a valid map
+        assert_eq!(generate_scss_palette(&[], "palette"), "$palette: ();\n");
+
+        // Test case 4: Indices are zero-padded to the largest index
+        let colors = vec![Color::new(0, 0, 0, 255); 10];
+        let scss = generate_scss_palette(&colors, "palette");
+        assert!(scss.contains("\"color-01\": #000000,"));
+        assert!(scss.contains("\"color-10\": #000000\n"));
+    }
+}
@@ -0,0 +1,806 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use exoquant::Color;
+use image::{ImageOutputFormat, RgbImage};
+
+use crate::error::ColorBuddyError;
+use crate::output::layout::{LayoutInfo, SwatchRect};
+use crate::output::title::{add_title, title_band_height};
+use crate::output::{
+    draw_swatch_borders, extend_last_swatch, save_swatch_image, swatch_spans, write_swatch_image,
+};
+use crate::types::config::{ColorSpace, Orientation, PaletteFill};
+use crate::utils::color_conversion::{lab_to_rgb, rgb_to_lab, Lab};
+
+/**
+ * The size of a standalone palette image, and the direction its swatches run in.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct StandaloneLayout<'a> {
+    /// The width of a strip of swatches.
+    pub width: u32,
+    /// The height of a strip of swatches.
+    pub height: u32,
+    /// The direction the swatches are laid out in.
+    pub orientation: Orientation,
+    /// A caption to draw in a band across the top of the image.
+    pub title: Option<&'a str>,
+    /// The color of the title band and (see `fill`) the end of the strip.
+    pub background: [u8; 3],
+    /// Blend the colors into a smooth gradient, interpolated in this color space, rather than
+    /// drawing swatches with hard edges.
+    pub gradient: Option<ColorSpace>,
+    /// How much of the image each color covers, to size the swatches in proportion to; `None`
+    /// for equal swatches.
+    pub weights: Option<&'a [u64]>,
+    /// What fills the end of the strip when the swatches don't reach it.
+    pub fill: PaletteFill,
+    /// How many times the palette is repeated along the strip, e.g. for a seamless tile.
+    pub repeat: u32,
+    /// How many rows of swatches to arrange the palette in. Above 1, the swatches are drawn as a
+    /// grid of equal cells, filled row by row, rather than as a strip.
+    pub rows: u32,
+    /// Give each swatch its color's alpha, saving the palette as RGBA (see `save_swatch_image`).
+    /// A gradient stays opaque.
+    pub transparent_swatches: bool,
+    /// The width of the border drawn inside each swatch, in pixels (0 for none). A gradient has
+    /// no swatches to outline.
+    pub swatch_border: u32,
+    /// The color of the border drawn inside each swatch.
+    pub swatch_border_color: [u8; 3],
+}
+
+/**
+ * Builds and saves an image containing only the palette (see `build_standalone_palette`).
+ * Returns where the swatches were drawn.
+ *
+ * [&[Color]] The palette colors.
+ * [Option<&[Color]>] The palette as seen with a color vision deficiency, drawn as a second strip.
+ * [usize] The number of swatches to divide the image into.
+ * [&StandaloneLayout] The size of the image, the direction the swatches run in, and the title.
+ * [&Path] The output file name.
+ * [u8] The quality to encode JPEG outputs at, from 1 to 100. Ignored for other formats.
+ */
+pub fn save_standalone_palette(
+    colors: &[Color],
+    simulated_colors: Option<&[Color]>,
+    number_of_colors: usize,
+    layout: &StandaloneLayout,
+    output_file_name: &Path,
+    jpeg_quality: u8,
+) -> Result<LayoutInfo> {
+    let (imgbuf, swatch_layout) =
+        build_standalone_palette(colors, simulated_colors, number_of_colors, layout)?;
+    save_swatch_image(
+        &imgbuf,
+        &swatch_layout,
+        layout.transparent_swatches,
+        output_file_name,
+        jpeg_quality,
+    )?;
+    Ok(swatch_layout)
+}
+
+/**
+ * Builds an image containing only the palette, and writes it, encoded in the given format, to
+ * `writer` (e.g. stdout) rather than a file. Returns where the swatches were drawn.
+ *
+ * [&[Color]] The palette colors.
+ * [Option<&[Color]>] The palette as seen with a color vision deficiency, drawn as a second strip.
+ * [usize] The number of swatches to divide the image into.
+ * [&StandaloneLayout] The size of the image, the direction the swatches run in, and the title.
+ * [ImageOutputFormat] The format to encode the image in.
+ * [&mut W] Where to write the encoded image.
+ */
+pub fn write_standalone_palette<W: Write>(
+    colors: &[Color],
+    simulated_colors: Option<&[Color]>,
+    number_of_colors: usize,
+    layout: &StandaloneLayout,
+    format: ImageOutputFormat,
+    writer: &mut W,
+) -> Result<LayoutInfo> {
+    let (palette, swatch_layout) =
+        build_standalone_palette(colors, simulated_colors, number_of_colors, layout)?;
+    write_swatch_image(
+        &palette,
+        &swatch_layout,
+        layout.transparent_swatches,
+        format,
+        writer,
+    )?;
+    Ok(swatch_layout)
+}
+
+/**
+ * Builds, in memory, an image containing only the palette: a strip of swatches, laid out left to
+ * right (horizontal) or top to bottom (vertical). The save and write functions wrap this; call it
+ * directly to encode or serve the image yourself.
+ *
+ * The image is widened (or, when vertical, lengthened) if necessary so that every swatch is at
+ * least one pixel long. With weights, the swatches are sized in proportion to them (see
+ * `swatch_spans`). Any of the strip the swatches don't reach is left in the background color, or
+ * covered by stretching the last swatch. When the palette is repeated, the strip is divided into
+ * `number_of_colors` swatches for each repetition, cycling through the same colors.
+ *
+ * With more than one row, the swatches are instead laid out as a grid of equal cells (see
+ * `grid_cell`). With a gradient, each strip is drawn by `build_gradient_strip` instead.
+ *
+ * A simulated palette is drawn as a second strip (or grid) below the first, or to the right of it
+ * when vertical, doubling the size of the image. A title is drawn in a band across the top. Each
+ * swatch can be outlined with a border drawn inside its edges.
+ *
+ * Alongside the image, returns where each swatch was drawn in it (below any title). A gradient has
+ * no hard-edged swatches, so none are listed for it. Fails with `ColorBuddyError::InvalidPalette`
+ * when there are no swatches.
+ *
+ * [&[Color]] The palette colors.
+ * [Option<&[Color]>] The palette as seen with a color vision deficiency, drawn as a second strip.
+ * [usize] The number of swatches to divide the image into.
+ * [&StandaloneLayout] The size of the image, the direction the swatches run in, and the title.
+ */
+pub fn build_standalone_palette(
+    colors: &[Color],
+    simulated_colors: Option<&[Color]>,
+    number_of_colors: usize,
+    layout: &StandaloneLayout,
+) -> Result<(RgbImage, LayoutInfo)> {
+    if number_of_colors == 0 {
+        return Err(
+            ColorBuddyError::InvalidPalette("there are no colors to draw".to_owned()).into(),
+        );
+    }
+
+    let StandaloneLayout {
+        width,
+        height,
+        orientation,
+        title,
+        background,
+        gradient,
+        weights,
+        fill,
+        repeat,
+        rows,
+        transparent_swatches: _,
+        swatch_border,
+        swatch_border_color,
+    } = *layout;
+    let repeat = repeat.max(1) as usize;
+    let swatches = (number_of_colors * repeat) as u32;
+    let strips: Vec<&[Color]> = std::iter::once(colors).chain(simulated_colors).collect();
+    // A grid is stacked like a horizontal strip, and wide and tall enough for a pixel per cell
+    let grid = (rows > 1 && gradient.is_none()).then(|| (rows, swatches.div_ceil(rows)));
+    let orientation = if grid.is_some() {
+        Orientation::Horizontal
+    } else {
+        orientation
+    };
+    let (width, height) = match (grid, orientation) {
+        (Some((rows, columns)), _) => (width.max(columns), height.max(rows)),
+        (None, Orientation::Horizontal) => (width.max(swatches), height),
+        (None, Orientation::Vertical) => (width, height.max(swatches)),
+    };
+    // Start from the background, so any of the strip the swatches don't reach is left in it
+    let mut imgbuf = match orientation {
+        Orientation::Horizontal => {
+            RgbImage::from_pixel(width, height * strips.len() as u32, image::Rgb(background))
+        }
+        Orientation::Vertical => {
+            RgbImage::from_pixel(width * strips.len() as u32, height, image::Rgb(background))
+        }
+    };
+
+    let length = match orientation {
+        Orientation::Horizontal => width,
+        Orientation::Vertical => height,
+    };
+    let weights = weights.map(|w| w[..number_of_colors.min(w.len())].repeat(repeat));
+    let spans = swatch_spans(length, number_of_colors * repeat, weights.as_deref());
+    let across = match orientation {
+        Orientation::Horizontal => height,
+        Orientation::Vertical => width,
+    };
+    let mut swatch_layout = LayoutInfo {
+        width: imgbuf.width(),
+        height: imgbuf.height(),
+        swatches: Vec::new(),
+    };
+
+    for (strip, strip_colors) in strips.iter().enumerate() {
+        let strip_start = strip as u32 * across;
+        if let Some(space) = gradient {
+            let strip_colors =
+                strip_colors[..number_of_colors.min(strip_colors.len())].repeat(repeat);
+            let gradient_strip = match orientation {
+                Orientation::Horizontal => {
+                    build_gradient_strip(&strip_colors, width, height, space)
+                }
+                // Turning a horizontal gradient clockwise runs it from top to bottom
+                Orientation::Vertical => image::imageops::rotate90(&build_gradient_strip(
+                    &strip_colors,
+                    height,
+                    width,
+                    space,
+                )),
+            };
+            let (x, y) = match orientation {
+                Orientation::Horizontal => (0, strip_start),
+                Orientation::Vertical => (strip_start, 0),
+            };
+            image::imageops::replace(&mut imgbuf, &gradient_strip, x as i64, y as i64);
+            continue;
+        }
+
+        if let Some(grid) = grid {
+            for i in 0..swatches as usize {
+                let Some(q) = strip_colors.get(i % number_of_colors) else {
+                    continue;
+                };
+                let ((x, y), size) = grid_cell(i as u32, grid, (width, height));
+                let position = (x, strip_start + y);
+                swatch_layout
+                    .swatches
+                    .push(SwatchRect::new(q, position, size));
+                for y in position.1..position.1 + size.1 {
+                    for x in position.0..position.0 + size.0 {
+                        imgbuf.put_pixel(x, y, image::Rgb([q.r, q.g, q.b]));
+                    }
+                }
+            }
+            continue;
+        }
+
+        let mut spans = spans.clone();
+        if fill == PaletteFill::Extend {
+            let drawn = (repeat - 1) * number_of_colors + strip_colors.len().min(number_of_colors);
+            extend_last_swatch(&mut spans, drawn, length);
+        }
+        for (i, &(start, color_length)) in spans.iter().enumerate() {
+            // A palette with fewer colors than swatches leaves the rest of each repetition empty
+            let Some(q) = strip_colors.get(i % number_of_colors) else {
+                continue;
+            };
+            let (position, size) = match orientation {
+                Orientation::Horizontal => ((start, strip_start), (color_length, across)),
+                Orientation::Vertical => ((strip_start, start), (across, color_length)),
+            };
+            swatch_layout
+                .swatches
+                .push(SwatchRect::new(q, position, size));
+            for offset in 0..color_length {
+                for a in strip_start..strip_start + across {
+                    let (x, y) = match orientation {
+                        Orientation::Horizontal => (start + offset, a),
+                        Orientation::Vertical => (a, start + offset),
+                    };
+                    imgbuf.put_pixel(x, y, image::Rgb([q.r, q.g, q.b]));
+                }
+            }
+        }
+    }
+
+    draw_swatch_borders(
+        &mut imgbuf,
+        &swatch_layout,
+        swatch_border,
+        swatch_border_color,
+    );
+
+    let imgbuf = match title {
+        Some(title) => {
+            let band_height = title_band_height(imgbuf.width());
+            swatch_layout.height += band_height;
+            swatch_layout.shift_down(band_height);
+            add_title(&imgbuf, title, background)
+        }
+        None => imgbuf,
+    };
+    Ok((imgbuf, swatch_layout))
+}
+
+/**
+ * Where a swatch goes in a grid, and its size. The cells are filled left to right, then top to
+ * bottom, and are all `width / columns` wide and `height / rows` high, so any pixels those
+ * divisions leave over at the right and bottom edges are left empty.
+ *
+ * [u32] The index of the swatch.
+ * [(u32, u32)] The number of rows and columns in the grid.
+ * [(u32, u32)] The width and height of the grid.
+ */
+fn grid_cell(
+    index: u32,
+    (rows, columns): (u32, u32),
+    (width, height): (u32, u32),
+) -> ((u32, u32), (u32, u32)) {
+    let (cell_width, cell_height) = (width / columns, height / rows);
+    (
+        (
+            (index % columns) * cell_width,
+            (index / columns) * cell_height,
+        ),
+        (cell_width, cell_height),
+    )
+}
+
+/**
+ * Builds a horizontal strip that blends smoothly from one color to the next, left to right. The
+ * colors are spread evenly across the width, with the first at the left edge and the last at the
+ * right, and each column is linearly interpolated between the two colors either side of it, in RGB
+ * or CIELAB. A single color fills the strip.
+ *
+ * [&[Color]] The colors to blend between, in order.
+ * [u32] The width of the strip.
+ * [u32] The height of the strip.
+ * [ColorSpace] The color space to interpolate in.
+ */
+pub fn build_gradient_strip(
+    colors: &[Color],
+    width: u32,
+    height: u32,
+    space: ColorSpace,
+) -> RgbImage {
+    let mut imgbuf = RgbImage::new(width, height);
+    if colors.is_empty() {
+        return imgbuf;
+    }
+
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+    let segments = colors.len() - 1;
+    for x in 0..width {
+        let position = if width > 1 {
+            x as f64 / (width - 1) as f64 * segments as f64
+        } else {
+            0.0
+        };
+        let segment = (position.floor() as usize).min(segments.saturating_sub(1));
+        let t = position - segment as f64;
+        let from = &colors[segment];
+        let to = &colors[(segment + 1).min(segments)];
+
+        let pixel = match space {
+            ColorSpace::Rgb => [
+                lerp(from.r as f64, to.r as f64, t).round() as u8,
+                lerp(from.g as f64, to.g as f64, t).round() as u8,
+                lerp(from.b as f64, to.b as f64, t).round() as u8,
+            ],
+            ColorSpace::Lab => {
+                let from = rgb_to_lab(from.r, from.g, from.b);
+                let to = rgb_to_lab(to.r, to.g, to.b);
+                lab_to_rgb(Lab {
+                    l: lerp(from.l, to.l, t),
+                    a: lerp(from.a, to.a, t),
+                    b: lerp(from.b, to.b, t),
+                })
+            }
+        };
+        for y in 0..height {
+            imgbuf.put_pixel(x, y, image::Rgb(pixel));
+        }
+    }
+
+    imgbuf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::DEFAULT_JPEG_QUALITY;
+    use crate::utils::color_conversion::rgb_to_hex;
+
+    fn layout(width: u32, height: u32, orientation: Orientation) -> StandaloneLayout<'static> {
+        StandaloneLayout {
+            width,
+            height,
+            orientation,
+            title: None,
+            background: [255, 255, 255],
+            gradient: None,
+            weights: None,
+            fill: PaletteFill::Background,
+            repeat: 1,
+            rows: 1,
+            transparent_swatches: false,
+            swatch_border: 0,
+            swatch_border_color: [0, 0, 0],
+        }
+    }
+
+    #[test]
+    fn test_build_standalone_palette() {
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+
+        let palette =
+            build_standalone_palette(&colors, None, 2, &layout(8, 3, Orientation::Horizontal))
+                .unwrap()
+                .0;
+        assert_eq!(palette.dimensions(), (8, 3));
+        for (x, _, pixel) in palette.enumerate_pixels() {
+            let expected = if x < 4 { [255, 0, 0] } else { [0, 0, 255] };
+            assert_eq!(pixel.0, expected);
+        }
+    }
+
+    #[test]
+    fn test_save_standalone_palette_orientation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("palette.png");
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+
+        // Test case 1: Horizontal swatches run left to right
+        save_standalone_palette(
+            &colors,
+            None,
+            2,
+            &layout(20, 10, Orientation::Horizontal),
+            &path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        let result = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(result.dimensions(), (20, 10));
+        assert_eq!(result.get_pixel(0, 9).0, [255, 0, 0]);
+        assert_eq!(result.get_pixel(19, 0).0, [0, 0, 255]);
+
+        // Test case 2: Vertical swatches run top to bottom
+        save_standalone_palette(
+            &colors,
+            None,
+            2,
+            &layout(20, 10, Orientation::Vertical),
+            &path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        let result = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(result.get_pixel(19, 0).0, [255, 0, 0]);
+        assert_eq!(result.get_pixel(0, 9).0, [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_save_standalone_palette_more_colors_than_pixels() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("palette.png");
+        let colors: Vec<Color> = (0..=255).map(|i| Color::new(i, 0, 255 - i, 255)).collect();
+
+        // Test case 1: The image is widened to give every swatch a pixel
+        save_standalone_palette(
+            &colors,
+            None,
+            256,
+            &layout(10, 4, Orientation::Horizontal),
+            &path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        let result = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(result.dimensions(), (256, 4));
+        assert_eq!(result.get_pixel(0, 0).0, [0, 0, 255]);
+        assert_eq!(result.get_pixel(255, 3).0, [255, 0, 0]);
+
+        // Test case 2: Vertical palettes are lengthened instead
+        save_standalone_palette(
+            &colors,
+            None,
+            256,
+            &layout(10, 4, Orientation::Vertical),
+            &path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        let result = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(result.dimensions(), (10, 256));
+
+        // Test case 3: No colors at all
+        let error = save_standalone_palette(
+            &[],
+            None,
+            0,
+            &layout(10, 4, Orientation::Horizontal),
+            &path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap_err()
+        .downcast::<ColorBuddyError>()
+        .unwrap();
+        assert!(matches!(error, ColorBuddyError::InvalidPalette(_)));
+    }
+
+    #[test]
+    fn test_save_standalone_palette_simulated_strip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("palette.png");
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+        let simulated = vec![Color::new(94, 94, 13, 255), Color::new(0, 0, 255, 255)];
+
+        // Test case 1: Horizontal palettes get a second strip below the first
+        let horizontal = layout(20, 10, Orientation::Horizontal);
+        save_standalone_palette(
+            &colors,
+            Some(&simulated),
+            2,
+            &horizontal,
+            &path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        let result = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(result.dimensions(), (20, 20));
+        assert_eq!(result.get_pixel(0, 9).0, [255, 0, 0]);
+        assert_eq!(result.get_pixel(0, 10).0, [94, 94, 13]);
+        assert_eq!(result.get_pixel(19, 19).0, [0, 0, 255]);
+
+        // Test case 2: Vertical palettes get it to the right
+        let vertical = layout(20, 10, Orientation::Vertical);
+        save_standalone_palette(
+            &colors,
+            Some(&simulated),
+            2,
+            &vertical,
+            &path,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        let result = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(result.dimensions(), (40, 10));
+        assert_eq!(result.get_pixel(19, 0).0, [255, 0, 0]);
+        assert_eq!(result.get_pixel(20, 0).0, [94, 94, 13]);
+    }
+
+    #[test]
+    fn test_build_gradient_strip() {
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+
+        // Test case 1: The midpoint is the average of the two colors in RGB
+        let strip = build_gradient_strip(&colors, 101, 2, ColorSpace::Rgb);
+        assert_eq!(strip.dimensions(), (101, 2));
+        assert_eq!(strip.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(strip.get_pixel(50, 1).0, [128, 0, 128]);
+        assert_eq!(strip.get_pixel(100, 0).0, [0, 0, 255]);
+
+        // Test case 2: ... and in CIELAB
+        let strip = build_gradient_strip(&colors, 101, 2, ColorSpace::Lab);
+        let red = rgb_to_lab(255, 0, 0);
+        let blue = rgb_to_lab(0, 0, 255);
+        let average = Lab {
+            l: (red.l + blue.l) / 2.0,
+            a: (red.a + blue.a) / 2.0,
+            b: (red.b + blue.b) / 2.0,
+        };
+        assert_eq!(strip.get_pixel(50, 0).0, lab_to_rgb(average));
+        assert_ne!(strip.get_pixel(50, 0).0, [128, 0, 128]);
+
+        // Test case 3: A single color fills the strip
+        let strip = build_gradient_strip(&colors[..1], 10, 1, ColorSpace::Rgb);
+        assert!(strip.pixels().all(|p| p.0 == [255, 0, 0]));
+    }
+
+    #[test]
+    fn test_build_standalone_palette_gradient() {
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+        let gradient = |orientation| StandaloneLayout {
+            gradient: Some(ColorSpace::Rgb),
+            ..layout(11, 11, orientation)
+        };
+
+        // Test case 1: Horizontal gradients run left to right
+        let palette =
+            build_standalone_palette(&colors, None, 2, &gradient(Orientation::Horizontal))
+                .unwrap()
+                .0;
+        assert_eq!(palette.get_pixel(0, 10).0, [255, 0, 0]);
+        assert_eq!(palette.get_pixel(5, 0).0, [128, 0, 128]);
+        assert_eq!(palette.get_pixel(10, 0).0, [0, 0, 255]);
+
+        // Test case 2: Vertical gradients run top to bottom
+        let palette = build_standalone_palette(&colors, None, 2, &gradient(Orientation::Vertical))
+            .unwrap()
+            .0;
+        assert_eq!(palette.get_pixel(10, 0).0, [255, 0, 0]);
+        assert_eq!(palette.get_pixel(0, 10).0, [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_build_standalone_palette_repeat() {
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+        let repeated = StandaloneLayout {
+            repeat: 3,
+            ..layout(60, 10, Orientation::Horizontal)
+        };
+
+        // Test case 1: Two colors repeated three times make six alternating bands
+        let palette = build_standalone_palette(&colors, None, 2, &repeated)
+            .unwrap()
+            .0;
+        assert_eq!(palette.dimensions(), (60, 10));
+        let bands: Vec<[u8; 3]> = (0..6).map(|i| palette.get_pixel(i * 10 + 5, 5).0).collect();
+        assert_eq!(
+            bands,
+            [
+                [255, 0, 0],
+                [0, 0, 255],
+                [255, 0, 0],
+                [0, 0, 255],
+                [255, 0, 0],
+                [0, 0, 255]
+            ]
+        );
+        assert_eq!(palette.get_pixel(9, 0).0, [255, 0, 0]);
+        assert_eq!(palette.get_pixel(10, 0).0, [0, 0, 255]);
+
+        // Test case 2: Every band is at least a pixel wide
+        let narrow = StandaloneLayout {
+            repeat: 3,
+            ..layout(2, 1, Orientation::Horizontal)
+        };
+        let palette = build_standalone_palette(&colors, None, 2, &narrow)
+            .unwrap()
+            .0;
+        assert_eq!(palette.width(), 6);
+        assert_eq!(palette.get_pixel(4, 0).0, [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_build_standalone_palette_layout() {
+        let colors = vec![
+            Color::new(255, 0, 0, 255),
+            Color::new(0, 255, 0, 255),
+            Color::new(0, 0, 255, 255),
+        ];
+        let weighted = StandaloneLayout {
+            weights: Some(&[5, 1, 14]),
+            ..layout(97, 12, Orientation::Horizontal)
+        };
+
+        // Test case 1: The swatches tile the strip, end to end, without gaps or overlaps
+        let (palette, swatch_layout) =
+            build_standalone_palette(&colors, None, 3, &weighted).unwrap();
+        assert_eq!((swatch_layout.width, swatch_layout.height), (97, 12));
+        assert_eq!(swatch_layout.swatches.len(), 3);
+        let mut x = 0;
+        for swatch in &swatch_layout.swatches {
+            assert_eq!((swatch.x, swatch.y, swatch.height), (x, 0, 12));
+            assert!(swatch.width > 0);
+            x += swatch.width;
+        }
+        assert_eq!(x, 97);
+
+        // Test case 2: Each rectangle is filled with the color it reports
+        for swatch in &swatch_layout.swatches {
+            let pixel =
+                palette.get_pixel(swatch.x + swatch.width - 1, swatch.y + swatch.height - 1);
+            assert_eq!(rgb_to_hex(pixel[0], pixel[1], pixel[2]), swatch.hex);
+        }
+
+        // Test case 3: A title moves the swatches down below its band
+        let titled = StandaloneLayout {
+            title: Some("Palette"),
+            ..weighted
+        };
+        let (palette, swatch_layout) = build_standalone_palette(&colors, None, 3, &titled).unwrap();
+        assert_eq!(swatch_layout.height, palette.height());
+        assert_eq!(swatch_layout.swatches[0].y, title_band_height(97));
+    }
+
+    #[test]
+    fn test_save_standalone_palette_transparent_swatches() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("palette.png");
+        let colors = vec![Color::new(255, 0, 0, 96), Color::new(0, 0, 255, 255)];
+        let transparent = StandaloneLayout {
+            transparent_swatches: true,
+            ..layout(8, 3, Orientation::Horizontal)
+        };
+
+        // Test case 1: The PNG is RGBA, and each swatch has its color's alpha
+        save_standalone_palette(
+            &colors,
+            None,
+            2,
+            &transparent,
+            &output,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        let palette = image::open(&output).unwrap();
+        assert_eq!(palette.color(), image::ColorType::Rgba8);
+        let palette = palette.to_rgba8();
+        assert_eq!(palette.get_pixel(1, 1).0, [255, 0, 0, 96]);
+        assert_eq!(palette.get_pixel(6, 1).0, [0, 0, 255, 255]);
+
+        // Test case 2: Without the option, the swatches are opaque
+        save_standalone_palette(
+            &colors,
+            None,
+            2,
+            &layout(8, 3, Orientation::Horizontal),
+            &output,
+            DEFAULT_JPEG_QUALITY,
+        )
+        .unwrap();
+        assert_eq!(
+            image::open(&output).unwrap().color(),
+            image::ColorType::Rgb8
+        );
+    }
+
+    #[test]
+    fn test_build_standalone_palette_swatch_border() {
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)];
+        let bordered = StandaloneLayout {
+            swatch_border: 2,
+            swatch_border_color: [10, 20, 30],
+            ..layout(20, 10, Orientation::Horizontal)
+        };
+
+        // Test case 1: The edges of each swatch are the border color, and the centers its color
+        let palette = build_standalone_palette(&colors, None, 2, &bordered)
+            .unwrap()
+            .0;
+        for (x, y) in [(0, 5), (1, 5), (5, 0), (9, 9), (10, 5), (19, 8)] {
+            assert_eq!(palette.get_pixel(x, y).0, [10, 20, 30], "({x}, {y})");
+        }
+        assert_eq!(palette.get_pixel(5, 5).0, [255, 0, 0]);
+        assert_eq!(palette.get_pixel(2, 2).0, [255, 0, 0]);
+        assert_eq!(palette.get_pixel(15, 5).0, [0, 0, 255]);
+
+        // Test case 2: No border by default
+        let palette =
+            build_standalone_palette(&colors, None, 2, &layout(20, 10, Orientation::Horizontal))
+                .unwrap()
+                .0;
+        assert_eq!(palette.get_pixel(0, 0).0, [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_build_standalone_palette_rows() {
+        let colors: Vec<Color> = (0..6).map(|i| Color::new(i * 40, 0, 255, 255)).collect();
+        let grid = StandaloneLayout {
+            rows: 2,
+            ..layout(60, 20, Orientation::Horizontal)
+        };
+
+        // Test case 1: Six colors in two rows make a grid of three columns by two rows
+        let (palette, swatch_layout) = build_standalone_palette(&colors, None, 6, &grid).unwrap();
+        assert_eq!(palette.dimensions(), (60, 20));
+        let cells: Vec<(u32, u32, u32, u32)> = swatch_layout
+            .swatches
+            .iter()
+            .map(|s| (s.x, s.y, s.width, s.height))
+            .collect();
+        assert_eq!(
+            cells,
+            [
+                (0, 0, 20, 10),
+                (20, 0, 20, 10),
+                (40, 0, 20, 10),
+                (0, 10, 20, 10),
+                (20, 10, 20, 10),
+                (40, 10, 20, 10)
+            ]
+        );
+        assert_eq!(palette.get_pixel(25, 5).0, [40, 0, 255]);
+        assert_eq!(palette.get_pixel(5, 15).0, [120, 0, 255]);
+        assert_eq!(palette.get_pixel(59, 19).0, [200, 0, 255]);
+
+        // Test case 2: A partly filled last row leaves the rest as background
+        let (palette, swatch_layout) =
+            build_standalone_palette(&colors[..5], None, 5, &grid).unwrap();
+        assert_eq!(swatch_layout.swatches.len(), 5);
+        assert_eq!(palette.get_pixel(45, 15).0, [255, 255, 255]);
+
+        // Test case 3: Pixels the cells don't divide into evenly are left as background
+        let uneven = StandaloneLayout {
+            rows: 2,
+            ..layout(62, 21, Orientation::Horizontal)
+        };
+        let palette = build_standalone_palette(&colors, None, 6, &uneven)
+            .unwrap()
+            .0;
+        assert_eq!(palette.get_pixel(61, 0).0, [255, 255, 255]);
+        assert_eq!(palette.get_pixel(0, 20).0, [255, 255, 255]);
+        assert_eq!(palette.get_pixel(59, 19).0, [200, 0, 255]);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::types::outcome::ProcessOutcome;
+
+/**
+ * Formats a table summarising the outcome of every processed image: one line per image with the
+ * file name, the number of colors extracted, the top color, and whether processing succeeded.
+ *
+ * [&[ProcessOutcome]] The outcomes of processing each image, in the order they were processed.
+ */
+pub fn format_summary(outcomes: &[ProcessOutcome]) -> String {
+    let file_names: Vec<String> = outcomes
+        .iter()
+        .map(|o| o.file.display().to_string())
+        .collect();
+    let file_width = file_names
+        .iter()
+        .map(|f| f.chars().count())
+        .chain(std::iter::once("File".len()))
+        .max()
+        .unwrap();
+
+    let mut summary = format!(
+        "{:<file_width$}  {:>6}  {:<7}  Status\n",
+        "File", "Colors", "Top"
+    );
+
+    for (outcome, file_name) in outcomes.iter().zip(&file_names) {
+        let status = match &outcome.error {
+            None => "OK".to_owned(),
+            Some(e) => format!("Failed: {e}"),
+        };
+        summary.push_str(&format!(
+            "{:<file_width$}  {:>6}  {:<7}  {}\n",
+            file_name,
+            outcome.color_count,
+            outcome.top_color.as_deref().unwrap_or("-"),
+            status
+        ));
+    }
+
+    let failures = outcomes.iter().filter(|o| !o.is_success()).count();
+    summary.push_str(&format!(
+        "{} processed, {} succeeded, {} failed\n",
+        outcomes.len(),
+        outcomes.len() - failures,
+        failures
+    ));
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_format_summary() {
+        let outcomes = vec![
+            ProcessOutcome {
+                file: PathBuf::from("good.png"),
+                color_count: 8,
+                top_color: Some("#1a6b3f".to_owned()),
+                error: None,
+            },
+            ProcessOutcome {
+                file: PathBuf::from("path/to/missing.png"),
+                color_count: 0,
+                top_color: None,
+                error: Some("Error opening image: path/to/missing.png".to_owned()),
+            },
+        ];
+
+        let summary = format_summary(&outcomes);
+        let lines: Vec<&str> = summary.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("File"));
+        assert!(lines[1].starts_with("good.png"));
+        assert!(lines[1].contains("#1a6b3f"));
+        assert!(lines[1].ends_with("OK"));
+        assert!(lines[2].starts_with("path/to/missing.png"));
+        assert!(lines[2].ends_with("Failed: Error opening image: path/to/missing.png"));
+        assert_eq!(lines[3], "2 processed, 1 succeeded, 1 failed");
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use exoquant::Color;
+
+use crate::types::config::Orientation;
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * Generates an SVG document containing the palette as a strip of equally sized `<rect>` swatches,
+ * optionally labelled with their hex values.
+ *
+ * [&[Color]] The palette colors.
+ * [u32] The width of the SVG.
+ * [u32] The height of the SVG.
+ * [Orientation] The direction the swatches are laid out in.
+ * [bool] Whether to label each swatch with its hex value.
+ */
+pub fn generate_svg_palette(
+    colors: &[Color],
+    width: u32,
+    height: u32,
+    orientation: Orientation,
+    labels: bool,
+) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+    );
+
+    let count = colors.len().max(1) as f64;
+    let (swatch_width, swatch_height) = match orientation {
+        Orientation::Horizontal => (width as f64 / count, height as f64),
+        Orientation::Vertical => (width as f64, height as f64 / count),
+    };
+
+    for (i, c) in colors.iter().enumerate() {
+        let (x, y) = match orientation {
+            Orientation::Horizontal => (i as f64 * swatch_width, 0.0),
+            Orientation::Vertical => (0.0, i as f64 * swatch_height),
+        };
+        let hex = rgb_to_hex(c.r, c.g, c.b);
+
+        svg.push_str(&format!(
+            "  <rect x=\"{x}\" y=\"{y}\" width=\"{swatch_width}\" height=\"{swatch_height}\" fill=\"{hex}\"/>\n"
+        ));
+
+        if labels {
+            // Pick whichever of black or white text will be legible on the swatch
+            let luma = 0.299 * c.r as f64 + 0.587 * c.g as f64 + 0.114 * c.b as f64;
+            let text_color = if luma > 128.0 { "#000000" } else { "#ffffff" };
+            svg.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" fill=\"{text_color}\" font-family=\"monospace\" text-anchor=\"middle\" dominant-baseline=\"middle\">{hex}</text>\n",
+                x + swatch_width / 2.0,
+                y + swatch_height / 2.0,
+            ));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/**
+ * Writes the palette as an SVG file.
+ *
+ * [&[Color]] The palette colors.
+ * [u32] The width of the SVG.
+ * [u32] The height of the SVG.
+ * [&Path] The output file name.
+ * [Orientation] The direction the swatches are laid out in.
+ * [bool] Whether to label each swatch with its hex value.
+ */
+pub fn write_svg_palette(
+    colors: &[Color],
+    width: u32,
+    height: u32,
+    output_file_name: &Path,
+    orientation: Orientation,
+    labels: bool,
+) -> Result<()> {
+    fs::write(
+        output_file_name,
+        generate_svg_palette(colors, width, height, orientation, labels),
+    )
+    .with_context(|| format!("Failed to save: {}", output_file_name.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_svg_palette() {
+        let colors = vec![
+            Color::new(255, 0, 0, 255),
+            Color::new(0, 255, 0, 255),
+            Color::new(0, 0, 255, 255),
+            Color::new(255, 255, 255, 255),
+        ];
+
+        // Test case 1: One rect per color, horizontally
+        let svg = generate_svg_palette(&colors, 400, 100, Orientation::Horizontal, false);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("width=\"400\" height=\"100\""));
+        assert_eq!(svg.matches("<rect ").count(), colors.len());
+        assert!(
+            svg.contains("<rect x=\"100\" y=\"0\" width=\"100\" height=\"100\" fill=\"#00ff00\"/>")
+        );
+        assert!(!svg.contains("<text"));
+
+        // Test case 2: Vertical, with labels
+        let svg = generate_svg_palette(&colors, 100, 400, Orientation::Vertical, true);
+        assert_eq!(svg.matches("<rect ").count(), colors.len());
+        assert!(
+            svg.contains("<rect x=\"0\" y=\"300\" width=\"100\" height=\"100\" fill=\"#ffffff\"/>")
+        );
+        assert_eq!(svg.matches("<text ").count(), colors.len());
+        assert!(svg.contains("fill=\"#000000\" font-family=\"monospace\" text-anchor=\"middle\" dominant-baseline=\"middle\">#ffffff</text>"));
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use anyhow::Result;
+use exoquant::Color;
+
+use crate::output::write_to_file_or_stdout;
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * Formats a palette as a newline-separated list of hex codes (`#rrggbb`), one per color.
+ *
+ * [&[Color]] The palette colors.
+ * [bool] Whether to use uppercase hex digits.
+ */
+pub fn generate_text_palette(colors: &[Color], uppercase: bool) -> String {
+    colors
+        .iter()
+        .map(|c| {
+            let hex = rgb_to_hex(c.r, c.g, c.b);
+            if uppercase {
+                format!("{}\n", hex.to_uppercase())
+            } else {
+                format!("{hex}\n")
+            }
+        })
+        .collect()
+}
+
+/**
+ * Writes a palette as a list of hex codes, either to a file or, when no file is given, to stdout.
+ *
+ * [&[Color]] The palette colors.
+ * [bool] Whether to use uppercase hex digits.
+ * [Option<&Path>] The file to write, or `None` for stdout.
+ */
+pub fn write_text_palette(colors: &[Color], uppercase: bool, output: Option<&Path>) -> Result<()> {
+    write_to_file_or_stdout(&generate_text_palette(colors, uppercase), output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette() -> Vec<Color> {
+        vec![Color::new(26, 107, 63, 255), Color::new(171, 205, 239, 255)]
+    }
+
+    #[test]
+    fn test_generate_text_palette() {
+        // Test case 1: Lowercase (default)
+        assert_eq!(
+            generate_text_palette(&palette(), false),
+            "#1a6b3f\n#abcdef\n"
+        );
+
+        // Test case 2: Uppercase
+        assert_eq!(
+            generate_text_palette(&palette(), true),
+            "#1A6B3F\n#ABCDEF\n"
+        );
+
+        // Test case 3: Empty palette
+        assert_eq!(generate_text_palette(&[], false), "");
+    }
+
+    #[test]
+    fn test_write_text_palette_to_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("palette.txt");
+
+        write_text_palette(&palette(), true, Some(&path)).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "#1A6B3F\n#ABCDEF\n"
+        );
+    }
+}
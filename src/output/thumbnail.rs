@@ -0,0 +1,45 @@
+use std::io::Cursor;
+
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use image::{DynamicImage, ImageOutputFormat, RgbImage};
+
+use crate::palette::extractor::downsample;
+use crate::types::config::DownscaleFilter;
+
+/**
+ * Shrinks an image so that its longest edge is at most `longest_edge` pixels, and returns it as a
+ * base64-encoded PNG `data:` URI, ready to embed in JSON output. Images that are already small
+ * enough are encoded as they are.
+ *
+ * [&RgbImage] The source image.
+ * [u32] The maximum length of the thumbnail's longest edge, in pixels.
+ */
+pub fn thumbnail_data_uri(input_image: &RgbImage, longest_edge: u32) -> Result<String> {
+    let thumbnail = downsample(input_image, longest_edge, DownscaleFilter::Triangle)
+        .unwrap_or_else(|| input_image.clone());
+
+    let mut png = Vec::new();
+    DynamicImage::ImageRgb8(thumbnail)
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .context("Failed to encode the thumbnail")?;
+
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_data_uri() {
+        let input_image = RgbImage::from_fn(40, 20, |x, _| image::Rgb([x as u8 * 6, 80, 160]));
+        let uri = thumbnail_data_uri(&input_image, 10).unwrap();
+
+        let encoded = uri.strip_prefix("data:image/png;base64,").unwrap();
+        let png = STANDARD.decode(encoded).unwrap();
+        let thumbnail = image::load_from_memory_with_format(&png, image::ImageFormat::Png).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (10, 5));
+    }
+}
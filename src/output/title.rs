@@ -0,0 +1,153 @@
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use image::{Rgb, RgbImage};
+
+use crate::utils::color_conversion::relative_luminance;
+
+/**
+ * The font titles are drawn in: DejaVu Sans, bundled so that titles look the same everywhere (see
+ * assets/fonts/LICENSE).
+ */
+const TITLE_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+/**
+ * The height of the title band, in pixels, is the width of the image divided by this, within the
+ * limits below.
+ */
+const TITLE_BAND_RATIO: u32 = 16;
+const MIN_TITLE_BAND_HEIGHT: u32 = 24;
+const MAX_TITLE_BAND_HEIGHT: u32 = 160;
+
+/**
+ * How much of the height of the band the text takes up.
+ */
+const TITLE_TEXT_SCALE: f32 = 0.6;
+
+/**
+ * The height of the band a title is drawn in, for an image of the given width.
+ */
+pub fn title_band_height(width: u32) -> u32 {
+    (width / TITLE_BAND_RATIO).clamp(MIN_TITLE_BAND_HEIGHT, MAX_TITLE_BAND_HEIGHT)
+}
+
+/**
+ * Adds a band across the top of an image with a title centered in it. The band is filled with the
+ * background color, and the text is drawn in black or white, whichever stands out against it. The
+ * text is shrunk if necessary so that it fits across the image.
+ *
+ * [&RgbImage] The image to add the title to.
+ * [&str] The title.
+ * [[u8; 3]] The color of the band.
+ */
+pub fn add_title(input_image: &RgbImage, title: &str, background: [u8; 3]) -> RgbImage {
+    let (width, height) = input_image.dimensions();
+    let band_height = title_band_height(width);
+    let mut imgbuf = RgbImage::from_pixel(width, height + band_height, Rgb(background));
+
+    for (x, y, pixel) in input_image.enumerate_pixels() {
+        imgbuf.put_pixel(x, y + band_height, *pixel);
+    }
+
+    let [r, g, b] = background;
+    let text_color = if relative_luminance(r, g, b) > 0.5 {
+        [0, 0, 0]
+    } else {
+        [255, 255, 255]
+    };
+
+    let font = FontRef::try_from_slice(TITLE_FONT).expect("the bundled font is valid");
+    let text_width = |scale: PxScale| {
+        let font = font.as_scaled(scale);
+        let mut previous = None;
+        let mut text_width = 0.0;
+        for c in title.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                text_width += font.kern(previous, id);
+            }
+            text_width += font.h_advance(id);
+            previous = Some(id);
+        }
+        text_width
+    };
+
+    // Leave a margin of half the band height at either end
+    let available_width = width.saturating_sub(band_height) as f32;
+    let mut scale = PxScale::from(band_height as f32 * TITLE_TEXT_SCALE);
+    let full_width = text_width(scale);
+    if full_width > available_width {
+        scale = PxScale::from(scale.y * available_width / full_width);
+    }
+
+    let scaled_font = font.as_scaled(scale);
+    let baseline = (band_height as f32 + scaled_font.ascent() + scaled_font.descent()) / 2.0;
+    let mut caret = point((width as f32 - text_width(scale)) / 2.0, baseline);
+    let mut previous = None;
+
+    for c in title.chars() {
+        let id = scaled_font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret.x += scaled_font.kern(previous, id);
+        }
+        previous = Some(id);
+
+        let glyph = id.with_scale_and_position(scale, caret);
+        caret.x += scaled_font.h_advance(id);
+
+        if let Some(outline) = font.outline_glyph(glyph) {
+            let bounds = outline.px_bounds();
+            outline.draw(|x, y, coverage| {
+                let x = bounds.min.x as i32 + x as i32;
+                let y = bounds.min.y as i32 + y as i32;
+                if x < 0 || y < 0 || x as u32 >= width || y as u32 >= band_height {
+                    return;
+                }
+
+                let pixel = imgbuf.get_pixel_mut(x as u32, y as u32);
+                for i in 0..3 {
+                    pixel[i] = (pixel[i] as f32 * (1.0 - coverage)
+                        + text_color[i] as f32 * coverage)
+                        .round() as u8;
+                }
+            });
+        }
+    }
+
+    imgbuf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_title() {
+        let input_image = RgbImage::from_pixel(200, 50, Rgb([255, 0, 0]));
+
+        // Test case 1: A band is added above the image, with dark text on a light background
+        let result = add_title(&input_image, "Sunset", [255, 255, 255]);
+        assert_eq!(result.dimensions(), (200, 50 + MIN_TITLE_BAND_HEIGHT));
+        assert_eq!(result.get_pixel(0, 0).0, [255, 255, 255]);
+        assert!((0..200)
+            .flat_map(|x| (0..MIN_TITLE_BAND_HEIGHT).map(move |y| (x, y)))
+            .any(|(x, y)| result.get_pixel(x, y).0[0] < 128));
+        assert_eq!(result.get_pixel(0, MIN_TITLE_BAND_HEIGHT).0, [255, 0, 0]);
+
+        // Test case 2: Light text on a dark background
+        let result = add_title(&input_image, "Sunset", [0, 0, 0]);
+        assert!(result.pixels().take(200 * 24).any(|p| p.0[0] > 128));
+
+        // Test case 3: Long titles are shrunk to fit, leaving the margins clear
+        let result = add_title(&input_image, &"Sunset ".repeat(20), [255, 255, 255]);
+        for y in 0..MIN_TITLE_BAND_HEIGHT {
+            assert_eq!(result.get_pixel(0, y).0, [255, 255, 255]);
+            assert_eq!(result.get_pixel(199, y).0, [255, 255, 255]);
+        }
+    }
+
+    #[test]
+    fn test_title_band_height() {
+        assert_eq!(title_band_height(100), MIN_TITLE_BAND_HEIGHT);
+        assert_eq!(title_band_height(800), 50);
+        assert_eq!(title_band_height(10000), MAX_TITLE_BAND_HEIGHT);
+    }
+}
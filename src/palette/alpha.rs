@@ -0,0 +1,71 @@
+use exoquant::Color;
+use image::RgbaImage;
+
+use crate::palette::kmeans::nearest;
+
+/**
+ * Gives each palette color the alpha of the part of the image it stands for, rather than leaving
+ * it opaque: the mean alpha of the pixels nearest to it (Euclidean distance in RGB). Colors no
+ * pixel is nearest to are left as they are.
+ *
+ * [Vec<Color>] The extracted palette.
+ * [&RgbaImage] The source image, with its alpha channel.
+ */
+pub fn with_source_alpha(mut colors: Vec<Color>, input_image: &RgbaImage) -> Vec<Color> {
+    if colors.is_empty() {
+        return colors;
+    }
+
+    let palette: Vec<[f64; 3]> = colors
+        .iter()
+        .map(|c| [c.r as f64, c.g as f64, c.b as f64])
+        .collect();
+    // The total alpha of the pixels nearest each color, and how many there are
+    let mut totals = vec![(0u64, 0u64); colors.len()];
+    for pixel in input_image.pixels() {
+        let [r, g, b, a] = pixel.0;
+        let total = &mut totals[nearest(&[r as f64, g as f64, b as f64], &palette)];
+        total.0 += a as u64;
+        total.1 += 1;
+    }
+
+    for (color, (alpha, count)) in colors.iter_mut().zip(totals) {
+        if count > 0 {
+            color.a = ((alpha as f64 / count as f64).round()) as u8;
+        }
+    }
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_with_source_alpha() {
+        // A half-transparent red half, and an opaque blue half
+        let image = RgbaImage::from_fn(8, 4, |x, _| {
+            if x < 4 {
+                Rgba([255, 0, 0, 128])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        });
+        let colors = vec![
+            Color::new(250, 0, 0, 255),
+            Color::new(0, 0, 250, 255),
+            Color::new(0, 255, 0, 255),
+        ];
+
+        let colors = with_source_alpha(colors, &image);
+        let alphas: Vec<u8> = colors.iter().map(|c| c.a).collect();
+
+        // Test case 1: Each color takes the alpha of its pixels, and the color channels are kept
+        assert_eq!(alphas[..2], [128, 255]);
+        assert_eq!((colors[0].r, colors[0].g, colors[0].b), (250, 0, 0));
+
+        // Test case 2: A color no pixel is nearest to is left as it was
+        assert_eq!(alphas[2], 255);
+    }
+}
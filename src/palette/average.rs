@@ -0,0 +1,109 @@
+use exoquant::Color;
+use image::RgbImage;
+
+use crate::utils::color_conversion::{linear_to_srgb, srgb_to_linear};
+
+/**
+ * The mean color of an image: the average of each channel across every pixel, rounded to the
+ * nearest integer. It's much cheaper than extracting a palette, as there's no quantisation at all.
+ *
+ * This isn't the same as the dominant color, which is the one that covers the most pixels: the
+ * mean of an image that's half black and half white is mid-gray, which appears nowhere in it,
+ * whereas its dominant color is black or white. The mean summarises the overall tone of an image;
+ * the dominant color is one that's actually in it.
+ *
+ * An empty image averages to black.
+ *
+ * [&RgbImage] The image to average.
+ */
+pub fn mean_color(input_image: &RgbImage) -> Color {
+    let pixels = (input_image.width() as u64 * input_image.height() as u64).max(1);
+    let mut sums = [0u64; 3];
+    for p in input_image.pixels() {
+        for (sum, channel) in sums.iter_mut().zip(p.0) {
+            *sum += channel as u64;
+        }
+    }
+
+    let [r, g, b] = sums.map(|sum| ((sum as f64 / pixels as f64).round()) as u8);
+    Color::new(r, g, b, 255)
+}
+
+/**
+ * The mean color of an image in linear light: each channel is linearised before averaging, and the
+ * average gamma-encoded back to sRGB (see `mean_color`). This is the color the image blurs to at a
+ * distance, so half black and half white averages to a light gray (#bcbcbc) rather than mid-gray.
+ *
+ * An empty image averages to black.
+ *
+ * [&RgbImage] The image to average.
+ */
+pub fn mean_color_linear(input_image: &RgbImage) -> Color {
+    let pixels = (input_image.width() as u64 * input_image.height() as u64).max(1);
+    let mut sums = [0f64; 3];
+    for p in input_image.pixels() {
+        for (sum, channel) in sums.iter_mut().zip(p.0) {
+            *sum += srgb_to_linear(channel);
+        }
+    }
+
+    let [r, g, b] = sums.map(|sum| linear_to_srgb(sum / pixels as f64));
+    Color::new(r, g, b, 255)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    #[test]
+    fn test_mean_color() {
+        // Test case 1: Half black and half white averages to mid-gray
+        let image = RgbImage::from_fn(10, 4, |x, _| {
+            if x < 5 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        });
+        let mean = mean_color(&image);
+        assert_eq!([mean.r, mean.g, mean.b], [128, 128, 128]);
+
+        // Test case 2: Each channel is averaged separately
+        let image = RgbImage::from_fn(4, 1, |x, _| match x {
+            0 => Rgb([255, 0, 0]),
+            _ => Rgb([0, 0, 255]),
+        });
+        let mean = mean_color(&image);
+        assert_eq!([mean.r, mean.g, mean.b], [64, 0, 191]);
+
+        // Test case 3: An empty image
+        let mean = mean_color(&RgbImage::new(0, 0));
+        assert_eq!([mean.r, mean.g, mean.b], [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_mean_color_linear() {
+        let image = RgbImage::from_fn(10, 4, |x, _| {
+            if x < 5 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        });
+
+        // Test case 1: Half black and half white averages to a light gray, not mid-gray
+        let mean = mean_color_linear(&image);
+        assert_eq!([mean.r, mean.g, mean.b], [188, 188, 188]);
+        let naive = mean_color(&image);
+        assert_ne!([mean.r, mean.g, mean.b], [naive.r, naive.g, naive.b]);
+
+        // Test case 2: A single color averages to itself
+        let mean = mean_color_linear(&RgbImage::from_pixel(3, 3, Rgb([30, 140, 220])));
+        assert_eq!([mean.r, mean.g, mean.b], [30, 140, 220]);
+
+        // Test case 3: An empty image
+        let mean = mean_color_linear(&RgbImage::new(0, 0));
+        assert_eq!([mean.r, mean.g, mean.b], [0, 0, 0]);
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::{anyhow, Result};
+use image::imageops::crop_imm;
+use image::RgbImage;
+
+/**
+ * Crops a border of the given width off every edge of an image, e.g. the noisy edge of a scan, so
+ * that it's left out of the palette. Returns `None` when the border is 0.
+ *
+ * Fails when the border is half the smallest dimension of the image or more, as there'd be
+ * nothing left.
+ *
+ * [&RgbImage] The image to be cropped.
+ * [u32] The width of the border, in pixels.
+ */
+pub fn crop_border(input_image: &RgbImage, border: u32) -> Result<Option<RgbImage>> {
+    if border == 0 {
+        return Ok(None);
+    }
+
+    let (width, height) = input_image.dimensions();
+    if border.saturating_mul(2) >= width.min(height) {
+        return Err(anyhow!(
+            "A {border}px border is at least half of the {width}x{height} image, so would leave nothing to extract from"
+        ));
+    }
+
+    Ok(Some(
+        crop_imm(
+            input_image,
+            border,
+            border,
+            width - 2 * border,
+            height - 2 * border,
+        )
+        .to_image(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    #[test]
+    fn test_crop_border() {
+        let image = RgbImage::from_fn(10, 8, |x, y| {
+            if x < 2 || y < 2 || x >= 8 || y >= 6 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+
+        // Test case 1: Only the interior is left
+        let cropped = crop_border(&image, 2).unwrap().unwrap();
+        assert_eq!(cropped.dimensions(), (6, 4));
+        assert!(cropped.pixels().all(|p| p.0 == [0, 0, 255]));
+
+        // Test case 2: No border
+        assert!(crop_border(&image, 0).unwrap().is_none());
+
+        // Test case 3: Borders that would leave nothing
+        assert!(crop_border(&image, 4).is_err());
+        assert!(crop_border(&image, u32::MAX).is_err());
+        assert!(crop_border(&image, 3).is_ok());
+    }
+}
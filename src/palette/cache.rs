@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use exoquant::Color;
+use sha2::{Digest, Sha256};
+
+/**
+ * Computes the key a palette is cached under: the SHA-256 digest, as lowercase hex, of everything
+ * the palette depends on, e.g. the pixels it's extracted from and the extraction options. Each part
+ * is prefixed with its length, so that moving bytes from one part to the next changes the key.
+ *
+ * [&[&[u8]]] The inputs to the extraction.
+ */
+pub fn palette_cache_key(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/**
+ * Reads a palette cached under the key, if there is one. A missing or unreadable cache file is a
+ * miss, so the palette is extracted afresh.
+ *
+ * [&Path] The cache directory.
+ * [&str] The cache key, from `palette_cache_key`.
+ */
+pub fn read_cached_palette(cache_dir: &Path, key: &str) -> Option<Vec<Color>> {
+    let json = fs::read(cache_dir.join(format!("{key}.json"))).ok()?;
+    let colors: Vec<[u8; 4]> = serde_json::from_slice(&json).ok()?;
+    Some(
+        colors
+            .into_iter()
+            .map(|[r, g, b, a]| Color::new(r, g, b, a))
+            .collect(),
+    )
+}
+
+/**
+ * Caches a palette under the key, as a JSON array of `[r, g, b, a]` colors. The cache directory is
+ * created if it doesn't exist.
+ *
+ * [&Path] The cache directory.
+ * [&str] The cache key, from `palette_cache_key`.
+ * [&[Color]] The palette.
+ */
+pub fn write_cached_palette(cache_dir: &Path, key: &str, colors: &[Color]) -> Result<()> {
+    let path = cache_dir.join(format!("{key}.json"));
+    let colors: Vec<[u8; 4]> = colors.iter().map(|c| [c.r, c.g, c.b, c.a]).collect();
+
+    fs::create_dir_all(cache_dir)
+        .and_then(|_| fs::write(&path, serde_json::to_vec(&colors)?))
+        .with_context(|| format!("Failed to cache palette: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette_cache_key() {
+        let key = palette_cache_key(&[b"pixels", b"k-means 8"]);
+
+        // Test case 1: The same inputs give the same key, as hex
+        assert_eq!(key, palette_cache_key(&[b"pixels", b"k-means 8"]));
+        assert_eq!(key.len(), 64);
+
+        // Test case 2: Any change to the inputs, or where they're split, changes it
+        assert_ne!(key, palette_cache_key(&[b"pixels", b"k-means 6"]));
+        assert_ne!(key, palette_cache_key(&[b"pixel", b"sk-means 8"]));
+    }
+
+    #[test]
+    fn test_cached_palette_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = dir.path().join("cache");
+        let colors = vec![Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 128)];
+
+        // Test case 1: Nothing is cached yet
+        assert!(read_cached_palette(&cache_dir, "key").is_none());
+
+        // Test case 2: A cached palette reads back the same, creating the directory
+        write_cached_palette(&cache_dir, "key", &colors).unwrap();
+        let cached = read_cached_palette(&cache_dir, "key").unwrap();
+        let rgba = |c: &[Color]| c.iter().map(|c| [c.r, c.g, c.b, c.a]).collect::<Vec<_>>();
+        assert_eq!(rgba(&cached), rgba(&colors));
+
+        // Test case 3: A corrupt cache file is a miss
+        fs::write(cache_dir.join("key.json"), b"not json").unwrap();
+        assert!(read_cached_palette(&cache_dir, "key").is_none());
+    }
+}
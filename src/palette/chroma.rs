@@ -0,0 +1,52 @@
+use exoquant::Color;
+
+use crate::utils::color_conversion::{hsl_to_rgb, rgb_to_hsl, Hsl};
+
+/**
+ * Makes palette colors more (or less) vivid by multiplying their HSL saturation by a factor,
+ * clamped to at most fully saturated. Hue, lightness and alpha are kept, so grays stay gray.
+ * A factor of 1 leaves the palette as it is.
+ *
+ * [Vec<Color>] The extracted palette.
+ * [f32] What to multiply each color's saturation by.
+ */
+pub fn boost_chroma(colors: Vec<Color>, factor: f32) -> Vec<Color> {
+    colors
+        .into_iter()
+        .map(|color| {
+            let hsl = rgb_to_hsl(color.r, color.g, color.b);
+            let [r, g, b] = hsl_to_rgb(Hsl {
+                s: (hsl.s * factor as f64).clamp(0.0, 1.0),
+                ..hsl
+            });
+            Color::new(r, g, b, color.a)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boost_chroma() {
+        let muted = Color::new(120, 100, 90, 255);
+        let before = rgb_to_hsl(muted.r, muted.g, muted.b);
+
+        // Test case 1: Boosting increases the saturation, keeping the hue and lightness
+        let boosted = boost_chroma(vec![muted], 2.0)[0];
+        let after = rgb_to_hsl(boosted.r, boosted.g, boosted.b);
+        assert!(after.s > before.s * 1.8);
+        assert!((after.h - before.h).abs() < 2.0);
+        assert!((after.l - before.l).abs() < 0.01);
+        assert_eq!(boosted.a, 255);
+
+        // Test case 2: Saturation is clamped, and grays have none to boost
+        let boosted = boost_chroma(vec![muted, Color::new(90, 90, 90, 255)], 100.0);
+        assert!((rgb_to_hsl(boosted[0].r, boosted[0].g, boosted[0].b).s - 1.0).abs() < 0.02);
+        assert!(boosted[1] == Color::new(90, 90, 90, 255));
+
+        // Test case 3: A factor of 1 changes nothing
+        assert!(boost_chroma(vec![muted], 1.0) == vec![muted]);
+    }
+}
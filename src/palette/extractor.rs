@@ -0,0 +1,779 @@
+use exoquant::{
+    generate_palette, optimizer, Color, ColorSpace as QuantisationSpace, SimpleColorSpace,
+};
+use image::imageops::{self, FilterType};
+use image::{ImageBuffer, Rgb, RgbImage};
+use mcq::ColorNode;
+use mcq::MMCQ;
+
+use crate::palette::histogram::{distinct_colors, rgb_histogram};
+use crate::palette::kmeans::{kmeans, kmeans_seeded, lab_kmeans_palette, nearest, MAX_ITERATIONS};
+use crate::palette::popularity::popularity_palette;
+use crate::palette::uniform::uniform_palette;
+use crate::types::config::{ColorSpace, DownscaleFilter, QuantisationMethod};
+
+/**
+ * An image with 16 bits per RGB channel.
+ */
+pub type Rgb16Image = ImageBuffer<Rgb<u16>, Vec<u16>>;
+
+/**
+ * The palette extracted from an image, along with how much of the image each color represents.
+ */
+#[derive(Clone)]
+pub struct ExtractionResult {
+    /// The extracted colors. There may be fewer than were requested.
+    pub colors: Vec<Color>,
+    /// The number of pixels closest to each color, in the same order as `colors`.
+    pub counts: Vec<u64>,
+    /// The method that was used to extract the colors.
+    pub method: QuantisationMethod,
+}
+
+/**
+ * Internally we deal with a Vector<Color> (`Color` provided by the exoquant crate).
+ * This helper function converts a Vector of MCQ `ColorNode`s into a Vector of exoquant `Color`s.
+ */
+fn mcq_color_nodes_to_exoquant_colors(mcq_color_nodes: Vec<ColorNode>) -> Vec<Color> {
+    mcq_color_nodes
+        .iter()
+        .map(|c| Color {
+            r: c.red,
+            g: c.grn,
+            b: c.blu,
+            a: 0xff,
+        })
+        .collect()
+}
+
+/**
+ * Shrinks an image so that its longest edge is at most `sample_size` pixels, preserving the aspect
+ * ratio. Extracting from the smaller image is much faster and barely affects the palette.
+ *
+ * Returns `None` when the image is already small enough.
+ *
+ * [&RgbImage] The image to be downsampled.
+ * [u32] The maximum length of the longest edge, in pixels.
+ * [DownscaleFilter] The resampling filter to use.
+ */
+pub fn downsample(
+    input_image: &RgbImage,
+    sample_size: u32,
+    filter: DownscaleFilter,
+) -> Option<RgbImage> {
+    let (width, height) = input_image.dimensions();
+    let longest_edge = width.max(height);
+    if longest_edge <= sample_size {
+        return None;
+    }
+
+    let scale = sample_size as f64 / longest_edge as f64;
+    let new_width = ((width as f64 * scale).round() as u32).max(1);
+    let new_height = ((height as f64 * scale).round() as u32).max(1);
+
+    let filter = match filter {
+        DownscaleFilter::Nearest => FilterType::Nearest,
+        DownscaleFilter::Triangle => FilterType::Triangle,
+        DownscaleFilter::Gaussian => FilterType::Gaussian,
+        DownscaleFilter::Lanczos3 => FilterType::Lanczos3,
+    };
+    Some(imageops::resize(input_image, new_width, new_height, filter))
+}
+
+/**
+ * This function abstracts the extraction of the Vector of `Color`s depending on the chosen
+ * quantisation method. `QuantisationMethod::Auto` picks a method to suit the image first (see
+ * `choose_method`).
+ *
+ * [&RgbImage] The image to be processed.
+ * [usize] The number of colors required for the palette.
+ * [QuantisationMethod] The quantisation method to be used.
+ * [ColorSpace] The color space K-Means clusters in (ignored by the other methods).
+ **/
+pub fn extract_palette(
+    input_image: &RgbImage,
+    number_of_colors: usize,
+    quantisation_method: QuantisationMethod,
+    color_space: ColorSpace,
+) -> Vec<Color> {
+    match quantisation_method {
+        QuantisationMethod::Auto => extract_palette(
+            input_image,
+            number_of_colors,
+            choose_method(input_image),
+            color_space,
+        ),
+        QuantisationMethod::KMeans if color_space == ColorSpace::Lab => {
+            lab_kmeans_palette(input_image, number_of_colors, &[], MAX_ITERATIONS)
+        }
+        _ => extract_palette_with_space(
+            input_image,
+            number_of_colors,
+            quantisation_method,
+            &SimpleColorSpace::default(),
+        ),
+    }
+}
+
+/**
+ * Extracts a palette like `extract_palette`, but lets the caller choose the exoquant color space
+ * K-Means works in, e.g. a `SimpleColorSpace` with a different gamma, or a custom implementation
+ * of `exoquant::ColorSpace`. `extract_palette` uses `SimpleColorSpace::default()`.
+ *
+ * Median Cut always works on the raw sRGB values, and Uniform on a fixed grid, so the color space
+ * is ignored for them.
+ *
+ * [&RgbImage] The image to be processed.
+ * [usize] The number of colors required for the palette.
+ * [QuantisationMethod] The quantisation method to be used.
+ * [&S] The exoquant color space K-Means clusters in.
+ **/
+pub fn extract_palette_with_space<S: QuantisationSpace>(
+    input_image: &RgbImage,
+    number_of_colors: usize,
+    quantisation_method: QuantisationMethod,
+    color_space: &S,
+) -> Vec<Color> {
+    match quantisation_method {
+        QuantisationMethod::MedianCut => {
+            // MMCQ expects one packed pixel per u32, with red in the lowest byte.
+            let data: Vec<u32> = input_image
+                .pixels()
+                .map(|p| u32::from_le_bytes([p[0], p[1], p[2], 0xff]))
+                .collect();
+            let mcq =
+                MMCQ::from_pixels_u32_rgba(data.as_slice(), number_of_colors.try_into().unwrap());
+
+            mcq_color_nodes_to_exoquant_colors(mcq.get_quantized_colors().to_vec())
+        }
+        QuantisationMethod::Uniform => uniform_palette(input_image, number_of_colors),
+        QuantisationMethod::Popularity => popularity_palette(input_image, number_of_colors),
+        QuantisationMethod::Auto => extract_palette_with_space(
+            input_image,
+            number_of_colors,
+            choose_method(input_image),
+            color_space,
+        ),
+        QuantisationMethod::KMeans => generate_palette(
+            &rgb_histogram(input_image),
+            color_space,
+            &optimizer::KMeans,
+            number_of_colors,
+        ),
+    }
+}
+
+/**
+ * Images with at most this many distinct colors (e.g. pixel art, logos, screenshots) are
+ * quantised with Popularity by `QuantisationMethod::Auto`.
+ */
+const AUTO_LOW_COLOR_LIMIT: usize = 256;
+
+/**
+ * Images with more pixels than this are quantised with Median Cut by `QuantisationMethod::Auto`,
+ * as it's much quicker than K-Means on them.
+ */
+const AUTO_LARGE_IMAGE_PIXELS: u64 = 16_000_000;
+
+/**
+ * Picks the quantisation method `QuantisationMethod::Auto` stands for, from the image: Median Cut
+ * for very large images (more than 16 megapixels), Popularity for images with few distinct colors
+ * (at most 256), whose colors it keeps exactly, and K-Means for everything else, e.g. photos.
+ *
+ * [&RgbImage] The image to be processed.
+ */
+pub fn choose_method(input_image: &RgbImage) -> QuantisationMethod {
+    let pixels = input_image.width() as u64 * input_image.height() as u64;
+    if pixels > AUTO_LARGE_IMAGE_PIXELS {
+        return QuantisationMethod::MedianCut;
+    }
+
+    if distinct_colors(input_image).len() <= AUTO_LOW_COLOR_LIMIT {
+        QuantisationMethod::Popularity
+    } else {
+        QuantisationMethod::KMeans
+    }
+}
+
+/**
+ * Extracts a palette with the crate's own K-Means rather than exoquant's, making at most the given
+ * number of refinement passes. The assignment of pixels to clusters runs on every core, so this is
+ * much quicker on large images, and the palette is close to exoquant's once the clusters have
+ * settled. Fewer passes trade some accuracy for speed.
+ *
+ * [&RgbImage] The image to be processed.
+ * [usize] The number of colors required for the palette.
+ * [ColorSpace] The color space to cluster in.
+ * [usize] The maximum number of refinement passes.
+ * [&[[u8; 3]]] Colors to start the clusters from, so the palette stays close to them (e.g. brand
+ * colors). Any more clusters needed are started as usual.
+ **/
+pub fn extract_palette_kmeans(
+    input_image: &RgbImage,
+    number_of_colors: usize,
+    color_space: ColorSpace,
+    iterations: usize,
+    seeds: &[[u8; 3]],
+) -> Vec<Color> {
+    if color_space == ColorSpace::Lab {
+        return lab_kmeans_palette(input_image, number_of_colors, seeds, iterations);
+    }
+
+    let points: Vec<([f64; 3], u64)> = distinct_colors(input_image)
+        .into_iter()
+        .map(|(rgb, count)| (rgb.map(f64::from), count))
+        .collect();
+
+    let seeds: Vec<[f64; 3]> = seeds.iter().map(|seed| seed.map(f64::from)).collect();
+
+    kmeans_seeded(&points, number_of_colors, &seeds, iterations)
+        .into_iter()
+        .map(|centroid| {
+            let [r, g, b] = centroid.map(|c| c.round() as u8);
+            Color::new(r, g, b, 0xff)
+        })
+        .collect()
+}
+
+/**
+ * The most colors a palette can have.
+ */
+pub const MAX_COLORS: usize = 256;
+
+/**
+ * The fraction of the image's total variance an extra color has to explain for `--auto-colors` to
+ * keep it.
+ */
+const AUTO_COLORS_THRESHOLD: f64 = 0.01;
+
+/**
+ * Extracts a palette without being told how many colors to use. Palettes of 1, 2, 3, ... colors
+ * are extracted in turn, until adding another color reduces the within-cluster variance (the sum
+ * of the squared RGB distances of the pixels to their nearest color) by less than 1% of the
+ * image's total variance: the "elbow" of the curve.
+ *
+ * [&RgbImage] The image to be processed.
+ * [QuantisationMethod] The quantisation method to be used.
+ * [ColorSpace] The color space K-Means clusters in (ignored by the other methods).
+ * [usize] The most colors the palette may have.
+ **/
+pub fn extract_palette_auto(
+    input_image: &RgbImage,
+    quantisation_method: QuantisationMethod,
+    color_space: ColorSpace,
+    max_colors: usize,
+) -> Vec<Color> {
+    let points: Vec<([f64; 3], u64)> = distinct_colors(input_image)
+        .into_iter()
+        .map(|(rgb, count)| (rgb.map(f64::from), count))
+        .collect();
+    let variance = |colors: &[Color]| -> f64 {
+        let centroids: Vec<[f64; 3]> = colors
+            .iter()
+            .map(|c| [c.r as f64, c.g as f64, c.b as f64])
+            .collect();
+        points
+            .iter()
+            .map(|(point, count)| {
+                let centroid = centroids[nearest(point, &centroids)];
+                let distance: f64 = (0..3).map(|i| (point[i] - centroid[i]).powi(2)).sum();
+                distance * *count as f64
+            })
+            .sum()
+    };
+
+    let mut palette = extract_palette(input_image, 1, quantisation_method, color_space);
+    let total_variance = variance(&palette);
+    let mut best_variance = total_variance;
+    let mut missed = false;
+
+    for number_of_colors in 2..=max_colors {
+        if best_variance == 0.0 {
+            break;
+        }
+
+        let candidate = extract_palette(
+            input_image,
+            number_of_colors,
+            quantisation_method,
+            color_space,
+        );
+        // The image has run out of distinct colors to split
+        if candidate.len() <= palette.len() {
+            break;
+        }
+
+        let candidate_variance = variance(&candidate);
+        if best_variance - candidate_variance < AUTO_COLORS_THRESHOLD * total_variance {
+            // Median Cut occasionally does worse with one more color (e.g. returning an empty
+            // box as black) and then recovers, so one miss is forgiven
+            if missed {
+                break;
+            }
+            missed = true;
+            continue;
+        }
+
+        palette = candidate;
+        best_variance = candidate_variance;
+        missed = false;
+    }
+
+    palette
+}
+
+/**
+ * Extracts a palette like `extract_palette`, and also reports how many of the image's pixels are
+ * closest to each extracted color (Euclidean distance in RGB), so the counts always sum to the
+ * number of pixels in the image.
+ *
+ * [&RgbImage] The image to be processed.
+ * [usize] The number of colors required for the palette.
+ * [QuantisationMethod] The quantisation method to be used.
+ * [ColorSpace] The color space K-Means clusters in (ignored by the other methods).
+ **/
+pub fn extract_palette_detailed(
+    input_image: &RgbImage,
+    number_of_colors: usize,
+    quantisation_method: QuantisationMethod,
+    color_space: ColorSpace,
+) -> ExtractionResult {
+    let quantisation_method = match quantisation_method {
+        QuantisationMethod::Auto => choose_method(input_image),
+        method => method,
+    };
+    let colors = extract_palette(
+        input_image,
+        number_of_colors,
+        quantisation_method,
+        color_space,
+    );
+    let counts = count_pixels(input_image, &colors);
+
+    ExtractionResult {
+        colors,
+        counts,
+        method: quantisation_method,
+    }
+}
+
+/**
+ * Extracts a palette from a 16-bit image without first truncating it to 8 bits, so that shades
+ * which differ by less than one 8-bit step are still told apart while clustering. Only the final
+ * centroids are quantised to 8 bits.
+ *
+ * This always uses K-Means in RGB (MMCQ and exoquant only accept 8-bit pixels). The counts are
+ * the number of pixels assigned to each cluster.
+ *
+ * [&Rgb16Image] The image to be processed.
+ * [usize] The number of colors required for the palette.
+ */
+pub fn extract_palette_high_precision(
+    input_image: &Rgb16Image,
+    number_of_colors: usize,
+) -> ExtractionResult {
+    let points: Vec<([f64; 3], u64)> = distinct_colors(input_image)
+        .into_iter()
+        .map(|(rgb, count)| (rgb.map(f64::from), count))
+        .collect();
+    let centroids = kmeans(&points, number_of_colors);
+
+    let mut counts = vec![0; centroids.len()];
+    for (point, count) in &points {
+        counts[nearest(point, &centroids)] += count;
+    }
+
+    let colors = centroids
+        .iter()
+        .map(|centroid| {
+            let [r, g, b] = centroid.map(|c| (c / 257.0).round() as u8);
+            Color::new(r, g, b, 0xff)
+        })
+        .collect();
+
+    ExtractionResult {
+        colors,
+        counts,
+        method: QuantisationMethod::KMeans,
+    }
+}
+
+/**
+ * Counts how many of the image's pixels are closest to each of the given colors.
+ *
+ * [&RgbImage] The image whose pixels are counted.
+ * [&[Color]] The palette colors.
+ */
+pub fn count_pixels(input_image: &RgbImage, colors: &[Color]) -> Vec<u64> {
+    let mut counts = vec![0; colors.len()];
+    if colors.is_empty() {
+        return counts;
+    }
+
+    let centroids: Vec<[f64; 3]> = colors
+        .iter()
+        .map(|c| [c.r as f64, c.g as f64, c.b as f64])
+        .collect();
+    for (rgb, count) in distinct_colors(input_image) {
+        counts[nearest(&rgb.map(|c| c as f64), &centroids)] += count;
+    }
+
+    counts
+}
+
+/**
+ * The most prominent color of a palette: the one with the highest count (the first, on a tie).
+ * Falls back to the first color when there are no counts to go by. Returns `None` for an empty
+ * palette.
+ *
+ * [&[Color]] The palette colors.
+ * [&[u64]] How many pixels each color covers, e.g. from `count_pixels`.
+ */
+pub fn dominant_color<'a>(colors: &'a [Color], counts: &[u64]) -> Option<&'a Color> {
+    let most_common = counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .fold(None, |best: Option<(usize, u64)>, (i, &count)| match best {
+            Some((_, best_count)) if best_count >= count => best,
+            _ => Some((i, count)),
+        });
+
+    match most_common {
+        Some((i, _)) => colors.get(i),
+        None => colors.first(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dominant_color() {
+        let colors = vec![
+            Color::new(255, 0, 0, 255),
+            Color::new(0, 255, 0, 255),
+            Color::new(0, 0, 255, 255),
+        ];
+
+        // Test case 1: The color with the highest count, and the first on a tie
+        assert_eq!(dominant_color(&colors, &[10, 70, 20]).unwrap().g, 255);
+        assert_eq!(dominant_color(&colors, &[10, 45, 45]).unwrap().g, 255);
+
+        // Test case 2: Without counts, the first color
+        assert_eq!(dominant_color(&colors, &[]).unwrap().r, 255);
+        assert_eq!(dominant_color(&colors, &[0, 0, 0]).unwrap().r, 255);
+
+        // Test case 3: An empty palette has none
+        assert!(dominant_color(&[], &[]).is_none());
+    }
+
+    #[test]
+    fn test_extract_palette_auto() {
+        let stripes = [[200, 30, 30], [30, 200, 30], [30, 30, 200]];
+        let image = RgbImage::from_fn(30, 10, |x, _| Rgb(stripes[(x / 10) as usize]));
+
+        // Test case 1: A three color image settles on three colors, whichever the method
+        for method in [QuantisationMethod::KMeans, QuantisationMethod::MedianCut] {
+            let palette = extract_palette_auto(&image, method, ColorSpace::Rgb, 256);
+            assert_eq!(palette.len(), 3, "{method}");
+        }
+
+        // Test case 2: The cap is respected
+        let palette = extract_palette_auto(&image, QuantisationMethod::KMeans, ColorSpace::Rgb, 2);
+        assert_eq!(palette.len(), 2);
+
+        // Test case 3: A single color image needs only one
+        let image = RgbImage::from_pixel(10, 10, Rgb([90, 90, 90]));
+        let palette =
+            extract_palette_auto(&image, QuantisationMethod::KMeans, ColorSpace::Rgb, 256);
+        assert_eq!(palette.len(), 1);
+    }
+
+    #[test]
+    fn test_extract_palette_kmeans_iterations() {
+        // A smooth diagonal gradient, so the clusters take several passes to settle.
+        let image = RgbImage::from_fn(64, 64, |x, y| {
+            Rgb([(x * 4) as u8, (y * 4) as u8, ((x + y) * 2) as u8])
+        });
+        let variance = |colors: &[Color]| -> f64 {
+            let centroids: Vec<[f64; 3]> = colors
+                .iter()
+                .map(|c| [c.r as f64, c.g as f64, c.b as f64])
+                .collect();
+            image
+                .pixels()
+                .map(|p| {
+                    let point = p.0.map(f64::from);
+                    let centroid = centroids[nearest(&point, &centroids)];
+                    (0..3)
+                        .map(|i| (point[i] - centroid[i]).powi(2))
+                        .sum::<f64>()
+                })
+                .sum()
+        };
+
+        // Test case 1: More passes leave less variance within the clusters
+        let rough = extract_palette_kmeans(&image, 6, ColorSpace::Rgb, 1, &[]);
+        let settled = extract_palette_kmeans(&image, 6, ColorSpace::Rgb, 16, &[]);
+        assert_eq!(rough.len(), 6);
+        assert_eq!(settled.len(), 6);
+        assert!(variance(&settled) < variance(&rough));
+
+        // Test case 2: Once settled, the palette is about as good as exoquant's
+        let exoquant = extract_palette(&image, 6, QuantisationMethod::KMeans, ColorSpace::Rgb);
+        assert!(variance(&settled) <= variance(&exoquant) * 1.1);
+    }
+
+    #[test]
+    fn test_extract_palette_kmeans_matches_exoquant() {
+        let quadrants = [[200, 30, 30], [30, 200, 30], [30, 30, 200], [220, 220, 40]];
+        let image = RgbImage::from_fn(40, 40, |x, y| {
+            Rgb(quadrants[(x / 20 + 2 * (y / 20)) as usize])
+        });
+
+        let mut parallel: Vec<[u8; 3]> =
+            extract_palette_kmeans(&image, 4, ColorSpace::Rgb, 16, &[])
+                .iter()
+                .map(|c| [c.r, c.g, c.b])
+                .collect();
+        let mut exoquant: Vec<[u8; 3]> =
+            extract_palette(&image, 4, QuantisationMethod::KMeans, ColorSpace::Rgb)
+                .iter()
+                .map(|c| [c.r, c.g, c.b])
+                .collect();
+        let mut expected = quadrants.to_vec();
+        parallel.sort();
+        exoquant.sort();
+        expected.sort();
+
+        assert_eq!(parallel, expected);
+        for (a, b) in parallel.iter().zip(&exoquant) {
+            assert!((0..3).all(|i| a[i].abs_diff(b[i]) <= 8), "{a:?} vs {b:?}");
+        }
+    }
+
+    #[test]
+    fn test_extract_palette_kmeans_seeded() {
+        let quadrants = [[200, 30, 30], [30, 200, 30], [30, 30, 200], [220, 220, 40]];
+        let image = RgbImage::from_fn(40, 40, |x, y| {
+            Rgb(quadrants[(x / 20 + 2 * (y / 20)) as usize])
+        });
+        let seeds = [[220, 220, 40], [30, 30, 200], [30, 200, 30], [200, 30, 30]];
+
+        // Test case 1: Seeded with the image's own colors, the palette stays at them, in order
+        for color_space in [ColorSpace::Rgb, ColorSpace::Lab] {
+            let seeded: Vec<[u8; 3]> = extract_palette_kmeans(&image, 4, color_space, 16, &seeds)
+                .iter()
+                .map(|c| [c.r, c.g, c.b])
+                .collect();
+            assert_eq!(seeded, seeds);
+        }
+
+        // Test case 2: Too few seeds are padded out to the number of colors
+        let padded = extract_palette_kmeans(&image, 4, ColorSpace::Rgb, 16, &seeds[..2]);
+        assert_eq!(padded.len(), 4);
+        assert_eq!([padded[0].r, padded[0].g, padded[0].b], seeds[0]);
+    }
+
+    #[test]
+    fn test_mcq_color_nodes_to_exoquant_colors() {
+        let mcq_colors = vec![
+            ColorNode {
+                red: 32,
+                grn: 64,
+                blu: 128,
+                rgb: 0,
+                cnt: 0,
+            },
+            ColorNode {
+                red: 133,
+                grn: 78,
+                blu: 232,
+                rgb: 0,
+                cnt: 0,
+            },
+        ];
+
+        let result = mcq_color_nodes_to_exoquant_colors(mcq_colors);
+
+        assert_eq!(result.len(), 2);
+
+        assert_eq!(result.first().unwrap().r, 32);
+        assert_eq!(result.first().unwrap().g, 64);
+        assert_eq!(result.first().unwrap().b, 128);
+
+        assert_eq!(result.get(1).unwrap().r, 133);
+        assert_eq!(result.get(1).unwrap().g, 78);
+        assert_eq!(result.get(1).unwrap().b, 232);
+    }
+
+    #[test]
+    fn test_downsample() {
+        // Test case 1: Longest edge is scaled down, preserving the aspect ratio
+        let image = RgbImage::new(400, 200);
+        let result = downsample(&image, 100, DownscaleFilter::Triangle).unwrap();
+        assert_eq!(result.dimensions(), (100, 50));
+
+        // Test case 2: Images already within the sample size are left alone
+        assert!(downsample(&image, 400, DownscaleFilter::Triangle).is_none());
+    }
+
+    #[test]
+    fn test_downsample_filters_differ_on_fine_detail() {
+        // A one pixel checkerboard of red and blue: nearest picks single pixels (all of one color,
+        // at this scale), while Lanczos blends them
+        let image = RgbImage::from_fn(200, 200, |x, y| {
+            if (x + y) % 2 == 0 {
+                Rgb([230, 20, 20])
+            } else {
+                Rgb([20, 20, 230])
+            }
+        });
+        let palette = |filter| {
+            let sampled = downsample(&image, 50, filter).unwrap();
+            extract_palette(&sampled, 1, QuantisationMethod::MedianCut, ColorSpace::Rgb)[0]
+        };
+
+        let nearest = palette(DownscaleFilter::Nearest);
+        let lanczos = palette(DownscaleFilter::Lanczos3);
+        let difference = |a: &Color, b: &Color| {
+            a.r.abs_diff(b.r) as u32 + a.g.abs_diff(b.g) as u32 + a.b.abs_diff(b.b) as u32
+        };
+        assert!(difference(&nearest, &lanczos) > 100);
+    }
+
+    #[test]
+    fn test_downsampled_palette_matches_full_resolution() {
+        // Four solid quadrants of distinct colors
+        let quadrants = [[200, 30, 30], [30, 200, 30], [30, 30, 200], [220, 220, 40]];
+        let image = RgbImage::from_fn(400, 400, |x, y| {
+            image::Rgb(quadrants[(x / 200 + 2 * (y / 200)) as usize])
+        });
+        let sampled = downsample(&image, 50, DownscaleFilter::Triangle).unwrap();
+
+        let mut full: Vec<[u8; 3]> =
+            extract_palette(&image, 4, QuantisationMethod::KMeans, ColorSpace::Rgb)
+                .iter()
+                .map(|c| [c.r, c.g, c.b])
+                .collect();
+        let mut fast: Vec<[u8; 3]> =
+            extract_palette(&sampled, 4, QuantisationMethod::KMeans, ColorSpace::Rgb)
+                .iter()
+                .map(|c| [c.r, c.g, c.b])
+                .collect();
+        full.sort();
+        fast.sort();
+
+        assert_eq!(full.len(), fast.len());
+        for (a, b) in full.iter().zip(&fast) {
+            for channel in 0..3 {
+                assert!((a[channel] as i16 - b[channel] as i16).abs() <= 8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_extract_palette_detailed() {
+        let image = RgbImage::from_fn(10, 10, |x, _| match x {
+            0..=6 => image::Rgb([200, 30, 30]),
+            _ => image::Rgb([30, 30, 200]),
+        });
+
+        for method in [QuantisationMethod::KMeans, QuantisationMethod::MedianCut] {
+            let result = extract_palette_detailed(&image, 2, method, ColorSpace::Rgb);
+
+            assert_eq!(result.counts.len(), result.colors.len());
+            assert_eq!(result.counts.iter().sum::<u64>(), 100);
+            assert_eq!(result.method, method);
+
+            let mut counts = result.counts.clone();
+            counts.sort();
+            assert_eq!(counts, vec![30, 70]);
+        }
+    }
+
+    #[test]
+    fn test_extract_palette_with_custom_color_space() {
+        let quadrants = [[200, 30, 30], [30, 200, 30], [30, 30, 200], [220, 220, 40]];
+        let image = RgbImage::from_fn(8, 8, |x, y| {
+            image::Rgb(quadrants[(x / 4 + 2 * (y / 4)) as usize])
+        });
+        let color_space = SimpleColorSpace {
+            gamma: 2.2,
+            ..Default::default()
+        };
+
+        // Test case 1: K-Means in the custom space returns the requested number of colors
+        let palette =
+            extract_palette_with_space(&image, 4, QuantisationMethod::KMeans, &color_space);
+        assert_eq!(palette.len(), 4);
+
+        // Test case 2: Median Cut ignores the color space
+        let with_space =
+            extract_palette_with_space(&image, 4, QuantisationMethod::MedianCut, &color_space);
+        let without_space =
+            extract_palette(&image, 4, QuantisationMethod::MedianCut, ColorSpace::Rgb);
+        let rgb = |colors: Vec<Color>| -> Vec<[u8; 3]> {
+            colors.iter().map(|c| [c.r, c.g, c.b]).collect()
+        };
+        assert_eq!(rgb(with_space), rgb(without_space));
+    }
+
+    #[test]
+    fn test_extract_palette_high_precision_keeps_subtle_shades_apart() {
+        // Two shades of red less than one 8-bit step apart, side by side
+        let image = Rgb16Image::from_fn(10, 10, |x, _| match x {
+            0..=5 => Rgb([0x4000, 0x1000, 0x1000]),
+            _ => Rgb([0x4060, 0x1000, 0x1000]),
+        });
+
+        // Test case 1: Truncating to 8 bits collapses the gradient into a single color
+        let truncated = image::DynamicImage::ImageRgb16(image.clone()).to_rgb8();
+        assert_eq!(distinct_colors(&truncated).len(), 1);
+        let palette = extract_palette(
+            &truncated,
+            2,
+            QuantisationMethod::MedianCut,
+            ColorSpace::Rgb,
+        );
+        assert_eq!(palette.len(), 1);
+
+        // Test case 2: The high-precision path finds both shades
+        let result = extract_palette_high_precision(&image, 2);
+        assert_eq!(result.colors.len(), 2);
+        let mut counts = result.counts.clone();
+        counts.sort();
+        assert_eq!(counts, vec![40, 60]);
+        assert!(result
+            .colors
+            .iter()
+            .all(|c| [c.r, c.g, c.b] == [0x40, 0x10, 0x10]));
+    }
+
+    #[test]
+    fn test_choose_method() {
+        // Test case 1: Pixel art with three colors is quantised with Popularity, which keeps them
+        let pixel_art = RgbImage::from_fn(12, 12, |x, y| match (x / 4 + y / 4) % 3 {
+            0 => Rgb([20, 20, 60]),
+            1 => Rgb([230, 200, 40]),
+            _ => Rgb([200, 40, 40]),
+        });
+        assert_eq!(choose_method(&pixel_art), QuantisationMethod::Popularity);
+        let palette = extract_palette(&pixel_art, 3, QuantisationMethod::Auto, ColorSpace::Rgb);
+        let mut palette: Vec<[u8; 3]> = palette.iter().map(|c| [c.r, c.g, c.b]).collect();
+        palette.sort();
+        assert_eq!(palette, [[20, 20, 60], [200, 40, 40], [230, 200, 40]]);
+
+        // Test case 2: A smooth gradient, like a photo, is quantised with K-Means
+        let gradient = RgbImage::from_fn(64, 64, |x, y| Rgb([x as u8 * 4, y as u8 * 4, 90]));
+        assert_eq!(choose_method(&gradient), QuantisationMethod::KMeans);
+
+        // Test case 3: The detailed extraction reports the method picked, not Auto
+        let result =
+            extract_palette_detailed(&gradient, 4, QuantisationMethod::Auto, ColorSpace::Rgb);
+        assert_eq!(result.method, QuantisationMethod::KMeans);
+        assert_eq!(result.colors.len(), 4);
+    }
+}
@@ -0,0 +1,184 @@
+use exoquant::Color;
+use image::RgbImage;
+
+use crate::utils::color_conversion::{relative_luminance, rgb_to_hsl};
+
+/**
+ * Removes every pixel within `tolerance` (Euclidean distance in RGB) of any of the given colors,
+ * e.g. to stop a plain white background dominating the palette.
+ *
+ * The remaining pixels are returned laid out in a single row, which is all that extraction needs.
+ * Returns `None` when no colors are excluded.
+ *
+ * [&RgbImage] The image whose pixels will be filtered.
+ * [&[[u8; 3]]] The colors to exclude.
+ * [f32] How far (0–441) a pixel may be from an excluded color and still be excluded.
+ */
+pub fn exclude_colors(
+    input_image: &RgbImage,
+    excluded: &[[u8; 3]],
+    tolerance: f32,
+) -> Option<RgbImage> {
+    if excluded.is_empty() {
+        return None;
+    }
+
+    let tolerance_squared = tolerance * tolerance;
+    let is_excluded = |p: &[u8; 3]| {
+        excluded.iter().any(|e| {
+            let distance_squared: f32 = (0..3).map(|c| (p[c] as f32 - e[c] as f32).powi(2)).sum();
+            distance_squared <= tolerance_squared
+        })
+    };
+
+    let pixels: Vec<u8> = input_image
+        .pixels()
+        .filter(|p| !is_excluded(&p.0))
+        .flat_map(|p| p.0)
+        .collect();
+
+    Some(RgbImage::from_raw((pixels.len() / 3) as u32, 1, pixels).unwrap())
+}
+
+/**
+ * Drops palette colors whose HSL saturation is below `min_saturation` (0–100), e.g. to keep only
+ * the accent colors of an image.
+ *
+ * Filtering happens after extraction, so the palette may end up with fewer colors than requested
+ * (or none at all); the remaining colors are not re-extracted, so they're exactly the colors that
+ * would have been extracted without the filter.
+ *
+ * [Vec<Color>] The extracted palette.
+ * [f32] The minimum saturation, as a percentage.
+ */
+pub fn filter_by_saturation(colors: Vec<Color>, min_saturation: f32) -> Vec<Color> {
+    colors
+        .into_iter()
+        .filter(|c| rgb_to_hsl(c.r, c.g, c.b).s * 100.0 >= min_saturation as f64)
+        .collect()
+}
+
+/**
+ * Drops palette colors whose relative luminance (Rec. 709 weights, 0 for black to 1 for white)
+ * falls outside the range, e.g. to keep only mid-to-dark colors for a dark theme.
+ *
+ * Like `filter_by_saturation`, this happens after extraction, so the palette may end up with fewer
+ * colors than requested (see `--pad-to-count` to make up the difference).
+ *
+ * [Vec<Color>] The extracted palette.
+ * [Option<f64>] The minimum luminance, if any.
+ * [Option<f64>] The maximum luminance, if any.
+ */
+pub fn filter_by_luminance(
+    colors: Vec<Color>,
+    min_luminance: Option<f64>,
+    max_luminance: Option<f64>,
+) -> Vec<Color> {
+    let range = min_luminance.unwrap_or(0.0)..=max_luminance.unwrap_or(1.0);
+    colors
+        .into_iter()
+        .filter(|c| range.contains(&relative_luminance(c.r, c.g, c.b)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::extractor::extract_palette;
+    use crate::types::config::{ColorSpace, QuantisationMethod};
+
+    /// An image that is 80% (near-)white and 20% red.
+    fn mostly_white_image() -> RgbImage {
+        RgbImage::from_fn(10, 10, |x, y| match x {
+            0..=1 => image::Rgb([220, 20, 20]),
+            _ if (x + y) % 2 == 0 => image::Rgb([255, 255, 255]),
+            _ => image::Rgb([250, 252, 251]),
+        })
+    }
+
+    #[test]
+    fn test_exclude_colors() {
+        let image = mostly_white_image();
+
+        // Test case 1: Nothing to exclude
+        assert!(exclude_colors(&image, &[], 10.0).is_none());
+
+        // Test case 2: Exact matches only
+        let result = exclude_colors(&image, &[[255, 255, 255]], 0.0).unwrap();
+        assert_eq!(result.width(), 60);
+
+        // Test case 3: Near-white pixels are excluded within the tolerance
+        let result = exclude_colors(&image, &[[255, 255, 255]], 10.0).unwrap();
+        assert_eq!(result.width(), 20);
+        assert!(result.pixels().all(|p| p.0 == [220, 20, 20]));
+    }
+
+    #[test]
+    fn test_excluding_background_leaves_palette_dominated_by_red() {
+        let image = mostly_white_image();
+        let filtered = exclude_colors(&image, &[[255, 255, 255]], 10.0).unwrap();
+
+        for method in [QuantisationMethod::KMeans, QuantisationMethod::MedianCut] {
+            let palette = extract_palette(&filtered, 1, method, ColorSpace::Rgb);
+            let dominant = palette.first().unwrap();
+            assert!(dominant.r > 200 && dominant.g < 40 && dominant.b < 40);
+
+            // Without the exclusion, white dominates
+            let palette = extract_palette(&image, 1, method, ColorSpace::Rgb);
+            let dominant = palette.first().unwrap();
+            assert!(dominant.g > 150 && dominant.b > 150);
+        }
+    }
+
+    #[test]
+    fn test_filter_by_saturation_drops_grays() {
+        let gray = [128, 128, 128];
+        let dark_gray = [60, 62, 61];
+        let red = [220, 20, 20];
+        let teal = [20, 160, 150];
+        let image = RgbImage::from_fn(4, 4, |x, _| {
+            image::Rgb([gray, dark_gray, red, teal][x as usize])
+        });
+
+        let palette = extract_palette(&image, 4, QuantisationMethod::MedianCut, ColorSpace::Rgb);
+        assert_eq!(palette.len(), 4);
+
+        // Test case 1: A threshold of zero keeps everything
+        assert_eq!(filter_by_saturation(palette.clone(), 0.0).len(), 4);
+
+        // Test case 2: A high threshold leaves only the vivid colors
+        let result: Vec<[u8; 3]> = filter_by_saturation(palette, 50.0)
+            .iter()
+            .map(|c| [c.r, c.g, c.b])
+            .collect();
+        assert_eq!(result.len(), 2);
+        assert!(result.contains(&red));
+        assert!(result.contains(&teal));
+    }
+
+    #[test]
+    fn test_filter_by_luminance_keeps_the_dark_half() {
+        // A black-to-white gradient, in eight steps
+        let image = RgbImage::from_fn(8, 1, |x, _| image::Rgb([(x * 255 / 7) as u8; 3]));
+        let palette = extract_palette(&image, 8, QuantisationMethod::MedianCut, ColorSpace::Rgb);
+        assert_eq!(palette.len(), 8);
+
+        // Test case 1: No bounds keeps everything
+        assert_eq!(filter_by_luminance(palette.clone(), None, None).len(), 8);
+
+        // Test case 2: A maximum of 0.2 drops the light colors
+        let dark = filter_by_luminance(palette.clone(), None, Some(0.2));
+        assert!(!dark.is_empty());
+        assert!(dark
+            .iter()
+            .all(|c| relative_luminance(c.r, c.g, c.b) <= 0.2));
+        assert!(dark.iter().all(|c| c.r < 128));
+        assert!(!dark.iter().any(|c| c.r == 255));
+
+        // Test case 3: A minimum drops the dark colors instead, and both bound a band
+        let light = filter_by_luminance(palette.clone(), Some(0.5), None);
+        assert!(light.iter().all(|c| c.r > 128));
+        let band = filter_by_luminance(palette, Some(0.01), Some(0.5));
+        assert!(band.iter().all(|c| c.r != 0 && c.r != 255));
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use exoquant::{Color, Histogram};
+use image::{ImageBuffer, Pixel, Rgb, RgbImage};
+
+/**
+ * Counts the occurrences of each distinct color in the image.
+ *
+ * The result is sorted by descending count (ties broken by RGB value) so that anything seeded
+ * from it is deterministic. Works on 8- and 16-bit images alike.
+ */
+pub fn distinct_colors<T>(input_image: &ImageBuffer<Rgb<T>, Vec<T>>) -> Vec<([T; 3], u64)>
+where
+    Rgb<T>: Pixel<Subpixel = T>,
+    T: Copy + Eq + Ord + Hash,
+{
+    let mut counts: HashMap<[T; 3], u64> = HashMap::new();
+    for p in input_image.pixels() {
+        *counts.entry(p.0).or_insert(0) += 1;
+    }
+
+    let mut colors: Vec<([T; 3], u64)> = counts.into_iter().collect();
+    colors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    colors
+}
+
+/**
+ * Builds the exoquant histogram of an image for K-Means, folding in one pixel at a time. No copy
+ * of the pixel data is made along the way, so the memory used scales with the number of distinct
+ * colors rather than the number of pixels.
+ *
+ * [&RgbImage] The image to be counted.
+ */
+pub fn rgb_histogram(input_image: &RgbImage) -> Histogram {
+    let mut histogram = Histogram::new();
+    for p in input_image.pixels() {
+        histogram.extend([Color::new(p[0], p[1], p[2], 0xff)]);
+    }
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distinct_colors() {
+        let image = RgbImage::from_fn(3, 2, |x, _| match x {
+            0 => Rgb([9, 9, 9]),
+            _ => Rgb([200, 100, 0]),
+        });
+
+        assert_eq!(
+            distinct_colors(&image),
+            vec![([200, 100, 0], 4), ([9, 9, 9], 2)]
+        );
+    }
+
+    #[test]
+    fn test_rgb_histogram_scales_with_distinct_colors() {
+        // 4 million pixels of a single color make a single histogram entry
+        let image = RgbImage::from_pixel(2000, 2000, Rgb([12, 34, 56]));
+        let histogram = rgb_histogram(&image);
+
+        let entries: Vec<(&Color, &usize)> = histogram.iter().collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(*entries[0].1, 4_000_000);
+        assert_eq!(
+            [entries[0].0.r, entries[0].0.g, entries[0].0.b],
+            [12, 34, 56]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use exoquant::Color;
+
+/**
+ * Makes sure each of the given colors is in the palette, e.g. a required text color. Colors
+ * already in the palette are left where they are. The rest are appended while the palette has
+ * fewer than `number_of_colors` colors, and otherwise take the place of the extracted color with
+ * the lowest weight, so the palette doesn't grow. Included colors are never displaced by each
+ * other.
+ *
+ * [Vec<Color>] The extracted palette.
+ * [&[u64]] The weight of each extracted color (e.g. how many pixels it covers).
+ * [&[[u8; 3]]] The colors that must be in the palette.
+ * [usize] The number of colors wanted.
+ */
+pub fn include_colors(
+    mut colors: Vec<Color>,
+    weights: &[u64],
+    included: &[[u8; 3]],
+    number_of_colors: usize,
+) -> Vec<Color> {
+    // Included colors are weighted above anything extracted, so they're never the one displaced
+    let mut weights: Vec<u64> = (0..colors.len())
+        .map(|i| weights.get(i).copied().unwrap_or(0))
+        .collect();
+
+    for &[r, g, b] in included {
+        let color = Color::new(r, g, b, 0xff);
+        if let Some(i) = colors.iter().position(|c| [c.r, c.g, c.b] == [r, g, b]) {
+            weights[i] = u64::MAX;
+            continue;
+        }
+
+        if colors.len() < number_of_colors {
+            colors.push(color);
+            weights.push(u64::MAX);
+        } else if let Some((i, _)) = weights
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w < u64::MAX)
+            .min_by_key(|(_, w)| **w)
+        {
+            colors[i] = color;
+            weights[i] = u64::MAX;
+        }
+    }
+
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(colors: &[Color]) -> Vec<[u8; 3]> {
+        colors.iter().map(|c| [c.r, c.g, c.b]).collect()
+    }
+
+    #[test]
+    fn test_include_colors() {
+        let colors = vec![
+            Color::new(255, 0, 0, 255),
+            Color::new(200, 0, 0, 255),
+            Color::new(150, 0, 0, 255),
+        ];
+        let weights = [50, 10, 40];
+
+        // Test case 1: The least weighted color is displaced
+        let result = include_colors(colors.clone(), &weights, &[[0, 0, 255]], 3);
+        assert_eq!(rgb(&result), [[255, 0, 0], [0, 0, 255], [150, 0, 0]]);
+
+        // Test case 2: With room to spare, the color is appended instead
+        let result = include_colors(colors.clone(), &weights, &[[0, 0, 255]], 4);
+        assert_eq!(rgb(&result).last(), Some(&[0, 0, 255]));
+        assert_eq!(result.len(), 4);
+
+        // Test case 3: A color that's already there is left alone
+        let result = include_colors(colors.clone(), &weights, &[[200, 0, 0]], 3);
+        assert_eq!(rgb(&result), rgb(&colors));
+
+        // Test case 4: Included colors don't displace each other
+        let result = include_colors(colors, &weights, &[[0, 0, 255], [0, 255, 0]], 3);
+        assert_eq!(rgb(&result), [[255, 0, 0], [0, 0, 255], [0, 255, 0]]);
+    }
+}
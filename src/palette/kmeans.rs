@@ -0,0 +1,251 @@
+use exoquant::Color;
+use image::RgbImage;
+use rayon::prelude::*;
+
+use crate::palette::histogram::distinct_colors;
+use crate::utils::color_conversion::{lab_to_rgb, rgb_to_lab, Lab};
+
+/**
+ * The maximum number of refinement passes made by `kmeans` before giving up on convergence.
+ */
+pub const MAX_ITERATIONS: usize = 32;
+
+/**
+ * The squared Euclidean distance between two points.
+ */
+pub fn distance_squared(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+/**
+ * Returns the index of the centroid closest to the point.
+ */
+pub fn nearest(point: &[f64; 3], centroids: &[[f64; 3]]) -> usize {
+    centroids
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            distance_squared(point, a)
+                .partial_cmp(&distance_squared(point, b))
+                .unwrap()
+        })
+        .map(|(i, _)| i)
+        .unwrap()
+}
+
+/**
+ * A small, weighted implementation of Lloyd's K-Means algorithm over 3-component points.
+ *
+ * Centroids are seeded deterministically: the first is the first (heaviest) point, and each
+ * subsequent one is the point farthest from all centroids chosen so far. Fewer than `k`
+ * centroids are returned when there are fewer than `k` points.
+ *
+ * [&[([f64; 3], u64)]] The points to cluster, each with a weight (e.g. a pixel count).
+ * [usize] The number of clusters.
+ */
+pub fn kmeans(points: &[([f64; 3], u64)], k: usize) -> Vec<[f64; 3]> {
+    kmeans_with_iterations(points, k, MAX_ITERATIONS)
+}
+
+/**
+ * Runs `kmeans` with at most the given number of refinement passes: fewer passes are quicker, but
+ * may stop before the centroids have settled. Each pass assigns the points to their nearest
+ * centroid in parallel, which is where almost all of the time goes on large images.
+ *
+ * [&[([f64; 3], u64)]] The points to cluster, each with a weight (e.g. a pixel count).
+ * [usize] The number of clusters.
+ * [usize] The maximum number of refinement passes.
+ */
+pub fn kmeans_with_iterations(
+    points: &[([f64; 3], u64)],
+    k: usize,
+    iterations: usize,
+) -> Vec<[f64; 3]> {
+    kmeans_seeded(points, k, &[], iterations)
+}
+
+/**
+ * Runs `kmeans_with_iterations` starting from the given centroids, so the clusters settle near
+ * them. Any centroids still needed after the seeds are chosen as usual, as the points farthest
+ * from those chosen so far; seeds beyond the `k`th are ignored.
+ *
+ * [&[([f64; 3], u64)]] The points to cluster, each with a weight (e.g. a pixel count).
+ * [usize] The number of clusters.
+ * [&[[f64; 3]]] The centroids to start from.
+ * [usize] The maximum number of refinement passes.
+ */
+pub fn kmeans_seeded(
+    points: &[([f64; 3], u64)],
+    k: usize,
+    seeds: &[[f64; 3]],
+    iterations: usize,
+) -> Vec<[f64; 3]> {
+    let k = k.min(points.len());
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let mut centroids: Vec<[f64; 3]> = seeds.iter().take(k).copied().collect();
+    if centroids.is_empty() {
+        centroids.push(points[0].0);
+    }
+    while centroids.len() < k {
+        let farthest = points
+            .iter()
+            .map(|(p, _)| (p, distance_squared(p, &centroids[nearest(p, &centroids)])))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(p, _)| *p)
+            .unwrap();
+        centroids.push(farthest);
+    }
+
+    let mut assignments = vec![usize::MAX; points.len()];
+    for _ in 0..iterations {
+        let nearest_clusters: Vec<usize> = points
+            .par_iter()
+            .map(|(p, _)| nearest(p, &centroids))
+            .collect();
+
+        if nearest_clusters == assignments {
+            break;
+        }
+        assignments = nearest_clusters;
+
+        let mut sums = vec![([0.0; 3], 0u64); k];
+        for ((p, weight), cluster) in points.iter().zip(&assignments) {
+            let (sum, total) = &mut sums[*cluster];
+            for c in 0..3 {
+                sum[c] += p[c] * *weight as f64;
+            }
+            *total += weight;
+        }
+
+        for (centroid, (sum, total)) in centroids.iter_mut().zip(sums) {
+            if total > 0 {
+                *centroid = sum.map(|s| s / total as f64);
+            }
+        }
+    }
+
+    centroids
+}
+
+/**
+ * Extracts a palette using K-Means clustering in the CIELAB color space, so that the distance
+ * between colors reflects how different they look rather than how different their RGB
+ * components are. The resulting centroids are mapped back to sRGB.
+ *
+ * [&RgbImage] The image to be processed.
+ * [usize] The number of colors required for the palette.
+ * [&[[u8; 3]]] The colors to start the clusters from (see `kmeans_seeded`).
+ * [usize] The maximum number of refinement passes.
+ */
+pub fn lab_kmeans_palette(
+    input_image: &RgbImage,
+    number_of_colors: usize,
+    seeds: &[[u8; 3]],
+    iterations: usize,
+) -> Vec<Color> {
+    let points: Vec<([f64; 3], u64)> = distinct_colors(input_image)
+        .into_iter()
+        .map(|(rgb, count)| {
+            let lab = rgb_to_lab(rgb[0], rgb[1], rgb[2]);
+            ([lab.l, lab.a, lab.b], count)
+        })
+        .collect();
+
+    let seeds: Vec<[f64; 3]> = seeds
+        .iter()
+        .map(|[r, g, b]| {
+            let lab = rgb_to_lab(*r, *g, *b);
+            [lab.l, lab.a, lab.b]
+        })
+        .collect();
+
+    kmeans_seeded(&points, number_of_colors, &seeds, iterations)
+        .into_iter()
+        .map(|[l, a, b]| {
+            let [r, g, b] = lab_to_rgb(Lab { l, a, b });
+            Color::new(r, g, b, 0xff)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CYAN: [u8; 3] = [0, 255, 255];
+    const PALE_CYAN: [u8; 3] = [160, 255, 255];
+    const AZURE: [u8; 3] = [0, 160, 255];
+
+    fn three_color_image() -> RgbImage {
+        RgbImage::from_fn(3, 1, |x, _| {
+            image::Rgb([CYAN, PALE_CYAN, AZURE][x as usize])
+        })
+    }
+
+    #[test]
+    fn test_kmeans_single_cluster() {
+        let points = vec![([0.0, 0.0, 0.0], 3), ([10.0, 20.0, 30.0], 1)];
+        let result = kmeans(&points, 1);
+        assert_eq!(result, vec![[2.5, 5.0, 7.5]]);
+    }
+
+    #[test]
+    fn test_kmeans_fewer_points_than_clusters() {
+        let points = vec![([1.0, 2.0, 3.0], 1)];
+        assert_eq!(kmeans(&points, 4).len(), 1);
+        assert!(kmeans(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn test_kmeans_seeded() {
+        let points = vec![
+            ([0.0, 0.0, 0.0], 4),
+            ([10.0, 0.0, 0.0], 1),
+            ([100.0, 0.0, 0.0], 1),
+        ];
+
+        // Test case 1: Unseeded, the heaviest point and the farthest from it start the clusters
+        assert_eq!(kmeans(&points, 2), vec![[2.0, 0.0, 0.0], [100.0, 0.0, 0.0]]);
+
+        // Test case 2: Seeds start the clusters instead, in their order
+        let seeds = [[10.0, 0.0, 0.0], [0.0, 0.0, 0.0]];
+        let result = kmeans_seeded(&points, 2, &seeds, MAX_ITERATIONS);
+        assert_eq!(result, vec![[100.0, 0.0, 0.0], [2.0, 0.0, 0.0]]);
+
+        // Test case 3: Too few seeds are padded with the farthest points, and extras are ignored
+        let result = kmeans_seeded(&points, 3, &seeds[..1], MAX_ITERATIONS);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0], [10.0, 0.0, 0.0]);
+        assert_eq!(kmeans_seeded(&points, 1, &seeds, 0), vec![[10.0, 0.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_lab_kmeans_merges_perceptually_similar_colors() {
+        // Cyan and pale cyan look alike (about 21 apart in CIELAB), but are 160 apart in RGB,
+        // farther than cyan is from azure (95), which looks quite different (about 69 in CIELAB).
+        let image = three_color_image();
+
+        // Test case 1: Clustering in RGB merges cyan and azure, keeping pale cyan separate
+        let points: Vec<([f64; 3], u64)> = distinct_colors(&image)
+            .into_iter()
+            .map(|(rgb, count)| (rgb.map(|c| c as f64), count))
+            .collect();
+        let rgb_result = kmeans(&points, 2);
+        assert!(rgb_result.contains(&PALE_CYAN.map(|c| c as f64)));
+        assert!(!rgb_result.contains(&CYAN.map(|c| c as f64)));
+        assert!(!rgb_result.contains(&AZURE.map(|c| c as f64)));
+
+        // Test case 2: Clustering in CIELAB merges cyan and pale cyan, keeping azure separate
+        let lab_result: Vec<[u8; 3]> = lab_kmeans_palette(&image, 2, &[], MAX_ITERATIONS)
+            .iter()
+            .map(|c| [c.r, c.g, c.b])
+            .collect();
+        assert_eq!(lab_result.len(), 2);
+        assert!(lab_result.contains(&AZURE));
+        assert!(!lab_result.contains(&CYAN));
+        assert!(!lab_result.contains(&PALE_CYAN));
+    }
+}
@@ -0,0 +1,60 @@
+use exoquant::Color;
+use image::{Rgb, RgbImage};
+
+use crate::utils::color_conversion::{delinearize, linearize};
+
+/**
+ * Linearises every pixel of an image (see `linearize`), so that a palette extracted from it
+ * averages and clusters light as it mixes, rather than the gamma-encoded values. Averaging
+ * gamma-encoded values darkens the mix: half black and half white averages to mid-gray (#808080),
+ * rather than the #bcbcbc the eye sees at a distance.
+ *
+ * [&RgbImage] The image to linearise.
+ */
+pub fn linearize_image(input_image: &RgbImage) -> RgbImage {
+    let mut linear_image = input_image.clone();
+    for pixel in linear_image.pixels_mut() {
+        *pixel = Rgb(linearize(pixel.0));
+    }
+    linear_image
+}
+
+/**
+ * Gamma-encodes a palette extracted from a linearised image back to sRGB. Alpha is kept.
+ *
+ * [Vec<Color>] The palette extracted from the linearised image.
+ */
+pub fn delinearize_palette(colors: Vec<Color>) -> Vec<Color> {
+    colors
+        .into_iter()
+        .map(|color| {
+            let [r, g, b] = delinearize([color.r, color.g, color.b]);
+            Color::new(r, g, b, color.a)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linearize_image() {
+        let image = RgbImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                Rgb([128, 188, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        });
+
+        // Test case 1: Each pixel is linearised
+        let linear_image = linearize_image(&image);
+        assert_eq!(linear_image.get_pixel(0, 0).0, [55, 128, 0]);
+        assert_eq!(linear_image.get_pixel(1, 0).0, [255, 255, 255]);
+
+        // Test case 2: The palette is gamma-encoded again
+        let palette = delinearize_palette(vec![Color::new(55, 128, 0, 96)]);
+        assert!(palette == vec![Color::new(128, 188, 0, 96)]);
+    }
+}
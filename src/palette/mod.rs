@@ -0,0 +1,17 @@
+pub mod alpha;
+pub mod average;
+pub mod border;
+pub mod cache;
+pub mod chroma;
+pub mod extractor;
+pub mod filter;
+pub mod histogram;
+pub mod include;
+pub mod kmeans;
+pub mod linear;
+pub mod pad;
+pub mod popularity;
+pub mod recolor;
+pub mod sort;
+pub mod uniform;
+pub mod weight;
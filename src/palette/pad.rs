@@ -0,0 +1,97 @@
+use exoquant::Color;
+
+use crate::types::config::PadStrategy;
+
+/**
+ * Pads a palette that came out smaller than requested (e.g. Median Cut on a low-variance image)
+ * up to the requested number of colors. Palettes that are already big enough are left alone.
+ *
+ * - `PadStrategy::Interpolate` repeatedly inserts the midpoint of the two neighbouring colors that
+ *   are furthest apart (in RGB), between them. A single color has nothing to interpolate with, so
+ *   it's repeated.
+ * - `PadStrategy::Repeat` repeats the last color.
+ *
+ * An empty palette stays empty.
+ *
+ * [Vec<Color>] The extracted palette.
+ * [usize] The number of colors wanted.
+ * [PadStrategy] How to make up the shortfall.
+ */
+pub fn pad_palette(mut colors: Vec<Color>, count: usize, strategy: PadStrategy) -> Vec<Color> {
+    while colors.len() < count {
+        let padding = match (strategy, colors.as_slice()) {
+            (_, []) => break,
+            (PadStrategy::Interpolate, [_, _, ..]) => {
+                let distance = |a: &Color, b: &Color| {
+                    [(a.r, b.r), (a.g, b.g), (a.b, b.b)]
+                        .iter()
+                        .map(|&(x, y)| (x as i32 - y as i32).pow(2))
+                        .sum::<i32>()
+                };
+                let (i, pair) = colors
+                    .windows(2)
+                    .enumerate()
+                    .max_by_key(|(i, pair)| (distance(&pair[0], &pair[1]), std::cmp::Reverse(*i)))
+                    .unwrap();
+                let midpoint = |x: u8, y: u8| (x as u16 + y as u16).div_ceil(2) as u8;
+                (
+                    i + 1,
+                    Color::new(
+                        midpoint(pair[0].r, pair[1].r),
+                        midpoint(pair[0].g, pair[1].g),
+                        midpoint(pair[0].b, pair[1].b),
+                        midpoint(pair[0].a, pair[1].a),
+                    ),
+                )
+            }
+            (_, [.., last]) => (colors.len(), *last),
+        };
+        colors.insert(padding.0, padding.1);
+    }
+
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(colors: &[Color]) -> Vec<[u8; 3]> {
+        colors.iter().map(|c| [c.r, c.g, c.b]).collect()
+    }
+
+    #[test]
+    fn test_pad_palette() {
+        let black = Color::new(0, 0, 0, 255);
+        let white = Color::new(255, 255, 255, 255);
+
+        // Test case 1: Interpolating between two colors
+        assert_eq!(
+            rgb(&pad_palette(
+                vec![black, white],
+                4,
+                PadStrategy::Interpolate
+            )),
+            vec![[0, 0, 0], [64, 64, 64], [128, 128, 128], [255, 255, 255]]
+        );
+
+        // Test case 2: Repeating the last color
+        assert_eq!(
+            rgb(&pad_palette(vec![black, white], 4, PadStrategy::Repeat)),
+            vec![[0, 0, 0], [255, 255, 255], [255, 255, 255], [255, 255, 255]]
+        );
+
+        // Test case 3: A single color can only be repeated
+        assert_eq!(
+            rgb(&pad_palette(vec![white], 3, PadStrategy::Interpolate)),
+            vec![[255, 255, 255]; 3]
+        );
+
+        // Test case 4: Nothing to do
+        assert_eq!(
+            pad_palette(vec![black, white], 2, PadStrategy::Repeat).len(),
+            2
+        );
+        assert!(pad_palette(Vec::new(), 4, PadStrategy::Repeat).is_empty());
+    }
+}
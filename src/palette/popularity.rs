@@ -0,0 +1,49 @@
+use exoquant::Color;
+use image::RgbImage;
+
+use crate::palette::histogram::distinct_colors;
+
+/**
+ * Builds a palette from the image's most common exact colors, without merging any. This suits
+ * images with few colors (e.g. pixel art, logos and screenshots), whose colors it keeps exactly,
+ * but on a photo it tends to pick several near-identical shades of the same area.
+ *
+ * At most `number_of_colors` colors are returned, and fewer when the image has fewer.
+ *
+ * [&RgbImage] The image to be processed.
+ * [usize] The number of colors required for the palette.
+ */
+pub fn popularity_palette(input_image: &RgbImage, number_of_colors: usize) -> Vec<Color> {
+    distinct_colors(input_image)
+        .into_iter()
+        .take(number_of_colors)
+        .map(|([r, g, b], _)| Color::new(r, g, b, 0xff))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popularity_palette() {
+        // Mostly red, some blue, a little green
+        let image = RgbImage::from_fn(10, 1, |x, _| match x {
+            0..=5 => image::Rgb([200, 10, 10]),
+            6..=8 => image::Rgb([10, 10, 200]),
+            _ => image::Rgb([10, 200, 10]),
+        });
+        let hex = |colors: Vec<Color>| -> Vec<[u8; 3]> {
+            colors.iter().map(|c| [c.r, c.g, c.b]).collect()
+        };
+
+        // Test case 1: The most common colors, exactly, most common first
+        assert_eq!(
+            hex(popularity_palette(&image, 2)),
+            [[200, 10, 10], [10, 10, 200]]
+        );
+
+        // Test case 2: No more colors than the image has
+        assert_eq!(popularity_palette(&image, 8).len(), 3);
+    }
+}
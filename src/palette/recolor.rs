@@ -0,0 +1,58 @@
+use exoquant::Color;
+use image::{Rgb, RgbImage};
+
+use crate::palette::kmeans::nearest;
+
+/**
+ * Remaps every pixel of an image to its nearest palette color (Euclidean distance in RGB).
+ *
+ * An empty palette leaves the image unchanged.
+ *
+ * [&RgbImage] The image to be recolored.
+ * [&[Color]] The palette colors.
+ */
+pub fn recolor_image(input_image: &RgbImage, colors: &[Color]) -> RgbImage {
+    if colors.is_empty() {
+        return input_image.clone();
+    }
+
+    let palette: Vec<[f64; 3]> = colors
+        .iter()
+        .map(|c| [c.r as f64, c.g as f64, c.b as f64])
+        .collect();
+
+    let mut output = input_image.clone();
+    for pixel in output.pixels_mut() {
+        let q = &colors[nearest(&pixel.0.map(|c| c as f64), &palette)];
+        *pixel = Rgb([q.r, q.g, q.b]);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::json::read_json_palette;
+    use crate::output::json::write_json_palette_to_file;
+    use crate::types::color::{ColorDetails, PaletteOutput};
+
+    #[test]
+    fn test_recolor_image_with_saved_palette() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("palette.json");
+        let navy = [10, 20, 90];
+        let gold = [240, 200, 40];
+        let colors = [navy, gold].map(|[r, g, b]| Color::new(r, g, b, 255));
+        write_json_palette_to_file(&PaletteOutput::new(&colors, ColorDetails::default()), &path)
+            .unwrap();
+
+        let gradient = RgbImage::from_fn(32, 4, |x, _| Rgb([x as u8 * 8, x as u8 * 6, 60]));
+        let result = recolor_image(&gradient, &read_json_palette(&path).unwrap());
+
+        assert_eq!(result.dimensions(), gradient.dimensions());
+        assert!(result.pixels().all(|p| p.0 == navy || p.0 == gold));
+        assert_eq!(result.get_pixel(0, 0).0, navy);
+        assert_eq!(result.get_pixel(31, 0).0, gold);
+    }
+}
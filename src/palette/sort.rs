@@ -0,0 +1,138 @@
+use exoquant::Color;
+
+use crate::types::config::SortOrder;
+use crate::utils::color_conversion::{
+    contrast_ratio, oklab_to_oklch, relative_luminance, rgb_to_hsl, rgb_to_oklab, Oklch,
+};
+
+/**
+ * Puts the palette in the requested order. This is applied as each output is written, as JSON and
+ * image outputs can be sorted differently (see `--sort-json` and `--sort-image`).
+ *
+ * [Vec<Color>] The extracted palette.
+ * [SortOrder] How to order the colors (`SortOrder::None` keeps the extraction order).
+ * [bool] Whether to reverse the order afterwards.
+ */
+pub fn sort_palette(mut colors: Vec<Color>, order: SortOrder, reverse: bool) -> Vec<Color> {
+    match order {
+        SortOrder::None => {}
+        SortOrder::Luminance => colors.sort_by(|a, b| {
+            relative_luminance(a.r, a.g, a.b).total_cmp(&relative_luminance(b.r, b.g, b.b))
+        }),
+        SortOrder::Hue => colors.sort_by(|a, b| {
+            rgb_to_hsl(a.r, a.g, a.b)
+                .h
+                .total_cmp(&rgb_to_hsl(b.r, b.g, b.b).h)
+        }),
+        SortOrder::Oklch => {
+            let oklch = |c: &Color| oklab_to_oklch(rgb_to_oklab(c.r, c.g, c.b));
+            colors.sort_by(|a, b| {
+                let (a, b): (Oklch, Oklch) = (oklch(a), oklch(b));
+                a.h.total_cmp(&b.h).then(b.l.total_cmp(&a.l))
+            })
+        }
+    }
+
+    if reverse {
+        colors.reverse();
+    }
+
+    colors
+}
+
+/**
+ * Puts the palette in order of WCAG contrast against a color (e.g. a page background), highest
+ * first, so the most readable color leads. Colors with the same contrast keep their order.
+ *
+ * [Vec<Color>] The extracted palette.
+ * [[u8; 3]] The color to measure contrast against.
+ * [bool] Whether to reverse the order afterwards.
+ */
+pub fn sort_by_contrast(mut colors: Vec<Color>, against: [u8; 3], reverse: bool) -> Vec<Color> {
+    let contrast = |c: &Color| contrast_ratio([c.r, c.g, c.b], against);
+    colors.sort_by(|a, b| contrast(b).total_cmp(&contrast(a)));
+
+    if reverse {
+        colors.reverse();
+    }
+
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(colors: &[Color]) -> Vec<[u8; 3]> {
+        colors.iter().map(|c| [c.r, c.g, c.b]).collect()
+    }
+
+    #[test]
+    fn test_sort_palette() {
+        let white = Color::new(255, 255, 255, 255);
+        let blue = Color::new(0, 0, 255, 255);
+        let yellow = Color::new(255, 255, 0, 255);
+        let red = Color::new(255, 0, 0, 255);
+        let palette = vec![white, blue, yellow, red];
+
+        // Test case 1: Extraction order
+        assert_eq!(
+            hex(&sort_palette(palette.clone(), SortOrder::None, false)),
+            hex(&[white, blue, yellow, red])
+        );
+
+        // Test case 2: Darkest first
+        assert_eq!(
+            hex(&sort_palette(palette.clone(), SortOrder::Luminance, false)),
+            hex(&[blue, red, yellow, white])
+        );
+
+        // Test case 3: By hue, with the achromatic white (hue 0) first
+        assert_eq!(
+            hex(&sort_palette(palette.clone(), SortOrder::Hue, false)),
+            hex(&[white, red, yellow, blue])
+        );
+
+        // Test case 4: By OKLCH hue, with the achromatic white (hue 0) first
+        assert_eq!(
+            hex(&sort_palette(palette.clone(), SortOrder::Oklch, false)),
+            hex(&[white, red, yellow, blue])
+        );
+        let black = Color::new(0, 0, 0, 255);
+        assert_eq!(
+            hex(&sort_palette(vec![black, white], SortOrder::Oklch, false)),
+            hex(&[white, black])
+        );
+
+        // Test case 5: Reversed
+        assert_eq!(
+            hex(&sort_palette(palette, SortOrder::None, true)),
+            hex(&[red, yellow, blue, white])
+        );
+    }
+
+    #[test]
+    fn test_sort_by_contrast() {
+        let white = Color::new(255, 255, 255, 255);
+        let navy = Color::new(0, 0, 128, 255);
+        let yellow = Color::new(255, 255, 0, 255);
+        let gray = Color::new(128, 128, 128, 255);
+        let palette = vec![yellow, gray, white, navy];
+
+        // Test case 1: Against white, the darkest color comes first and white itself last
+        assert_eq!(
+            hex(&sort_by_contrast(palette.clone(), [255, 255, 255], false)),
+            hex(&[navy, gray, yellow, white])
+        );
+
+        // Test case 2: Against black, the lightest comes first; and reversed
+        assert_eq!(
+            hex(&sort_by_contrast(palette.clone(), [0, 0, 0], false)),
+            hex(&[white, yellow, gray, navy])
+        );
+        assert_eq!(
+            hex(&sort_by_contrast(palette, [255, 255, 255], true)),
+            hex(&[white, yellow, gray, navy])
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use exoquant::Color;
+use image::RgbImage;
+
+use crate::palette::histogram::distinct_colors;
+
+/**
+ * The number of levels per channel of the grid used for a palette of `number_of_colors` colors:
+ * the cube root of the color count, rounded to the nearest whole number, and at least 2 (so that
+ * the grid always spans black to white).
+ *
+ * [usize] The number of colors required for the palette.
+ */
+pub fn grid_levels(number_of_colors: usize) -> u32 {
+    ((number_of_colors as f64).cbrt().round() as u32).max(2)
+}
+
+/**
+ * Snaps a channel value to the nearest of `levels` evenly spaced values between 0 and 255.
+ */
+fn snap(value: u8, levels: u32) -> u8 {
+    let step = 255.0 / (levels - 1) as f64;
+    ((value as f64 / step).round() * step).round() as u8
+}
+
+/**
+ * Builds a palette from a fixed, uniform subdivision of the RGB cube, rather than from the
+ * image's colors. The image only decides which cells of the grid are populated: each pixel is
+ * snapped to the nearest grid color, and the most common of those are returned.
+ *
+ * The grid has `levels³` cells, where `levels` is the cube root of `number_of_colors` rounded to
+ * the nearest whole number (see `grid_levels`), so e.g. asking for 10 colors uses the 8-color
+ * grid. At most `number_of_colors` colors are returned, and fewer when fewer cells are populated.
+ *
+ * [&RgbImage] The image to be processed.
+ * [usize] The number of colors required for the palette.
+ */
+pub fn uniform_palette(input_image: &RgbImage, number_of_colors: usize) -> Vec<Color> {
+    let levels = grid_levels(number_of_colors);
+
+    let mut cells: Vec<([u8; 3], u64)> = Vec::new();
+    for (rgb, count) in distinct_colors(input_image) {
+        let cell = rgb.map(|c| snap(c, levels));
+        match cells.iter_mut().find(|(c, _)| *c == cell) {
+            Some((_, total)) => *total += count,
+            None => cells.push((cell, count)),
+        }
+    }
+    cells.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    cells
+        .into_iter()
+        .take(number_of_colors)
+        .map(|([r, g, b], _)| Color::new(r, g, b, 0xff))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_levels() {
+        assert_eq!(grid_levels(1), 2);
+        assert_eq!(grid_levels(8), 2);
+        assert_eq!(grid_levels(10), 2);
+        assert_eq!(grid_levels(27), 3);
+        assert_eq!(grid_levels(50), 4);
+    }
+
+    #[test]
+    fn test_uniform_palette_is_grid_aligned() {
+        let image = RgbImage::from_fn(16, 16, |x, y| image::Rgb([x as u8 * 16, y as u8 * 16, 200]));
+        let palette = uniform_palette(&image, 27);
+
+        assert!(!palette.is_empty() && palette.len() <= 27);
+        for color in &palette {
+            for channel in [color.r, color.g, color.b] {
+                assert!(
+                    [0, 128, 255].contains(&channel),
+                    "{channel} is off the grid"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_uniform_palette_solid_image() {
+        let image = RgbImage::from_pixel(4, 4, image::Rgb([200, 60, 120]));
+
+        // Test case 1: The nearest color of the 27-color grid
+        let palette: Vec<[u8; 3]> = uniform_palette(&image, 27)
+            .iter()
+            .map(|c| [c.r, c.g, c.b])
+            .collect();
+        assert_eq!(palette, vec![[255, 0, 128]]);
+
+        // Test case 2: The nearest color of the 8-color grid
+        let palette: Vec<[u8; 3]> = uniform_palette(&image, 8)
+            .iter()
+            .map(|c| [c.r, c.g, c.b])
+            .collect();
+        assert_eq!(palette, vec![[255, 0, 0]]);
+    }
+}
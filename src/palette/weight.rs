@@ -0,0 +1,168 @@
+use image::RgbImage;
+
+/**
+ * The largest supported center weight. Every pixel is repeated up to this many times, so it also
+ * bounds how much extra memory the weighting needs.
+ */
+pub const MAX_CENTER_WEIGHT: f32 = 16.0;
+
+/**
+ * Weights the pixels of an image by how close they are to its center, so that central colors
+ * (e.g. the subject of a portrait) dominate the palette over the background at the edges.
+ *
+ * A pixel's weight falls off linearly from `center_weight` at the center to 1 at the corners, and
+ * is applied by repeating the pixel that many times (rounded), which works for every quantisation
+ * method. The weighted pixels are returned laid out in a single row, which is all that extraction
+ * needs. Returns `None` when the weight is 1 (every pixel counts the same).
+ *
+ * [&RgbImage] The image to be weighted.
+ * [f32] The weight of the central pixel, from 1 to `MAX_CENTER_WEIGHT`.
+ */
+pub fn center_weighted(input_image: &RgbImage, center_weight: f32) -> Option<RgbImage> {
+    if center_weight <= 1.0 {
+        return None;
+    }
+
+    let (width, height) = input_image.dimensions();
+    let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+    let max_distance = center_x.hypot(center_y);
+
+    let mut pixels: Vec<u8> = Vec::new();
+    for (x, y, pixel) in input_image.enumerate_pixels() {
+        let distance = (x as f32 + 0.5 - center_x).hypot(y as f32 + 0.5 - center_y);
+        let weight = 1.0 + (center_weight - 1.0) * (1.0 - distance / max_distance);
+        for _ in 0..(weight.round() as u32).max(1) {
+            pixels.extend_from_slice(&pixel.0);
+        }
+    }
+
+    Some(RgbImage::from_raw((pixels.len() / 3) as u32, 1, pixels).unwrap())
+}
+
+/**
+ * The weight of the pixels with the strongest edges under `saliency_weighted`. Like
+ * `MAX_CENTER_WEIGHT`, it bounds how many times a pixel is repeated.
+ */
+pub const MAX_SALIENCY_WEIGHT: f32 = 16.0;
+
+/**
+ * The strength of the edge at each pixel of an image: the magnitude of the Sobel gradient of its
+ * luma, in row-major order. Pixels beyond the edges of the image are taken to be the same as the
+ * nearest pixel inside it.
+ *
+ * [&RgbImage] The image to be measured.
+ */
+pub fn edge_strength(input_image: &RgbImage) -> Vec<f32> {
+    let (width, height) = input_image.dimensions();
+    let luma = |x: i64, y: i64| {
+        let x = x.clamp(0, width as i64 - 1) as u32;
+        let y = y.clamp(0, height as i64 - 1) as u32;
+        let [r, g, b] = input_image.get_pixel(x, y).0;
+        0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32
+    };
+
+    let mut strengths = Vec::with_capacity((width * height) as usize);
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            let gx = (luma(x + 1, y - 1) + 2.0 * luma(x + 1, y) + luma(x + 1, y + 1))
+                - (luma(x - 1, y - 1) + 2.0 * luma(x - 1, y) + luma(x - 1, y + 1));
+            let gy = (luma(x - 1, y + 1) + 2.0 * luma(x, y + 1) + luma(x + 1, y + 1))
+                - (luma(x - 1, y - 1) + 2.0 * luma(x, y - 1) + luma(x + 1, y - 1));
+            strengths.push(gx.hypot(gy));
+        }
+    }
+    strengths
+}
+
+/**
+ * Weights the pixels of an image by how much detail surrounds them, so that the colors of detailed
+ * regions (e.g. the subject of a photo) dominate the palette over flat areas like a smooth
+ * background gradient.
+ *
+ * A pixel's weight rises linearly from 1 where the image is flat to `MAX_SALIENCY_WEIGHT` at its
+ * strongest edge (see `edge_strength`), and is applied by repeating the pixel, as `center_weighted`
+ * does. Returns `None` when the image has no edges at all (every pixel counts the same).
+ *
+ * [&RgbImage] The image to be weighted.
+ */
+pub fn saliency_weighted(input_image: &RgbImage) -> Option<RgbImage> {
+    let strengths = edge_strength(input_image);
+    let strongest = strengths.iter().copied().fold(0.0, f32::max);
+    if strongest == 0.0 {
+        return None;
+    }
+
+    let mut pixels: Vec<u8> = Vec::new();
+    for (pixel, strength) in input_image.pixels().zip(strengths) {
+        let weight = 1.0 + (MAX_SALIENCY_WEIGHT - 1.0) * strength / strongest;
+        for _ in 0..(weight.round() as u32).max(1) {
+            pixels.extend_from_slice(&pixel.0);
+        }
+    }
+
+    Some(RgbImage::from_raw((pixels.len() / 3) as u32, 1, pixels).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::histogram::distinct_colors;
+
+    const RED: [u8; 3] = [220, 20, 20];
+    const BLUE: [u8; 3] = [20, 20, 220];
+
+    /// A red square in the middle of a wider blue border.
+    fn portrait() -> RgbImage {
+        RgbImage::from_fn(20, 20, |x, y| {
+            if (4..16).contains(&x) && (4..16).contains(&y) {
+                image::Rgb(RED)
+            } else {
+                image::Rgb(BLUE)
+            }
+        })
+    }
+
+    #[test]
+    fn test_center_weighted() {
+        let image = portrait();
+
+        // Test case 1: A weight of 1 leaves the image alone, and the border dominates
+        assert!(center_weighted(&image, 1.0).is_none());
+        assert_eq!(distinct_colors(&image)[0].0, BLUE);
+
+        // Test case 2: A high center weight lets the red center dominate
+        let weighted = center_weighted(&image, MAX_CENTER_WEIGHT).unwrap();
+        let counts = distinct_colors(&weighted);
+        assert_eq!(counts[0].0, RED);
+        assert!(counts[0].1 > counts[1].1);
+    }
+
+    #[test]
+    fn test_saliency_weighted() {
+        const GRAY: [u8; 3] = [128, 128, 128];
+        const YELLOW: [u8; 3] = [250, 230, 40];
+        const NAVY: [u8; 3] = [10, 10, 60];
+        // A flat gray background around a detailed center of yellow and navy stripes
+        let image = RgbImage::from_fn(32, 32, |x, y| {
+            if (8..24).contains(&x) && (8..24).contains(&y) {
+                image::Rgb(if (x / 2) % 2 == 0 { YELLOW } else { NAVY })
+            } else {
+                image::Rgb(GRAY)
+            }
+        });
+
+        // Test case 1: Unweighted, the background dominates
+        assert_eq!(distinct_colors(&image)[0].0, GRAY);
+
+        // Test case 2: Weighted by saliency, the colors of the detailed center rank above it
+        let weighted = saliency_weighted(&image).unwrap();
+        let ranked: Vec<[u8; 3]> = distinct_colors(&weighted).iter().map(|(c, _)| *c).collect();
+        assert_eq!(ranked.len(), 3);
+        assert_eq!(ranked[2], GRAY);
+
+        // Test case 3: A flat image has no edges to weight by
+        let flat = RgbImage::from_pixel(8, 8, image::Rgb(GRAY));
+        assert!(edge_strength(&flat).iter().all(|&s| s == 0.0));
+        assert!(saliency_weighted(&flat).is_none());
+    }
+}
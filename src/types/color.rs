@@ -0,0 +1,210 @@
+use exoquant::Color;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::types::config::ColorVisionDeficiency;
+use crate::utils::color_conversion::{
+    lab_to_lch, nearest_named_color, oklab_to_oklch, rgb_to_hex, rgb_to_lab, rgb_to_oklab,
+    simulate_cvd, Lab, Lch, Oklch,
+};
+
+/**
+ * Which of the optional details to include for each color in JSON output.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ColorDetails {
+    /// Include the CIELAB, LCh and OKLCH representations.
+    pub color_spaces: bool,
+    /// Include the name of the nearest CSS named color.
+    pub named_colors: bool,
+    /// Include how the color appears with this color vision deficiency.
+    pub simulate: Option<ColorVisionDeficiency>,
+}
+
+/**
+ * A palette color as it appears to someone with a color vision deficiency.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimulatedColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub hex: String,
+}
+
+/**
+ * The details of a single palette color, as written to JSON output.
+ *
+ * The `name`, `lab`, `lch`, `oklch` and `simulated` fields are only populated (and serialized)
+ * when they have been requested.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ColorInfo {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+    pub hex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lab: Option<Lab>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lch: Option<Lch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oklch: Option<Oklch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulated: Option<SimulatedColor>,
+}
+
+impl ColorInfo {
+    /**
+     * Builds the details for a palette color.
+     *
+     * [&Color] The palette color.
+     * [ColorDetails] Which of the optional details to include.
+     */
+    pub fn new(color: &Color, details: ColorDetails) -> ColorInfo {
+        let lab = details
+            .color_spaces
+            .then(|| rgb_to_lab(color.r, color.g, color.b));
+
+        ColorInfo {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: color.a,
+            hex: rgb_to_hex(color.r, color.g, color.b),
+            name: details
+                .named_colors
+                .then(|| nearest_named_color(color.r, color.g, color.b)),
+            lab,
+            lch: lab.map(lab_to_lch),
+            oklch: details
+                .color_spaces
+                .then(|| oklab_to_oklch(rgb_to_oklab(color.r, color.g, color.b))),
+            simulated: details.simulate.map(|kind| {
+                let simulated = simulate_cvd(color, kind);
+                SimulatedColor {
+                    r: simulated.r,
+                    g: simulated.g,
+                    b: simulated.b,
+                    hex: rgb_to_hex(simulated.r, simulated.g, simulated.b),
+                }
+            }),
+        }
+    }
+}
+
+/**
+ * One of the image's exact colors, and how many pixels are that color, as written to the JSON
+ * `histogram`.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct HistogramEntry {
+    pub hex: String,
+    pub count: u64,
+}
+
+impl HistogramEntry {
+    /**
+     * [[u8; 3]] The color.
+     * [u64] How many pixels are that color.
+     */
+    pub fn new([r, g, b]: [u8; 3], count: u64) -> HistogramEntry {
+        HistogramEntry {
+            hex: rgb_to_hex(r, g, b),
+            count,
+        }
+    }
+}
+
+/**
+ * The JSON document describing a palette. Each color is keyed by its 1-based position in the
+ * palette (`color_1`, `color_2`, ...), alongside any optional extras.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PaletteOutput {
+    #[serde(flatten)]
+    pub colors: Map<String, Value>,
+    /// A small PNG of the source image, as a base64 `data:` URI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    /// The 1-based positions of the colors in each hue family.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Map<String, Value>>,
+    /// The number of colors `--auto-colors` settled on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extracted_colors: Option<usize>,
+    /// The quantisation method `--quantisation-method auto` picked for the image.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantisation_method: Option<String>,
+    /// The SHA-256 digest of the source image file, as hex.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_sha256: Option<String>,
+    /// The image the palette was extracted from: an absolute path, or a URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<String>,
+    /// When the source image file was last modified, in seconds since the Unix epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_modified: Option<u64>,
+    /// How the source image's pixels were stored (the `image` crate's `ColorType`, e.g. `L8` for
+    /// grayscale or `Rgba8`), before being converted to RGB for extraction. Indexed PNGs are
+    /// expanded to RGB (or RGBA) as they're decoded, so are recorded as such.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_color_type: Option<String>,
+    /// The correlated color temperature of the image's average color, in Kelvin.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_temperature_k: Option<u32>,
+    /// The color covering the most of the image.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dominant_color: Option<ColorInfo>,
+    /// The image's most common exact colors, before quantisation, most common first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<Vec<HistogramEntry>>,
+    /// Write just the colors, as a bare array, leaving out the extras.
+    #[serde(skip)]
+    pub flat: bool,
+    /// Write the JSON on a single line, without any whitespace.
+    #[serde(skip)]
+    pub compact: bool,
+}
+
+impl PaletteOutput {
+    /**
+     * Builds the document for a palette, without any of the optional extras.
+     *
+     * [&[Color]] The palette colors.
+     * [ColorDetails] Which of the optional details to include for each color.
+     */
+    pub fn new(colors: &[Color], details: ColorDetails) -> PaletteOutput {
+        let colors = colors
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                (
+                    format!("color_{}", i + 1),
+                    serde_json::to_value(ColorInfo::new(color, details))
+                        .expect("ColorInfo is always serializable"),
+                )
+            })
+            .collect();
+
+        PaletteOutput {
+            colors,
+            thumbnail: None,
+            groups: None,
+            extracted_colors: None,
+            quantisation_method: None,
+            source_sha256: None,
+            source_path: None,
+            source_modified: None,
+            source_color_type: None,
+            color_temperature_k: None,
+            dominant_color: None,
+            histogram: None,
+            flat: false,
+            compact: false,
+        }
+    }
+}
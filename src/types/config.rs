@@ -0,0 +1,292 @@
+use std::fmt;
+
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum OutputType {
+    Json,
+    JsonFile,
+    OriginalImage,
+    StandalonePalette,
+    Text,
+    Scss,
+    Svg,
+    PaintNet,
+    /// A GIMP palette, which `--palette-in` can read back.
+    Gpl,
+    /// The original image remapped to its own palette, without a strip.
+    Posterize,
+    /// The palette as an indexed PNG with one pixel per color, for use as a color lookup table.
+    Clut,
+}
+
+impl fmt::Display for OutputType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OutputType::Json => write!(f, "json"),
+            OutputType::JsonFile => write!(f, "json-file"),
+            OutputType::OriginalImage => write!(f, "original-image"),
+            OutputType::StandalonePalette => write!(f, "standalone"),
+            OutputType::Text => write!(f, "text"),
+            OutputType::Scss => write!(f, "scss"),
+            OutputType::Svg => write!(f, "svg"),
+            OutputType::PaintNet => write!(f, "paint-net"),
+            OutputType::Gpl => write!(f, "gpl"),
+            OutputType::Posterize => write!(f, "posterize"),
+            OutputType::Clut => write!(f, "clut"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum QuantisationMethod {
+    KMeans,
+    MedianCut,
+    Uniform,
+    Popularity,
+    /// Pick one of the others to suit the image (see `choose_method`).
+    Auto,
+}
+
+impl fmt::Display for QuantisationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            QuantisationMethod::MedianCut => write!(f, "median-cut"),
+            QuantisationMethod::KMeans => write!(f, "k-means"),
+            QuantisationMethod::Uniform => write!(f, "uniform"),
+            QuantisationMethod::Popularity => write!(f, "popularity"),
+            QuantisationMethod::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/**
+ * The color space in which K-Means measures the distance between colors.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ColorSpace {
+    Rgb,
+    Lab,
+}
+
+impl fmt::Display for ColorSpace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ColorSpace::Rgb => write!(f, "rgb"),
+            ColorSpace::Lab => write!(f, "lab"),
+        }
+    }
+}
+
+/**
+ * The direction in which the swatches of a standalone palette are laid out.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+impl fmt::Display for Orientation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Orientation::Horizontal => write!(f, "horizontal"),
+            Orientation::Vertical => write!(f, "vertical"),
+        }
+    }
+}
+
+/**
+ * The order in which the palette colors are listed in every output.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum SortOrder {
+    /// The order the quantisation method produced them in.
+    None,
+    /// Darkest first, by WCAG relative luminance.
+    Luminance,
+    /// Around the HSL color wheel, starting at red.
+    Hue,
+    /// Around the perceptual OKLCH hue wheel, starting at pink-red, lightest first within a hue.
+    Oklch,
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SortOrder::None => write!(f, "none"),
+            SortOrder::Luminance => write!(f, "luminance"),
+            SortOrder::Hue => write!(f, "hue"),
+            SortOrder::Oklch => write!(f, "oklch"),
+        }
+    }
+}
+
+/**
+ * How a palette that came out smaller than requested is padded up to the requested size.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum PadStrategy {
+    /// Insert colors midway between the neighbouring colors furthest apart.
+    Interpolate,
+    /// Repeat the last color.
+    Repeat,
+}
+
+impl fmt::Display for PadStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PadStrategy::Interpolate => write!(f, "interpolate"),
+            PadStrategy::Repeat => write!(f, "repeat"),
+        }
+    }
+}
+
+/**
+ * What fills the end of a palette strip that the swatches don't reach, e.g. when the strip's length
+ * isn't a multiple of the number of colors.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum PaletteFill {
+    /// The background color.
+    Background,
+    /// The last swatch, stretched to the end of the strip.
+    Extend,
+}
+
+impl fmt::Display for PaletteFill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PaletteFill::Background => write!(f, "background"),
+            PaletteFill::Extend => write!(f, "extend"),
+        }
+    }
+}
+
+/**
+ * The resampling filter `--sample-size` downsamples with, from quickest to most accurate.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum DownscaleFilter {
+    /// Keep the nearest pixel. Quickest, but fine detail can be skipped over entirely.
+    Nearest,
+    /// Average neighbouring pixels linearly.
+    Triangle,
+    /// Average neighbouring pixels with a Gaussian falloff, softening the image a little.
+    Gaussian,
+    /// Lanczos with a window of 3. Slowest, but keeps the most detail.
+    Lanczos3,
+}
+
+impl fmt::Display for DownscaleFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DownscaleFilter::Nearest => write!(f, "nearest"),
+            DownscaleFilter::Triangle => write!(f, "triangle"),
+            DownscaleFilter::Gaussian => write!(f, "gaussian"),
+            DownscaleFilter::Lanczos3 => write!(f, "lanczos3"),
+        }
+    }
+}
+
+/**
+ * How per-image errors are reported on stderr.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ErrorFormat {
+    /// A line of English per error.
+    Text,
+    /// A JSON object per error, one per line.
+    Json,
+}
+
+impl fmt::Display for ErrorFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ErrorFormat::Text => write!(f, "text"),
+            ErrorFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/**
+ * The edge of the original image that the palette strip is attached to.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum PalettePosition {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl fmt::Display for PalettePosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PalettePosition::Top => write!(f, "top"),
+            PalettePosition::Bottom => write!(f, "bottom"),
+            PalettePosition::Left => write!(f, "left"),
+            PalettePosition::Right => write!(f, "right"),
+        }
+    }
+}
+
+/**
+ * What a percentage palette height of a standalone palette is a percentage of.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum PercentageBase {
+    /// The height of the source image.
+    Height,
+    /// The width of the palette itself (`--palette-width`, or the width of the source image).
+    Width,
+}
+
+impl fmt::Display for PercentageBase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PercentageBase::Height => write!(f, "height"),
+            PercentageBase::Width => write!(f, "width"),
+        }
+    }
+}
+
+/**
+ * The forms of dichromatic color blindness a palette can be simulated under.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ColorVisionDeficiency {
+    /// No red (L) cones.
+    Protanopia,
+    /// No green (M) cones.
+    Deuteranopia,
+    /// No blue (S) cones.
+    Tritanopia,
+}
+
+impl fmt::Display for ColorVisionDeficiency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ColorVisionDeficiency::Protanopia => write!(f, "protanopia"),
+            ColorVisionDeficiency::Deuteranopia => write!(f, "deuteranopia"),
+            ColorVisionDeficiency::Tritanopia => write!(f, "tritanopia"),
+        }
+    }
+}
+
+/**
+ * The percentage of the image a palette height of `auto` stands for.
+ */
+pub const AUTO_PALETTE_HEIGHT_PERCENTAGE: f32 = 15.0;
+
+/**
+ * The largest percentage palette height accepted, with `--allow-large-palette`. Without it, the
+ * palette can be at most as big as the image (100%).
+ */
+pub const MAX_PALETTE_HEIGHT_PERCENTAGE: f32 = 1000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaletteHeight {
+    Absolute(u32),
+    Percentage(f32),
+}
@@ -0,0 +1,3 @@
+pub mod color;
+pub mod config;
+pub mod outcome;
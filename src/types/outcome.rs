@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use exoquant::Color;
+
+use crate::utils::color_conversion::rgb_to_hex;
+
+/**
+ * The outcome of processing a single image, kept so that a summary can be reported once the
+ * whole batch has been processed.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcessOutcome {
+    pub file: PathBuf,
+    pub color_count: usize,
+    pub top_color: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ProcessOutcome {
+    /**
+     * Builds the outcome for an image from the result of processing it.
+     *
+     * [&Path] The image that was processed.
+     * [&Result<Vec<Color>>] The extracted palette, or the error that stopped processing.
+     */
+    pub fn new(file: &Path, result: &Result<Vec<Color>>) -> ProcessOutcome {
+        match result {
+            Ok(colors) => ProcessOutcome {
+                file: file.to_path_buf(),
+                color_count: colors.len(),
+                top_color: colors.first().map(|c| rgb_to_hex(c.r, c.g, c.b)),
+                error: None,
+            },
+            Err(e) => ProcessOutcome {
+                file: file.to_path_buf(),
+                color_count: 0,
+                top_color: None,
+                error: Some(format!("{e:#}")),
+            },
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
@@ -0,0 +1,902 @@
+use exoquant::Color;
+use serde::Serialize;
+
+use crate::types::config::ColorVisionDeficiency;
+
+/**
+ * The reference white of the CIE standard illuminant D65 (2° standard observer), with the
+ * luminance (Y) normalised to 1.0.
+ */
+const D65_WHITE: [f64; 3] = [0.95047, 1.0, 1.08883];
+
+/**
+ * A color in the CIELAB (L*a*b*) color space.
+ *
+ * `l` is the lightness (0–100); `a` and `b` are the green–red and blue–yellow axes respectively.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Lab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/**
+ * A color in the CIE LCh(ab) color space: the cylindrical form of CIELAB.
+ *
+ * `l` is the lightness (0–100), `c` the chroma, and `h` the hue angle in degrees (0–360).
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Lch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+}
+
+/**
+ * A color in the OKLab color space (Björn Ottosson, 2020).
+ *
+ * `l` is the perceived lightness (0–1); `a` and `b` are the green–red and blue–yellow axes
+ * respectively, roughly within ±0.4.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Oklab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/**
+ * A color in the OKLCH color space: the cylindrical form of OKLab.
+ *
+ * `l` is the perceived lightness (0–1), `c` the chroma (0 to roughly 0.37 for sRGB colors), and
+ * `h` the hue angle in degrees (0–360).
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Oklch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+}
+
+/**
+ * A color in the HSL color space.
+ *
+ * `h` is the hue in degrees (0–360); `s` (saturation) and `l` (lightness) are in the range 0–1.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Hsl {
+    pub h: f64,
+    pub s: f64,
+    pub l: f64,
+}
+
+/**
+ * This helper function just converts a color from RGB values to a hex string.
+ */
+pub fn rgb_to_hex(red: u8, green: u8, blue: u8) -> String {
+    format!("#{red:02x}{green:02x}{blue:02x}")
+}
+
+/**
+ * The CSS3 named colors. Where two names share a value (e.g. `aqua` and `cyan`), the first is the
+ * one `nearest_named_color` returns.
+ */
+const CSS_NAMED_COLORS: [(&str, [u8; 3]); 147] = [
+    ("aliceblue", [240, 248, 255]),
+    ("antiquewhite", [250, 235, 215]),
+    ("aqua", [0, 255, 255]),
+    ("aquamarine", [127, 255, 212]),
+    ("azure", [240, 255, 255]),
+    ("beige", [245, 245, 220]),
+    ("bisque", [255, 228, 196]),
+    ("black", [0, 0, 0]),
+    ("blanchedalmond", [255, 235, 205]),
+    ("blue", [0, 0, 255]),
+    ("blueviolet", [138, 43, 226]),
+    ("brown", [165, 42, 42]),
+    ("burlywood", [222, 184, 135]),
+    ("cadetblue", [95, 158, 160]),
+    ("chartreuse", [127, 255, 0]),
+    ("chocolate", [210, 105, 30]),
+    ("coral", [255, 127, 80]),
+    ("cornflowerblue", [100, 149, 237]),
+    ("cornsilk", [255, 248, 220]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("darkblue", [0, 0, 139]),
+    ("darkcyan", [0, 139, 139]),
+    ("darkgoldenrod", [184, 134, 11]),
+    ("darkgray", [169, 169, 169]),
+    ("darkgreen", [0, 100, 0]),
+    ("darkgrey", [169, 169, 169]),
+    ("darkkhaki", [189, 183, 107]),
+    ("darkmagenta", [139, 0, 139]),
+    ("darkolivegreen", [85, 107, 47]),
+    ("darkorange", [255, 140, 0]),
+    ("darkorchid", [153, 50, 204]),
+    ("darkred", [139, 0, 0]),
+    ("darksalmon", [233, 150, 122]),
+    ("darkseagreen", [143, 188, 143]),
+    ("darkslateblue", [72, 61, 139]),
+    ("darkslategray", [47, 79, 79]),
+    ("darkslategrey", [47, 79, 79]),
+    ("darkturquoise", [0, 206, 209]),
+    ("darkviolet", [148, 0, 211]),
+    ("deeppink", [255, 20, 147]),
+    ("deepskyblue", [0, 191, 255]),
+    ("dimgray", [105, 105, 105]),
+    ("dimgrey", [105, 105, 105]),
+    ("dodgerblue", [30, 144, 255]),
+    ("firebrick", [178, 34, 34]),
+    ("floralwhite", [255, 250, 240]),
+    ("forestgreen", [34, 139, 34]),
+    ("fuchsia", [255, 0, 255]),
+    ("gainsboro", [220, 220, 220]),
+    ("ghostwhite", [248, 248, 255]),
+    ("gold", [255, 215, 0]),
+    ("goldenrod", [218, 165, 32]),
+    ("gray", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("greenyellow", [173, 255, 47]),
+    ("grey", [128, 128, 128]),
+    ("honeydew", [240, 255, 240]),
+    ("hotpink", [255, 105, 180]),
+    ("indianred", [205, 92, 92]),
+    ("indigo", [75, 0, 130]),
+    ("ivory", [255, 255, 240]),
+    ("khaki", [240, 230, 140]),
+    ("lavender", [230, 230, 250]),
+    ("lavenderblush", [255, 240, 245]),
+    ("lawngreen", [124, 252, 0]),
+    ("lemonchiffon", [255, 250, 205]),
+    ("lightblue", [173, 216, 230]),
+    ("lightcoral", [240, 128, 128]),
+    ("lightcyan", [224, 255, 255]),
+    ("lightgoldenrodyellow", [250, 250, 210]),
+    ("lightgray", [211, 211, 211]),
+    ("lightgreen", [144, 238, 144]),
+    ("lightgrey", [211, 211, 211]),
+    ("lightpink", [255, 182, 193]),
+    ("lightsalmon", [255, 160, 122]),
+    ("lightseagreen", [32, 178, 170]),
+    ("lightskyblue", [135, 206, 250]),
+    ("lightslategray", [119, 136, 153]),
+    ("lightslategrey", [119, 136, 153]),
+    ("lightsteelblue", [176, 196, 222]),
+    ("lightyellow", [255, 255, 224]),
+    ("lime", [0, 255, 0]),
+    ("limegreen", [50, 205, 50]),
+    ("linen", [250, 240, 230]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("mediumaquamarine", [102, 205, 170]),
+    ("mediumblue", [0, 0, 205]),
+    ("mediumorchid", [186, 85, 211]),
+    ("mediumpurple", [147, 112, 219]),
+    ("mediumseagreen", [60, 179, 113]),
+    ("mediumslateblue", [123, 104, 238]),
+    ("mediumspringgreen", [0, 250, 154]),
+    ("mediumturquoise", [72, 209, 204]),
+    ("mediumvioletred", [199, 21, 133]),
+    ("midnightblue", [25, 25, 112]),
+    ("mintcream", [245, 255, 250]),
+    ("mistyrose", [255, 228, 225]),
+    ("moccasin", [255, 228, 181]),
+    ("navajowhite", [255, 222, 173]),
+    ("navy", [0, 0, 128]),
+    ("oldlace", [253, 245, 230]),
+    ("olive", [128, 128, 0]),
+    ("olivedrab", [107, 142, 35]),
+    ("orange", [255, 165, 0]),
+    ("orangered", [255, 69, 0]),
+    ("orchid", [218, 112, 214]),
+    ("palegoldenrod", [238, 232, 170]),
+    ("palegreen", [152, 251, 152]),
+    ("paleturquoise", [175, 238, 238]),
+    ("palevioletred", [219, 112, 147]),
+    ("papayawhip", [255, 239, 213]),
+    ("peachpuff", [255, 218, 185]),
+    ("peru", [205, 133, 63]),
+    ("pink", [255, 192, 203]),
+    ("plum", [221, 160, 221]),
+    ("powderblue", [176, 224, 230]),
+    ("purple", [128, 0, 128]),
+    ("red", [255, 0, 0]),
+    ("rosybrown", [188, 143, 143]),
+    ("royalblue", [65, 105, 225]),
+    ("saddlebrown", [139, 69, 19]),
+    ("salmon", [250, 128, 114]),
+    ("sandybrown", [244, 164, 96]),
+    ("seagreen", [46, 139, 87]),
+    ("seashell", [255, 245, 238]),
+    ("sienna", [160, 82, 45]),
+    ("silver", [192, 192, 192]),
+    ("skyblue", [135, 206, 235]),
+    ("slateblue", [106, 90, 205]),
+    ("slategray", [112, 128, 144]),
+    ("slategrey", [112, 128, 144]),
+    ("snow", [255, 250, 250]),
+    ("springgreen", [0, 255, 127]),
+    ("steelblue", [70, 130, 180]),
+    ("tan", [210, 180, 140]),
+    ("teal", [0, 128, 128]),
+    ("thistle", [216, 191, 216]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("wheat", [245, 222, 179]),
+    ("white", [255, 255, 255]),
+    ("whitesmoke", [245, 245, 245]),
+    ("yellow", [255, 255, 0]),
+    ("yellowgreen", [154, 205, 50]),
+];
+
+/**
+ * Returns the name of the CSS3 named color closest to an RGB color (by Euclidean distance in
+ * RGB). Colors that are exactly a named color resolve to that name.
+ */
+pub fn nearest_named_color(red: u8, green: u8, blue: u8) -> &'static str {
+    let distance = |c: &[u8; 3]| -> u32 {
+        (c[0] as i32 - red as i32).pow(2) as u32
+            + (c[1] as i32 - green as i32).pow(2) as u32
+            + (c[2] as i32 - blue as i32).pow(2) as u32
+    };
+
+    CSS_NAMED_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| distance(rgb))
+        .map(|(name, _)| *name)
+        .unwrap()
+}
+
+/**
+ * This helper function is used by clap when handling options that take a color. It parses a hex
+ * string (`#rrggbb` or `rrggbb`, in either case) into its RGB components.
+ */
+pub fn hex_to_rgb(s: &str) -> Result<[u8; 3], String> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("'{s}' is not a hex color (e.g. #1a6b3f)"));
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+    Ok([channel(0), channel(2), channel(4)])
+}
+
+/**
+ * Converts an RGB color to HSL. Achromatic colors (grays) have a hue and saturation of 0.
+ */
+pub fn rgb_to_hsl(red: u8, green: u8, blue: u8) -> Hsl {
+    let r = red as f64 / 255.0;
+    let g = green as f64 / 255.0;
+    let b = blue as f64 / 255.0;
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let l = (max + min) / 2.0;
+
+    if delta == 0.0 {
+        return Hsl { h: 0.0, s: 0.0, l };
+    }
+
+    let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+
+    Hsl { h, s, l }
+}
+
+/**
+ * Converts an HSL color back to RGB, rounding each channel to the nearest integer.
+ */
+pub fn hsl_to_rgb(hsl: Hsl) -> [u8; 3] {
+    let chroma = (1.0 - (2.0 * hsl.l - 1.0).abs()) * hsl.s;
+    let h = hsl.h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = hsl.l - chroma / 2.0;
+    let channel = |c: f64| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+/**
+ * Converts a single gamma-encoded sRGB channel (0–255) to linear light (0.0–1.0) using the sRGB
+ * transfer function.
+ */
+pub fn srgb_to_linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/**
+ * Converts a linear light value (0.0–1.0) back to a gamma-encoded sRGB channel (0–255). Values
+ * outside the displayable range are clamped.
+ */
+pub fn linear_to_srgb(value: f64) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let c = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+
+    (c * 255.0).round() as u8
+}
+
+/**
+ * Linearises a gamma-encoded sRGB color, scaling linear light back to 0–255 so it can be stored in
+ * an 8-bit image. Dark shades are crowded together by the rounding (the darkest seven all become
+ * 0), which matters little for averaging and clustering, as they carry little light.
+ */
+pub fn linearize(rgb: [u8; 3]) -> [u8; 3] {
+    rgb.map(|c| (srgb_to_linear(c) * 255.0).round() as u8)
+}
+
+/**
+ * Gamma-encodes a color linearised by `linearize` back to sRGB.
+ */
+pub fn delinearize(rgb: [u8; 3]) -> [u8; 3] {
+    rgb.map(|c| linear_to_srgb(c as f64 / 255.0))
+}
+
+/**
+ * Converts linear RGB to the LMS cone response space (Viénot, Brettel & Mollon, 1999).
+ */
+const RGB_TO_LMS: [[f64; 3]; 3] = [
+    [17.8824, 43.5161, 4.11935],
+    [3.45565, 27.1554, 3.86714],
+    [0.0299566, 0.184309, 1.46709],
+];
+
+/**
+ * The inverse of `RGB_TO_LMS`.
+ */
+const LMS_TO_RGB: [[f64; 3]; 3] = [
+    [0.0809444479, -0.130504409, 0.116721066],
+    [-0.0102485335, 0.0540193266, -0.113614708],
+    [-0.000365296938, -0.00412161469, 0.693511405],
+];
+
+fn multiply(matrix: &[[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    matrix.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+/**
+ * Simulates how a color appears to someone with a form of dichromatic color blindness. The color
+ * is taken into LMS space, the response of the missing cone is rebuilt from the other two, and the
+ * result is taken back to sRGB. The projections keep white, and blue (protanopia and deuteranopia)
+ * or red (tritanopia), unchanged. Alpha is left as it is.
+ */
+pub fn simulate_cvd(color: &Color, kind: ColorVisionDeficiency) -> Color {
+    let linear = [color.r, color.g, color.b].map(srgb_to_linear);
+    let [l, m, s] = multiply(&RGB_TO_LMS, linear);
+
+    let lms = match kind {
+        ColorVisionDeficiency::Protanopia => [2.02344 * m - 2.52581 * s, m, s],
+        ColorVisionDeficiency::Deuteranopia => [l, 0.494207 * l + 1.24827 * s, s],
+        ColorVisionDeficiency::Tritanopia => [l, m, -0.0122450 * l + 0.0720345 * m],
+    };
+
+    let [r, g, b] = multiply(&LMS_TO_RGB, lms).map(linear_to_srgb);
+    Color::new(r, g, b, color.a)
+}
+
+/**
+ * The relative luminance of an sRGB color, as defined by WCAG: 0.0 for black, 1.0 for white.
+ */
+pub fn relative_luminance(red: u8, green: u8, blue: u8) -> f64 {
+    0.2126 * srgb_to_linear(red) + 0.7152 * srgb_to_linear(green) + 0.0722 * srgb_to_linear(blue)
+}
+
+/**
+ * The WCAG contrast ratio between two sRGB colors, from 1.0 (the same luminance) to 21.0 (black
+ * and white). The order of the colors doesn't matter.
+ */
+pub fn contrast_ratio(a: [u8; 3], b: [u8; 3]) -> f64 {
+    let a = relative_luminance(a[0], a[1], a[2]);
+    let b = relative_luminance(b[0], b[1], b[2]);
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/**
+ * Converts an sRGB color to CIE XYZ.
+ *
+ * Assumes the sRGB primaries with a D65 white point and the CIE 1931 2° standard observer. The
+ * result is scaled so that the Y of sRGB white is 1.0.
+ */
+pub fn rgb_to_xyz(red: u8, green: u8, blue: u8) -> [f64; 3] {
+    let r = srgb_to_linear(red);
+    let g = srgb_to_linear(green);
+    let b = srgb_to_linear(blue);
+
+    [
+        0.4124564 * r + 0.3575761 * g + 0.1804375 * b,
+        0.2126729 * r + 0.7151522 * g + 0.0721750 * b,
+        0.0193339 * r + 0.1191920 * g + 0.9503041 * b,
+    ]
+}
+
+/**
+ * Estimates the correlated color temperature of an sRGB color, in Kelvin, from its CIE 1931 xy
+ * chromaticity using McCamy's cubic approximation. Warm (reddish) colors come out low, around
+ * 2000K, and cool (bluish) ones high; sRGB white is about 6500K. The approximation is only
+ * meaningful for colors near the white of some light source, not for saturated colors. Returns
+ * `None` for black, which has no chromaticity.
+ */
+pub fn rgb_to_cct(red: u8, green: u8, blue: u8) -> Option<f64> {
+    let [x, y, z] = rgb_to_xyz(red, green, blue);
+    let sum = x + y + z;
+    if sum == 0.0 {
+        return None;
+    }
+
+    let n = (x / sum - 0.3320) / (0.1858 - y / sum);
+    Some(449.0 * n.powi(3) + 3525.0 * n.powi(2) + 6823.3 * n + 5520.33)
+}
+
+/**
+ * Converts a CIE XYZ color to CIELAB, relative to the D65 reference white (2° observer).
+ */
+pub fn xyz_to_lab(xyz: [f64; 3]) -> Lab {
+    fn f(t: f64) -> f64 {
+        const DELTA: f64 = 6.0 / 29.0;
+        if t > DELTA * DELTA * DELTA {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    }
+
+    let fx = f(xyz[0] / D65_WHITE[0]);
+    let fy = f(xyz[1] / D65_WHITE[1]);
+    let fz = f(xyz[2] / D65_WHITE[2]);
+
+    Lab {
+        l: 116.0 * fy - 16.0,
+        a: 500.0 * (fx - fy),
+        b: 200.0 * (fy - fz),
+    }
+}
+
+/**
+ * Converts a CIE XYZ color (D65, 2° observer, Y of white = 1.0) back to sRGB. Colors outside the
+ * sRGB gamut are clamped.
+ */
+pub fn xyz_to_rgb(xyz: [f64; 3]) -> [u8; 3] {
+    let [x, y, z] = xyz;
+
+    [
+        linear_to_srgb(3.2404542 * x - 1.5371385 * y - 0.4985314 * z),
+        linear_to_srgb(-0.9692660 * x + 1.8760108 * y + 0.0415560 * z),
+        linear_to_srgb(0.0556434 * x - 0.2040259 * y + 1.0572252 * z),
+    ]
+}
+
+/**
+ * Converts a CIELAB color back to CIE XYZ, relative to the D65 reference white (2° observer).
+ */
+pub fn lab_to_xyz(lab: Lab) -> [f64; 3] {
+    fn f_inv(t: f64) -> f64 {
+        const DELTA: f64 = 6.0 / 29.0;
+        if t > DELTA {
+            t * t * t
+        } else {
+            3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+        }
+    }
+
+    let fy = (lab.l + 16.0) / 116.0;
+    let fx = fy + lab.a / 500.0;
+    let fz = fy - lab.b / 200.0;
+
+    [
+        D65_WHITE[0] * f_inv(fx),
+        D65_WHITE[1] * f_inv(fy),
+        D65_WHITE[2] * f_inv(fz),
+    ]
+}
+
+/**
+ * Converts an sRGB color to CIELAB via linear RGB and CIE XYZ.
+ *
+ * Uses the D65 illuminant and the CIE 1931 2° standard observer throughout; no chromatic
+ * adaptation is applied.
+ */
+pub fn rgb_to_lab(red: u8, green: u8, blue: u8) -> Lab {
+    xyz_to_lab(rgb_to_xyz(red, green, blue))
+}
+
+/**
+ * Converts a CIELAB color (D65 illuminant, 2° observer) back to sRGB, clamping out-of-gamut
+ * colors.
+ */
+pub fn lab_to_rgb(lab: Lab) -> [u8; 3] {
+    xyz_to_rgb(lab_to_xyz(lab))
+}
+
+/**
+ * LCh chroma below this is treated as zero: far smaller than the difference one 8-bit step makes.
+ */
+const LCH_ACHROMATIC_CHROMA: f64 = 1e-3;
+
+/**
+ * Converts a CIELAB color to its cylindrical LCh form.
+ *
+ * The hue is returned in degrees, normalised to the range 0–360. Achromatic colors (chroma of
+ * zero) have an undefined hue, which is reported as 0. Rounding leaves grays with a tiny chroma
+ * rather than exactly zero, so anything below `LCH_ACHROMATIC_CHROMA` counts as achromatic.
+ */
+pub fn lab_to_lch(lab: Lab) -> Lch {
+    let c = (lab.a * lab.a + lab.b * lab.b).sqrt();
+    let h = if c < LCH_ACHROMATIC_CHROMA {
+        0.0
+    } else {
+        lab.b.atan2(lab.a).to_degrees().rem_euclid(360.0)
+    };
+
+    Lch { l: lab.l, c, h }
+}
+
+/**
+ * OKLCH chroma below this is treated as zero: far smaller than the difference one 8-bit step makes.
+ */
+const OKLCH_ACHROMATIC_CHROMA: f64 = 1e-4;
+
+/**
+ * Converts an sRGB color to OKLab: the channels are linearised, taken to an approximation of the
+ * cone responses (LMS), compressed with a cube root, and then mixed into lightness and the two
+ * opponent axes.
+ */
+pub fn rgb_to_oklab(red: u8, green: u8, blue: u8) -> Oklab {
+    let [r, g, b] = [red, green, blue].map(srgb_to_linear);
+
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    Oklab {
+        l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    }
+}
+
+/**
+ * Converts an OKLab color to its cylindrical OKLCH form.
+ *
+ * The hue is returned in degrees, normalised to the range 0–360. Achromatic colors have an
+ * undefined hue, which is reported as 0. The conversion matrices leave grays with a tiny chroma
+ * rather than exactly zero, so anything below `OKLCH_ACHROMATIC_CHROMA` counts as achromatic.
+ */
+pub fn oklab_to_oklch(oklab: Oklab) -> Oklch {
+    let c = (oklab.a * oklab.a + oklab.b * oklab.b).sqrt();
+    let h = if c < OKLCH_ACHROMATIC_CHROMA {
+        0.0
+    } else {
+        oklab.b.atan2(oklab.a).to_degrees().rem_euclid(360.0)
+    };
+
+    Oklch { l: oklab.l, c, h }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {expected} ± {tolerance}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_rgb_to_hex() {
+        // Test case 1: All zeros
+        assert_eq!(rgb_to_hex(0, 0, 0), "#000000");
+
+        // Test case 2: All max values
+        assert_eq!(rgb_to_hex(255, 255, 255), "#ffffff");
+
+        // Test case 3: Random values
+        assert_eq!(rgb_to_hex(128, 64, 32), "#804020");
+    }
+
+    #[test]
+    fn test_hex_to_rgb() {
+        // Test case 1: With a leading '#'
+        assert_eq!(hex_to_rgb("#804020"), Ok([128, 64, 32]));
+
+        // Test case 2: Without a leading '#', in uppercase
+        assert_eq!(hex_to_rgb("FFfF00"), Ok([255, 255, 0]));
+
+        // Test case 3: Invalid input
+        assert!(hex_to_rgb("#fff").is_err());
+        assert!(hex_to_rgb("#gggggg").is_err());
+        assert!(hex_to_rgb("").is_err());
+    }
+
+    #[test]
+    fn test_rgb_to_hsl() {
+        // Test case 1: Primaries
+        assert_eq!(
+            rgb_to_hsl(255, 0, 0),
+            Hsl {
+                h: 0.0,
+                s: 1.0,
+                l: 0.5
+            }
+        );
+        assert_eq!(
+            rgb_to_hsl(0, 255, 0),
+            Hsl {
+                h: 120.0,
+                s: 1.0,
+                l: 0.5
+            }
+        );
+        assert_eq!(
+            rgb_to_hsl(0, 0, 255),
+            Hsl {
+                h: 240.0,
+                s: 1.0,
+                l: 0.5
+            }
+        );
+
+        // Test case 2: Grays have no hue or saturation
+        let hsl = rgb_to_hsl(128, 128, 128);
+        assert_eq!((hsl.h, hsl.s), (0.0, 0.0));
+        assert_close(hsl.l, 0.502, 0.001);
+
+        // Test case 3: A muted color
+        let hsl = rgb_to_hsl(26, 107, 63);
+        assert_close(hsl.h, 147.4, 0.1);
+        assert_close(hsl.s, 0.609, 0.001);
+        assert_close(hsl.l, 0.261, 0.001);
+    }
+
+    #[test]
+    fn test_hsl_to_rgb() {
+        // Test case 1: Primaries and grays
+        assert_eq!(hsl_to_rgb(rgb_to_hsl(255, 0, 0)), [255, 0, 0]);
+        assert_eq!(hsl_to_rgb(rgb_to_hsl(0, 0, 255)), [0, 0, 255]);
+        assert_eq!(hsl_to_rgb(rgb_to_hsl(128, 128, 128)), [128, 128, 128]);
+
+        // Test case 2: Colors survive the round trip
+        for rgb in [
+            [26, 107, 63],
+            [200, 150, 30],
+            [90, 20, 180],
+            [250, 240, 230],
+        ] {
+            assert_eq!(hsl_to_rgb(rgb_to_hsl(rgb[0], rgb[1], rgb[2])), rgb);
+        }
+    }
+
+    #[test]
+    fn test_rgb_to_lab() {
+        // Test case 1: White
+        let lab = rgb_to_lab(255, 255, 255);
+        assert_close(lab.l, 100.0, 0.01);
+        assert_close(lab.a, 0.0, 0.01);
+        assert_close(lab.b, 0.0, 0.01);
+
+        // Test case 2: Black
+        let lab = rgb_to_lab(0, 0, 0);
+        assert_close(lab.l, 0.0, 0.01);
+        assert_close(lab.a, 0.0, 0.01);
+        assert_close(lab.b, 0.0, 0.01);
+
+        // Test case 3: Red
+        let lab = rgb_to_lab(255, 0, 0);
+        assert_close(lab.l, 53.24, 0.01);
+        assert_close(lab.a, 80.09, 0.01);
+        assert_close(lab.b, 67.20, 0.01);
+
+        // Test case 4: Blue
+        let lab = rgb_to_lab(0, 0, 255);
+        assert_close(lab.l, 32.30, 0.01);
+        assert_close(lab.a, 79.19, 0.01);
+        assert_close(lab.b, -107.86, 0.01);
+    }
+
+    #[test]
+    fn test_lab_to_rgb() {
+        // Converting to CIELAB and back should be lossless at 8 bits per channel
+        for rgb in [
+            [0, 0, 0],
+            [255, 255, 255],
+            [255, 0, 0],
+            [18, 200, 97],
+            [3, 7, 250],
+        ] {
+            assert_eq!(lab_to_rgb(rgb_to_lab(rgb[0], rgb[1], rgb[2])), rgb);
+        }
+    }
+
+    #[test]
+    fn test_lab_to_lch() {
+        // Test case 1: Red
+        let lch = lab_to_lch(rgb_to_lab(255, 0, 0));
+        assert_close(lch.l, 53.24, 0.01);
+        assert_close(lch.c, 104.55, 0.01);
+        assert_close(lch.h, 40.00, 0.01);
+
+        // Test case 2: Blue (hue is negative before normalisation)
+        let lch = lab_to_lch(rgb_to_lab(0, 0, 255));
+        assert_close(lch.c, 133.81, 0.01);
+        assert_close(lch.h, 306.29, 0.01);
+
+        // Test case 3: Grays have no chroma, and a hue of 0
+        for gray in [0, 1, 128, 254, 255] {
+            let lch = lab_to_lch(rgb_to_lab(gray, gray, gray));
+            assert_close(lch.c, 0.0, 0.01);
+            assert_eq!(lch.h, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_rgb_to_oklab() {
+        // Reference values from https://bottosson.github.io/posts/oklab/ and CSS Color 4
+
+        // Test case 1: White
+        let oklab = rgb_to_oklab(255, 255, 255);
+        assert_close(oklab.l, 1.0, 0.0001);
+        assert_close(oklab.a, 0.0, 0.0001);
+        assert_close(oklab.b, 0.0, 0.0001);
+
+        // Test case 2: The primaries
+        let oklab = rgb_to_oklab(255, 0, 0);
+        assert_close(oklab.l, 0.62796, 0.0001);
+        assert_close(oklab.a, 0.22486, 0.0001);
+        assert_close(oklab.b, 0.12585, 0.0001);
+
+        let oklab = rgb_to_oklab(0, 255, 0);
+        assert_close(oklab.l, 0.86644, 0.0001);
+        assert_close(oklab.a, -0.23389, 0.0001);
+        assert_close(oklab.b, 0.17950, 0.0001);
+
+        let oklab = rgb_to_oklab(0, 0, 255);
+        assert_close(oklab.l, 0.45201, 0.0001);
+        assert_close(oklab.a, -0.03246, 0.0001);
+        assert_close(oklab.b, -0.31153, 0.0001);
+    }
+
+    #[test]
+    fn test_oklab_to_oklch() {
+        // Test case 1: Red
+        let oklch = oklab_to_oklch(rgb_to_oklab(255, 0, 0));
+        assert_close(oklch.l, 0.62796, 0.0001);
+        assert_close(oklch.c, 0.25768, 0.0001);
+        assert_close(oklch.h, 29.234, 0.01);
+
+        // Test case 2: Blue
+        let oklch = oklab_to_oklch(rgb_to_oklab(0, 0, 255));
+        assert_close(oklch.c, 0.31321, 0.0001);
+        assert_close(oklch.h, 264.052, 0.01);
+
+        // Test case 3: Gray has no chroma, and a hue of 0
+        let oklch = oklab_to_oklch(rgb_to_oklab(128, 128, 128));
+        assert_close(oklch.c, 0.0, 0.0001);
+        assert_close(oklch.h, 0.0, 1e-9);
+    }
+
+    #[test]
+    fn test_relative_luminance() {
+        assert_close(relative_luminance(0, 0, 0), 0.0, 1e-9);
+        assert_close(relative_luminance(255, 255, 255), 1.0, 1e-9);
+        assert_close(relative_luminance(255, 0, 0), 0.2126, 1e-9);
+        assert_close(relative_luminance(128, 128, 128), 0.2159, 0.0001);
+    }
+
+    #[test]
+    fn test_linearize() {
+        // Test case 1: Black and white are unchanged, and mid-gray is much darker in linear light
+        assert_eq!(linearize([0, 0, 0]), [0, 0, 0]);
+        assert_eq!(linearize([255, 255, 255]), [255, 255, 255]);
+        assert_eq!(linearize([128, 188, 255]), [55, 128, 255]);
+
+        // Test case 2: Half of linear light is much lighter than mid-gray in sRGB
+        assert_eq!(delinearize([128, 128, 128]), [188, 188, 188]);
+
+        // Test case 3: Lighter colors round-trip exactly, and darker ones nearly
+        for c in 0..=255u8 {
+            let [round_trip, _, _] = delinearize(linearize([c, c, c]));
+            let tolerance = if c >= 124 { 0 } else { 6 };
+            assert!(
+                round_trip.abs_diff(c) <= tolerance,
+                "{c} became {round_trip}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert_close(contrast_ratio([0, 0, 0], [255, 255, 255]), 21.0, 1e-9);
+        assert_close(contrast_ratio([255, 255, 255], [0, 0, 0]), 21.0, 1e-9);
+        assert_close(contrast_ratio([80, 80, 80], [80, 80, 80]), 1.0, 1e-9);
+        assert_close(contrast_ratio([118, 118, 118], [255, 255, 255]), 4.54, 0.01);
+    }
+
+    #[test]
+    fn test_simulate_cvd() {
+        let red = Color::new(255, 0, 0, 255);
+
+        // Test case 1: Under protanopia red darkens to an olive brown, with red and green
+        // indistinguishable
+        let simulated = simulate_cvd(&red, ColorVisionDeficiency::Protanopia);
+        assert!(simulated.r < 160, "{}", simulated.r);
+        assert!((simulated.r as i16 - simulated.g as i16).abs() <= 8);
+        assert!(simulated.b < 20);
+        assert_eq!(simulated.a, 255);
+
+        // Test case 2: Under deuteranopia red stays brighter than under protanopia
+        let deuteranopia = simulate_cvd(&red, ColorVisionDeficiency::Deuteranopia);
+        assert!(deuteranopia.r > simulated.r);
+
+        // Test case 3: Red is one of the colors tritanopia leaves alone
+        let tritanopia = simulate_cvd(&red, ColorVisionDeficiency::Tritanopia);
+        assert_eq!([tritanopia.r, tritanopia.g, tritanopia.b], [255, 0, 0]);
+
+        // Test case 4: Neutral colors are unaffected
+        for kind in [
+            ColorVisionDeficiency::Protanopia,
+            ColorVisionDeficiency::Deuteranopia,
+            ColorVisionDeficiency::Tritanopia,
+        ] {
+            let gray = simulate_cvd(&Color::new(128, 128, 128, 255), kind);
+            for channel in [gray.r, gray.g, gray.b] {
+                assert!((channel as i16 - 128).abs() <= 2, "{kind}: {channel}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_rgb_to_cct() {
+        // Test case 1: sRGB white is lit by D65, at about 6500K
+        assert_close(rgb_to_cct(255, 255, 255).unwrap(), 6504.0, 10.0);
+
+        // Test case 2: A warm, reddish color is low, and a cool, bluish one high
+        let warm = rgb_to_cct(255, 170, 100).unwrap();
+        let cool = rgb_to_cct(190, 210, 255).unwrap();
+        assert!(warm < 3500.0, "{warm}");
+        assert!(cool > 9000.0, "{cool}");
+
+        // Test case 3: Black has no temperature
+        assert!(rgb_to_cct(0, 0, 0).is_none());
+    }
+
+    #[test]
+    fn test_nearest_named_color() {
+        // Test case 1: Exact matches
+        assert_eq!(nearest_named_color(255, 0, 0), "red");
+        assert_eq!(nearest_named_color(178, 34, 34), "firebrick");
+        assert_eq!(nearest_named_color(0, 255, 255), "aqua");
+        assert_eq!(nearest_named_color(128, 128, 128), "gray");
+
+        // Test case 2: Approximate matches
+        assert_eq!(nearest_named_color(250, 5, 5), "red");
+        assert_eq!(nearest_named_color(180, 30, 40), "firebrick");
+        assert_eq!(nearest_named_color(1, 1, 130), "navy");
+        assert_eq!(nearest_named_color(254, 254, 254), "white");
+    }
+}
@@ -0,0 +1,2 @@
+pub mod color_conversion;
+pub mod retry;
@@ -0,0 +1,116 @@
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::warn;
+
+/**
+ * How long to wait before the first retry. Each subsequent retry waits twice as long.
+ */
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/**
+ * Whether an error looks like a transient IO failure (e.g. a timeout on a network mount) that
+ * might succeed if tried again. Anything else, like a missing file or a corrupt image, is not.
+ */
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        let io_error = cause.downcast_ref::<io::Error>().or_else(|| {
+            match cause.downcast_ref::<image::ImageError>() {
+                Some(image::ImageError::IoError(e)) => Some(e),
+                _ => None,
+            }
+        });
+
+        io_error.is_some_and(|e| {
+            matches!(
+                e.kind(),
+                io::ErrorKind::Interrupted
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::WouldBlock
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::NotConnected
+                    | io::ErrorKind::BrokenPipe
+            )
+        })
+    })
+}
+
+/**
+ * Runs a fallible operation, retrying it up to `retries` times with an exponential backoff while
+ * it fails with a transient error. Each retry is logged as a warning.
+ *
+ * [u32] The maximum number of retries (0 runs the operation once).
+ * [&Path] The file being processed, used when logging.
+ * [FnMut() -> Result<T>] The operation.
+ */
+pub fn retry<T>(retries: u32, file: &Path, mut operation: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+
+    loop {
+        match operation() {
+            Err(e) if attempt < retries && is_transient(&e) => {
+                attempt += 1;
+                warn!(
+                    "{}: {e:#}; retrying in {backoff:.2?} ({attempt}/{retries})",
+                    file.display()
+                );
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    /// An operation that fails with the given error kind the first `failures` times it's called.
+    fn flaky(failures: u32, kind: io::ErrorKind) -> impl FnMut() -> Result<u32> {
+        let mut calls = 0;
+        move || {
+            calls += 1;
+            if calls <= failures {
+                Err(io::Error::from(kind)).context("Failed to read")
+            } else {
+                Ok(calls)
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let timeout = anyhow::Error::from(io::Error::from(io::ErrorKind::TimedOut));
+        assert!(is_transient(&timeout.context("Failed to save")));
+
+        let not_found = anyhow::Error::from(io::Error::from(io::ErrorKind::NotFound));
+        assert!(!is_transient(&not_found));
+
+        let image_timeout = image::ImageError::IoError(io::Error::from(io::ErrorKind::TimedOut));
+        assert!(is_transient(&anyhow::Error::from(image_timeout)));
+
+        assert!(!is_transient(&anyhow::anyhow!("Corrupt image")));
+    }
+
+    #[test]
+    fn test_retry_recovers_from_a_transient_failure() {
+        let file = Path::new("flaky.png");
+
+        // Test case 1: A single failure is retried, and the second attempt succeeds
+        let result = retry(2, file, flaky(1, io::ErrorKind::TimedOut));
+        assert_eq!(result.unwrap(), 2);
+
+        // Test case 2: Without retries, the failure is returned
+        assert!(retry(0, file, flaky(1, io::ErrorKind::TimedOut)).is_err());
+
+        // Test case 3: Non-transient failures aren't retried
+        assert!(retry(2, file, flaky(1, io::ErrorKind::NotFound)).is_err());
+    }
+}
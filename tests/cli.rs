@@ -0,0 +1,1718 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+use image::{Rgb, RgbImage, Rgba, RgbaImage};
+use tempfile::TempDir;
+
+fn colorbuddy(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_colorbuddy"))
+        .args(args)
+        .output()
+        .expect("failed to run colorbuddy")
+}
+
+fn write_solid_image(path: &Path, color: [u8; 3]) {
+    RgbImage::from_pixel(16, 16, Rgb(color))
+        .save(path)
+        .expect("failed to write test image");
+}
+
+#[test]
+fn test_summary_reports_successes_and_failures() {
+    let dir = TempDir::new().unwrap();
+    let valid = dir.path().join("red.png");
+    let invalid = dir.path().join("missing.png");
+    write_solid_image(&valid, [255, 0, 0]);
+
+    let output = colorbuddy(&[
+        "--summary",
+        "-t",
+        "json",
+        "-n",
+        "1",
+        valid.to_str().unwrap(),
+        invalid.to_str().unwrap(),
+    ]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    let valid_line = stderr
+        .lines()
+        .find(|l| l.starts_with(valid.to_str().unwrap()))
+        .expect("summary line for the valid image");
+    assert!(valid_line.contains("#ff0000"));
+    assert!(valid_line.ends_with("OK"));
+
+    let invalid_line = stderr
+        .lines()
+        .find(|l| l.starts_with(invalid.to_str().unwrap()))
+        .expect("summary line for the invalid image");
+    assert!(invalid_line.contains("Failed: Error opening image"));
+
+    assert!(stderr.contains("2 processed, 1 succeeded, 1 failed"));
+}
+
+#[test]
+fn test_verbose_logs_timings_to_stderr() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+
+    // Test case 1: -v logs stage timings without touching the JSON on stdout
+    let output = colorbuddy(&["-v", "-t", "json", "-n", "1", input.to_str().unwrap()]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("loaded in"));
+    assert!(stderr.contains("extracted 1 colors using k-means in"));
+    assert!(stderr.contains("wrote json output in"));
+    assert!(!stderr.contains("dimensions"));
+    assert!(String::from_utf8(output.stdout).unwrap().starts_with('{'));
+
+    // Test case 2: -vv also logs the image dimensions and output path
+    let output = colorbuddy(&["-vv", "-t", "json-file", "-n", "1", input.to_str().unwrap()]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("dimensions 16x16"));
+    assert!(stderr.contains("red_palette.json"));
+
+    // Test case 3: Without -v nothing is logged
+    let output = colorbuddy(&["-t", "json", "-n", "1", input.to_str().unwrap()]);
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_text_output_goes_to_stdout_without_an_output_path() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+
+    let output = colorbuddy(&["-t", "text", "-n", "1", input.to_str().unwrap()]);
+
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "#ff0000\n");
+    assert!(!dir.path().join("red_palette.txt").exists());
+}
+
+#[test]
+fn test_combine_extracts_one_palette_from_every_image() {
+    let dir = TempDir::new().unwrap();
+    let red = dir.path().join("red.png");
+    let blue = dir.path().join("blue.png");
+    let output = dir.path().join("brand.json");
+    write_solid_image(&red, [255, 0, 0]);
+    write_solid_image(&blue, [0, 0, 255]);
+
+    let result = colorbuddy(&[
+        "--combine",
+        "-n",
+        "2",
+        "-t",
+        "json-file",
+        "-o",
+        output.to_str().unwrap(),
+        red.to_str().unwrap(),
+        blue.to_str().unwrap(),
+    ]);
+    assert!(result.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    let hexes: Vec<&str> = json
+        .as_object()
+        .unwrap()
+        .iter()
+        .filter(|(key, _)| key.starts_with("color_"))
+        .map(|(_, c)| c["hex"].as_str().unwrap())
+        .collect();
+    assert_eq!(hexes.len(), 2);
+    assert!(hexes.contains(&"#ff0000"));
+    assert!(hexes.contains(&"#0000ff"));
+
+    // Only the combined output is written; no per-image outputs
+    assert!(!dir.path().join("red_palette.json").exists());
+}
+
+#[test]
+fn test_sort_order_is_shared_by_json_and_standalone_outputs() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("stripes.png");
+    let stripes = [
+        [20, 40, 200],
+        [240, 240, 240],
+        [200, 30, 30],
+        [250, 220, 40],
+    ];
+    RgbImage::from_fn(40, 10, |x, _| Rgb(stripes[(x / 10) as usize]))
+        .save(&input)
+        .unwrap();
+
+    for output_type in ["json-file", "standalone-palette"] {
+        let status = colorbuddy(&[
+            "-m",
+            "median-cut",
+            "-n",
+            "4",
+            "--sort",
+            "luminance",
+            "--reverse",
+            "-t",
+            output_type,
+            "-o",
+            dir.path().to_str().unwrap(),
+            input.to_str().unwrap(),
+        ])
+        .status;
+        assert!(status.success());
+    }
+
+    let json = std::fs::read_to_string(dir.path().join("stripes_palette.json")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let standalone = image::open(dir.path().join("stripes_palette.png"))
+        .unwrap()
+        .to_rgb8();
+
+    // The lightest color comes first in both
+    assert_eq!(json["color_1"]["hex"], "#f0f0f0");
+    let first = ["r", "g", "b"].map(|c| json["color_1"][c].as_u64().unwrap() as u8);
+    assert_eq!(first, standalone.get_pixel(0, 0).0);
+}
+
+#[test]
+fn test_json_error_format() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing.png");
+
+    let output = colorbuddy(&[
+        "--error-format",
+        "json",
+        "-t",
+        "json",
+        missing.to_str().unwrap(),
+    ]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    let error: serde_json::Value = serde_json::from_str(stderr.lines().next().unwrap()).unwrap();
+    assert_eq!(error["file"], missing.to_str().unwrap());
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Error opening image"));
+    assert!(!output.status.success());
+
+    // Test case 2: Turning logging off doesn't hide the errors
+    let output = Command::new(env!("CARGO_BIN_EXE_colorbuddy"))
+        .args([
+            "--error-format",
+            "json",
+            "-t",
+            "json",
+            missing.to_str().unwrap(),
+        ])
+        .env("RUST_LOG", "off")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let error: serde_json::Value = serde_json::from_str(stderr.lines().next().unwrap()).unwrap();
+    assert_eq!(error["file"], missing.to_str().unwrap());
+}
+
+#[test]
+fn test_exit_status_reflects_failures() {
+    let dir = TempDir::new().unwrap();
+    let red = dir.path().join("red.png");
+    let blue = dir.path().join("blue.png");
+    let missing = dir.path().join("missing.png");
+    write_solid_image(&red, [255, 0, 0]);
+    write_solid_image(&blue, [0, 0, 255]);
+    let run = |extra: &[&str], images: &[&Path]| {
+        let mut args = vec!["-n", "1", "-t", "json-file"];
+        args.extend_from_slice(extra);
+        args.extend(images.iter().map(|i| i.to_str().unwrap()));
+        colorbuddy(&args).status
+    };
+
+    // Test case 1: Every image succeeds
+    assert_eq!(run(&[], &[&red, &blue]).code(), Some(0));
+
+    // Test case 2: A failure doesn't stop the rest from being processed, but is reported
+    assert_eq!(run(&[], &[&missing, &red]).code(), Some(1));
+    assert!(dir.path().join("red_palette.json").exists());
+
+    // Test case 3: --strict stops at the first failure
+    std::fs::remove_file(dir.path().join("blue_palette.json")).unwrap();
+    assert_eq!(run(&["--strict"], &[&missing, &blue]).code(), Some(1));
+    assert!(!dir.path().join("blue_palette.json").exists());
+}
+
+#[test]
+fn test_image_output_to_stdout() {
+    let dir = TempDir::new().unwrap();
+    let red = dir.path().join("red.png");
+    let blue = dir.path().join("blue.png");
+    write_solid_image(&red, [255, 0, 0]);
+    write_solid_image(&blue, [0, 0, 255]);
+
+    // Test case 1: The PNG is written to stdout, and not to disk
+    let output = colorbuddy(&[
+        "--stdout",
+        "-t",
+        "standalone-palette",
+        "-n",
+        "1",
+        "-p",
+        "10",
+        red.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let palette = image::load_from_memory_with_format(&output.stdout, image::ImageFormat::Png)
+        .unwrap()
+        .to_rgb8();
+    assert_eq!(palette.dimensions(), (16, 10));
+    assert_eq!(palette.get_pixel(0, 0).0, [255, 0, 0]);
+    assert!(!dir.path().join("red_palette.png").exists());
+
+    // Test case 2: Multiple images can't all go to stdout
+    let output = colorbuddy(&["--stdout", red.to_str().unwrap(), blue.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_max_dimension_rejects_large_images() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+
+    // Test case 1: Larger than the limit
+    let output = colorbuddy(&[
+        "--max-dimension",
+        "8",
+        "-t",
+        "json",
+        input.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Image is too large: 16x16"));
+
+    // Test case 2: The check can be disabled
+    let output = colorbuddy(&[
+        "--max-dimension",
+        "0",
+        "-t",
+        "json",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_ico_and_tga_inputs() {
+    let dir = TempDir::new().unwrap();
+
+    // Test case 1: Favicons and TGAs are read like any other image. Icons are written with an
+    // alpha channel, as the ICO decoder only accepts RGBA PNG icons.
+    for name in ["favicon.ico", "texture.tga"] {
+        let input = dir.path().join(name);
+        RgbaImage::from_pixel(16, 16, Rgba([0, 0, 255, 255]))
+            .save(&input)
+            .unwrap();
+
+        let output = colorbuddy(&["-t", "text", "-n", "1", input.to_str().unwrap()]);
+        assert!(output.status.success(), "{name}");
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "#0000ff\n");
+    }
+
+    // Test case 2: A format that can't be read is named
+    let input = dir.path().join("drawing.xcf");
+    std::fs::write(&input, b"gimp xcf v011").unwrap();
+    let output = colorbuddy(&["-t", "text", input.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Unsupported image format: .xcf"));
+}
+
+#[test]
+fn test_duplicate_inputs_are_processed_once() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+    let same = dir.path().join(".").join("red.png");
+
+    let output = colorbuddy(&[
+        "-v",
+        "--summary",
+        "-t",
+        "json",
+        "-n",
+        "1",
+        input.to_str().unwrap(),
+        same.to_str().unwrap(),
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("color_1").count(), 1);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("1 processed, 1 succeeded, 0 failed"));
+    assert_eq!(
+        stderr.matches("skipped, as it was already given").count(),
+        2
+    );
+}
+
+#[test]
+fn test_json_and_image_outputs_can_be_sorted_differently() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("stripes.png");
+    let stripes = [
+        [20, 40, 200],
+        [240, 240, 240],
+        [200, 30, 30],
+        [250, 220, 40],
+    ];
+    RgbImage::from_fn(40, 10, |x, _| Rgb(stripes[(x / 10) as usize]))
+        .save(&input)
+        .unwrap();
+
+    for output_type in ["json-file", "standalone-palette"] {
+        let status = colorbuddy(&[
+            "-m",
+            "median-cut",
+            "-n",
+            "4",
+            "--sort",
+            "luminance",
+            "--sort-image",
+            "hue",
+            "-t",
+            output_type,
+            "-o",
+            dir.path().to_str().unwrap(),
+            input.to_str().unwrap(),
+        ])
+        .status;
+        assert!(status.success());
+    }
+
+    let json = std::fs::read_to_string(dir.path().join("stripes_palette.json")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let standalone = image::open(dir.path().join("stripes_palette.png"))
+        .unwrap()
+        .to_rgb8();
+
+    // Test case 1: JSON falls back to --sort, so the darkest color comes first
+    assert_eq!(json["color_1"]["hex"], "#1428c8");
+    assert_eq!(json["color_4"]["hex"], "#f0f0f0");
+
+    // Test case 2: The swatches are in hue order, so blue is last
+    assert_ne!(standalone.get_pixel(0, 0).0, [20, 40, 200]);
+    assert_eq!(standalone.get_pixel(39, 0).0, [20, 40, 200]);
+}
+
+#[test]
+fn test_no_clobber_keeps_existing_outputs() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    let output = dir.path().join("red_palette.json");
+    write_solid_image(&input, [255, 0, 0]);
+    std::fs::write(&output, "existing").unwrap();
+    let run = |extra: &[&str]| {
+        let mut args = vec!["-n", "1", "-t", "json-file"];
+        args.extend_from_slice(extra);
+        args.push(input.to_str().unwrap());
+        colorbuddy(&args)
+    };
+
+    // Test case 1: --no-clobber leaves the file alone, and says so
+    let result = run(&["--no-clobber"]);
+    assert!(result.status.success());
+    assert!(String::from_utf8(result.stderr)
+        .unwrap()
+        .contains("already exists"));
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "existing");
+
+    // Test case 2: The last of --no-clobber and --overwrite wins
+    run(&["--overwrite", "--no-clobber"]);
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "existing");
+    run(&["--no-clobber", "--overwrite"]);
+    assert!(std::fs::read_to_string(&output)
+        .unwrap()
+        .contains("#ff0000"));
+}
+
+#[test]
+fn test_unique_names_keeps_same_named_inputs_apart() {
+    let dir = TempDir::new().unwrap();
+    let out = dir.path().join("out");
+    std::fs::create_dir_all(dir.path().join("a")).unwrap();
+    std::fs::create_dir_all(dir.path().join("b")).unwrap();
+    std::fs::create_dir_all(&out).unwrap();
+    let red = dir.path().join("a").join("photo.png");
+    let blue = dir.path().join("b").join("photo.png");
+    write_solid_image(&red, [255, 0, 0]);
+    write_solid_image(&blue, [0, 0, 255]);
+
+    let status = colorbuddy(&[
+        "--unique-names",
+        "-n",
+        "1",
+        "-t",
+        "json-file",
+        "-o",
+        out.to_str().unwrap(),
+        red.to_str().unwrap(),
+        blue.to_str().unwrap(),
+    ])
+    .status;
+    assert!(status.success());
+
+    let first = std::fs::read_to_string(out.join("photo_palette.json")).unwrap();
+    let second = std::fs::read_to_string(out.join("photo_palette_1.json")).unwrap();
+    assert!(first.contains("#ff0000"));
+    assert!(second.contains("#0000ff"));
+}
+
+#[test]
+fn test_pad_to_count_fills_short_palettes() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("two.png");
+    RgbImage::from_fn(16, 16, |x, _| {
+        if x < 8 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    let run = |extra: &[&str]| {
+        let mut args = vec!["--json-flat", "-t", "json", "-m", "median-cut", "-n", "4"];
+        args.extend_from_slice(extra);
+        args.push(input.to_str().unwrap());
+        let output = colorbuddy(&args);
+        assert!(output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json.as_array().unwrap().len()
+    };
+
+    // Test case 1: Only the two colors in the image come out without padding
+    assert_eq!(run(&[]), 2);
+
+    // Test case 2: Padded up to the requested count, by either strategy
+    assert_eq!(run(&["--pad-to-count"]), 4);
+    assert_eq!(run(&["--pad-to-count=repeat"]), 4);
+}
+
+/**
+ * Serves `png` at any path ending in `.png`, ignoring the query string (and 404s everything else)
+ * on a local port, for `requests` requests. Returns the base URL.
+ */
+fn serve_png(png: Vec<u8>, requests: usize) -> String {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // Read the rest of the headers before responding
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap() > 2 {
+                header.clear();
+            }
+
+            let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+            if path.split('?').next().unwrap().ends_with(".png") {
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    png.len()
+                )
+                .unwrap();
+                stream.write_all(&png).unwrap();
+            } else {
+                write!(
+                    stream,
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+        }
+    });
+    base_url
+}
+
+#[test]
+fn test_extracts_palette_from_url() {
+    let dir = TempDir::new().unwrap();
+    let mut png = Vec::new();
+    RgbImage::from_pixel(16, 16, Rgb([255, 0, 0]))
+        .write_to(
+            &mut std::io::Cursor::new(&mut png),
+            image::ImageOutputFormat::Png,
+        )
+        .unwrap();
+    let base_url = serve_png(png, 2);
+
+    // Test case 1: The image is fetched, and the output is named after the URL's path
+    let output = colorbuddy(&[
+        "-n",
+        "1",
+        "-t",
+        "json-file",
+        "--timeout",
+        "5",
+        "-o",
+        dir.path().to_str().unwrap(),
+        &format!("{base_url}/images/red.png?size=16"),
+    ]);
+    assert!(output.status.success());
+    let json = std::fs::read_to_string(dir.path().join("red_palette.json")).unwrap();
+    assert!(json.contains("#ff0000"));
+
+    // Test case 2: Anything but 200 OK is an error naming the status
+    let output = colorbuddy(&["-n", "1", "-t", "json", &format!("{base_url}/missing")]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("the server responded with HTTP 404"));
+}
+
+#[test]
+fn test_posterize_remaps_the_image_to_its_palette() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("gradient.png");
+    RgbImage::from_fn(40, 30, |x, y| Rgb([x as u8 * 6, y as u8 * 8, 100]))
+        .save(&input)
+        .unwrap();
+
+    let output = colorbuddy(&["-t", "posterize", "-n", "4", input.to_str().unwrap()]);
+    assert!(output.status.success());
+
+    let posterized = image::open(dir.path().join("gradient_posterized.png"))
+        .unwrap()
+        .to_rgb8();
+    assert_eq!(posterized.dimensions(), (40, 30));
+
+    let json = colorbuddy(&[
+        "-t",
+        "json",
+        "-n",
+        "4",
+        "--json-flat",
+        input.to_str().unwrap(),
+    ]);
+    let palette: Vec<serde_json::Value> = serde_json::from_slice(&json.stdout).unwrap();
+    let palette: Vec<&str> = palette.iter().map(|c| c["hex"].as_str().unwrap()).collect();
+    for pixel in posterized.pixels() {
+        let hex = format!("#{:02x}{:02x}{:02x}", pixel[0], pixel[1], pixel[2]);
+        assert!(
+            palette.contains(&hex.as_str()),
+            "{hex} isn't in {palette:?}"
+        );
+    }
+}
+
+#[test]
+fn test_embed_metadata_in_png_outputs() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+
+    // Test case 1: The palette is embedded in a tEXt chunk of the PNG
+    let output = colorbuddy(&[
+        "--embed-metadata",
+        "-t",
+        "standalone-palette",
+        "-n",
+        "1",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let file = std::fs::File::open(dir.path().join("red_palette.png")).unwrap();
+    let reader = png::Decoder::new(file).read_info().unwrap();
+    let palette = reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .find(|chunk| chunk.keyword == "colorbuddy:palette")
+        .expect("palette tEXt chunk");
+    assert_eq!(palette.text, "#ff0000");
+
+    // Test case 2: A clut keeps its PNG palette alongside the embedded one
+    let output = colorbuddy(&[
+        "--embed-metadata",
+        "-t",
+        "clut",
+        "-n",
+        "1",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("--embed-metadata"));
+    let file = std::fs::File::open(dir.path().join("red_clut.png")).unwrap();
+    let reader = png::Decoder::new(file).read_info().unwrap();
+    assert_eq!(reader.info().palette.as_deref(), Some(&[255, 0, 0][..]));
+    assert!(reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .any(|chunk| chunk.keyword == "colorbuddy:palette" && chunk.text == "#ff0000"));
+
+    // Test case 3: Other outputs are left alone, with a warning
+    let output = colorbuddy(&[
+        "--embed-metadata",
+        "-t",
+        "json",
+        "-n",
+        "1",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("--embed-metadata only applies to PNG image files"));
+}
+
+#[test]
+fn test_proportional_swatches() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("mostly_red.png");
+    RgbImage::from_fn(100, 10, |x, _| {
+        if x < 70 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    let output = colorbuddy(&[
+        "--proportional",
+        "-t",
+        "standalone-palette",
+        "-n",
+        "2",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    let palette = image::open(dir.path().join("mostly_red_palette.png"))
+        .unwrap()
+        .to_rgb8();
+    assert_eq!(palette.width(), 100);
+    let red = (0..100)
+        .filter(|&x| palette.get_pixel(x, 0).0 == [255, 0, 0])
+        .count();
+    let blue = (0..100)
+        .filter(|&x| palette.get_pixel(x, 0).0 == [0, 0, 255])
+        .count();
+    assert!((69..=71).contains(&red), "red spans {red}px");
+    assert_eq!(red + blue, 100);
+}
+
+#[test]
+fn test_output_type_inferred_from_output_extension() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    let out = dir.path().join("out");
+    std::fs::create_dir(&out).unwrap();
+    write_solid_image(&input, [255, 0, 0]);
+
+    // Test case 1: A .json output is a JSON file
+    let status = colorbuddy(&[
+        "-n",
+        "1",
+        "-o",
+        out.join("palette.json").to_str().unwrap(),
+        input.to_str().unwrap(),
+    ])
+    .status;
+    assert!(status.success());
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join("red_palette.json")).unwrap())
+            .unwrap();
+    assert_eq!(json["color_1"]["hex"], "#ff0000");
+
+    // Test case 2: A .png output is an image
+    let status = colorbuddy(&[
+        "-n",
+        "1",
+        "-o",
+        out.join("palette.png").to_str().unwrap(),
+        input.to_str().unwrap(),
+    ])
+    .status;
+    assert!(status.success());
+    assert!(image::open(out.join("red_palette.png")).is_ok());
+
+    // Test case 3: An explicit --output-type wins
+    let status = colorbuddy(&[
+        "-n",
+        "1",
+        "-t",
+        "svg",
+        "-o",
+        out.join("palette.json").to_str().unwrap(),
+        input.to_str().unwrap(),
+    ])
+    .status;
+    assert!(status.success());
+    assert!(out.join("red_palette.svg").is_file());
+}
+
+#[test]
+fn test_kmeans_iterations_uses_parallel_kmeans() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("two.png");
+    RgbImage::from_fn(16, 16, |x, _| {
+        if x < 8 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    let run = |iterations: &str| {
+        colorbuddy(&[
+            "--json-flat",
+            "-t",
+            "json",
+            "-n",
+            "2",
+            iterations,
+            input.to_str().unwrap(),
+        ])
+    };
+
+    // Test case 1: Both flag forms find exactly the two colors in the image
+    for iterations in ["--kmeans-iterations", "--kmeans-iterations=4"] {
+        let output = run(iterations);
+        assert!(output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let mut hexes: Vec<&str> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["hex"].as_str().unwrap())
+            .collect();
+        hexes.sort();
+        assert_eq!(hexes, ["#0000ff", "#ff0000"]);
+    }
+
+    // Test case 2: At least one pass is needed
+    assert!(!run("--kmeans-iterations=0").status.success());
+}
+
+#[test]
+fn test_seed_palette() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("two.png");
+    RgbImage::from_fn(16, 16, |x, _| {
+        if x < 8 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    let run = |seeds: &str| {
+        colorbuddy(&[
+            "--json-flat",
+            "-t",
+            "json",
+            "-n",
+            "2",
+            "--seed-palette",
+            seeds,
+            input.to_str().unwrap(),
+        ])
+    };
+
+    // Test case 1: Seeded with the image's colors, the palette is exactly those colors
+    let output = run("#0000ff,#ff0000");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut hexes: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["hex"].as_str().unwrap())
+        .collect();
+    hexes.sort();
+    assert_eq!(hexes, ["#0000ff", "#ff0000"]);
+
+    // Test case 2: More seeds than colors, or a seed that isn't a color, are rejected
+    assert!(!run("#0000ff,#ff0000,#00ff00").status.success());
+    assert!(!run("#0000ff,blue").status.success());
+}
+
+#[test]
+fn test_clut_output_has_one_pixel_per_color() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("two.png");
+    RgbImage::from_fn(16, 16, |x, _| {
+        if x < 8 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    let run = |extra: &[&str]| {
+        let mut args = vec!["-t", "clut", "-m", "median-cut", "-n", "2"];
+        args.extend_from_slice(extra);
+        args.push(input.to_str().unwrap());
+        assert!(colorbuddy(&args).status.success());
+        image::open(dir.path().join("two_clut.png"))
+            .unwrap()
+            .to_rgb8()
+    };
+
+    // Test case 1: One pixel per color, left to right
+    let clut = run(&[]);
+    assert_eq!(clut.dimensions(), (2, 1));
+    let mut pixels: Vec<[u8; 3]> = clut.pixels().map(|p| p.0).collect();
+    pixels.sort();
+    assert_eq!(pixels, [[0, 0, 255], [255, 0, 0]]);
+
+    // Test case 2: Scaled up and vertical
+    let clut = run(&["--clut-scale", "3", "--orientation", "vertical"]);
+    assert_eq!(clut.dimensions(), (3, 6));
+}
+
+#[test]
+fn test_strict_count_rejects_short_palettes() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("gray.png");
+    write_solid_image(&input, [128, 128, 128]);
+
+    let run = |extra: &[&str]| {
+        let mut args = vec!["-t", "json", "-m", "median-cut", "-n", "4"];
+        args.extend_from_slice(extra);
+        args.push(input.to_str().unwrap());
+        colorbuddy(&args)
+    };
+
+    // Test case 1: Without it, the single color comes out on its own
+    assert!(run(&[]).status.success());
+
+    // Test case 2: With it, the image fails, saying how many colors came out
+    let output = run(&["--strict-count"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("extracted 1 of the 4 requested"),
+        "{stderr}"
+    );
+
+    // Test case 3: It can't be combined with padding
+    assert!(!run(&["--strict-count", "--pad-to-count"]).status.success());
+}
+
+#[test]
+fn test_temperature_in_json() {
+    let dir = TempDir::new().unwrap();
+    let warm = dir.path().join("warm.png");
+    let cool = dir.path().join("cool.png");
+    write_solid_image(&warm, [255, 170, 100]);
+    write_solid_image(&cool, [190, 210, 255]);
+
+    let temperature = |input: &Path, extra: &[&str]| {
+        let mut args = vec!["-t", "json", "-n", "1"];
+        args.extend_from_slice(extra);
+        args.push(input.to_str().unwrap());
+        let output = colorbuddy(&args);
+        assert!(output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["color_temperature_k"].as_u64()
+    };
+
+    // Test case 1: A warm image is low, and a cool one high
+    assert!(temperature(&warm, &["--temperature"]).unwrap() < 3500);
+    assert!(temperature(&cool, &["--temperature"]).unwrap() > 9000);
+
+    // Test case 2: Left out unless asked for
+    assert!(temperature(&warm, &[]).is_none());
+}
+
+#[test]
+fn test_output_template_names_outputs() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+
+    // Test case 1: The placeholders are filled in for the image
+    let output = colorbuddy(&[
+        "-t",
+        "json-file",
+        "-m",
+        "median-cut",
+        "-n",
+        "3",
+        "--output-template",
+        "{stem}-{method}-{count}.{ext}",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert!(dir.path().join("red-median-cut-3.json").exists());
+
+    // Test case 2: Unknown placeholders are rejected up front
+    let output = colorbuddy(&[
+        "--output-template",
+        "{stem}-{size}.{ext}",
+        input.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown placeholder {size}"), "{stderr}");
+}
+
+#[cfg(not(feature = "pdf"))]
+#[test]
+fn test_pdf_input_needs_the_pdf_feature() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("brand.pdf");
+    std::fs::write(&input, b"%PDF-1.4\n%%EOF\n").unwrap();
+
+    let output = colorbuddy(&["-t", "json", input.to_str().unwrap()]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--features pdf"), "{stderr}");
+}
+
+#[test]
+fn test_dominant_color_in_json() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("meadow.png");
+    // Mostly green, with a red stripe along the top
+    RgbImage::from_fn(20, 20, |_, y| {
+        if y < 4 {
+            Rgb([220, 30, 30])
+        } else {
+            Rgb([40, 180, 60])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    let output = colorbuddy(&["-t", "json", "-n", "2", input.to_str().unwrap()]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let dominant = &json["dominant_color"];
+    let channel = |c: &str| dominant[c].as_u64().unwrap();
+
+    assert!(
+        channel("g") > channel("r") && channel("g") > channel("b"),
+        "{dominant}"
+    );
+    assert!(dominant["hex"].as_str().unwrap().starts_with('#'));
+}
+
+#[test]
+fn test_grayscale_and_indexed_sources() {
+    let dir = TempDir::new().unwrap();
+    let colors_of = |json: &serde_json::Value| {
+        let mut colors: Vec<[u64; 3]> = (1..=2)
+            .map(|i| {
+                let color = &json[format!("color_{i}")];
+                ["r", "g", "b"].map(|c| color[c].as_u64().unwrap())
+            })
+            .collect();
+        colors.sort();
+        colors
+    };
+    let run = |input: &Path| {
+        let output = colorbuddy(&[
+            "-t",
+            "json",
+            "-m",
+            "median-cut",
+            "-n",
+            "2",
+            input.to_str().unwrap(),
+        ]);
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    // Test case 1: A grayscale PNG gives a gray palette, and is recorded as 8-bit luminance
+    let gray = dir.path().join("gray.png");
+    image::GrayImage::from_fn(16, 16, |x, _| image::Luma([if x < 8 { 40 } else { 200 }]))
+        .save(&gray)
+        .unwrap();
+    let json = run(&gray);
+    assert_eq!(json["source_color_type"], "L8");
+    assert_eq!(colors_of(&json), [[40, 40, 40], [200, 200, 200]]);
+
+    // Test case 2: An indexed PNG gives the colors of its own palette, and is recorded as the RGB
+    // it's expanded to when decoded
+    let indexed = dir.path().join("indexed.png");
+    let mut encoder = png::Encoder::new(std::fs::File::create(&indexed).unwrap(), 16, 16);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(vec![255, 0, 0, 0, 0, 255]);
+    let pixels: Vec<u8> = (0..256).map(|i| u8::from(i % 16 >= 8)).collect();
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&pixels).unwrap();
+    writer.finish().unwrap();
+    let json = run(&indexed);
+    assert_eq!(json["source_color_type"], "Rgb8");
+    assert_eq!(colors_of(&json), [[0, 0, 255], [255, 0, 0]]);
+}
+
+#[test]
+fn test_also_json_writes_a_sidecar() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("two.png");
+    RgbImage::from_fn(16, 16, |x, _| {
+        if x < 8 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    let output = colorbuddy(&[
+        "--also-json",
+        "-m",
+        "median-cut",
+        "-n",
+        "2",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    // Both the image and its JSON are written, and the JSON has every color
+    assert!(dir.path().join("two_palette.png").is_file());
+    let json: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(dir.path().join("two_palette.json")).unwrap())
+            .unwrap();
+    let colors = json
+        .as_object()
+        .unwrap()
+        .keys()
+        .filter(|key| key.starts_with("color_"))
+        .count();
+    assert_eq!(colors, 2);
+}
+
+#[test]
+fn test_include_color() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("reds.png");
+    RgbImage::from_fn(16, 16, |x, _| Rgb([255 - x as u8 * 12, 0, 0]))
+        .save(&input)
+        .unwrap();
+
+    let output = colorbuddy(&[
+        "--json-flat",
+        "-t",
+        "json",
+        "-m",
+        "median-cut",
+        "-n",
+        "4",
+        "--include-color",
+        "#0000ff",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let hexes: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["hex"].as_str().unwrap())
+        .collect();
+
+    // Blue displaces one of the reds, leaving four colors
+    assert_eq!(hexes.len(), 4);
+    assert!(hexes.contains(&"#0000ff"), "{hexes:?}");
+}
+
+#[test]
+fn test_bench_subcommand() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+
+    // Test case 1: The table has a line per method
+    let output = colorbuddy(&["bench", "-n", "2", input.to_str().unwrap()]);
+    assert!(output.status.success());
+    let table = String::from_utf8(output.stdout).unwrap();
+    for method in ["k-means", "median-cut", "uniform", "popularity"] {
+        assert!(table.contains(method), "{table}");
+    }
+
+    // Test case 2: The JSON has an entry per method
+    let output = colorbuddy(&["bench", "--json", input.to_str().unwrap()]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 4);
+
+    // Test case 3: The number of colors is checked like the main -n
+    for number_of_colors in ["0", "257"] {
+        let output = colorbuddy(&["bench", "-n", number_of_colors, input.to_str().unwrap()]);
+        assert!(!output.status.success(), "-n {number_of_colors}");
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("--number-of-colors"));
+    }
+}
+
+#[test]
+fn test_aspect_sizes_standalone_palettes() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+
+    let output = colorbuddy(&[
+        "-t",
+        "standalone-palette",
+        "-w",
+        "300",
+        "--aspect",
+        "3:1",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let palette = image::open(dir.path().join("red_palette.png")).unwrap();
+    assert_eq!((palette.width(), palette.height()), (300, 100));
+
+    assert!(!colorbuddy(&["--aspect", "3:0", input.to_str().unwrap()])
+        .status
+        .success());
+}
+
+#[test]
+fn test_cache_dir_reuses_palettes() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    let cache_dir = dir.path().join("cache");
+    write_solid_image(&input, [255, 0, 0]);
+
+    let run = |colors: &str| {
+        let output = colorbuddy(&[
+            "--json-flat",
+            "-t",
+            "json",
+            "-n",
+            colors,
+            "--cache-dir",
+            cache_dir.to_str().unwrap(),
+            input.to_str().unwrap(),
+        ]);
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()[0]["hex"]
+            .as_str()
+            .unwrap()
+            .to_owned()
+    };
+
+    // Test case 1: The first run extracts the palette, and caches it
+    assert_eq!(run("1"), "#ff0000");
+    let cached: Vec<_> = std::fs::read_dir(&cache_dir).unwrap().collect();
+    assert_eq!(cached.len(), 1);
+    let cache_file = cached[0].as_ref().unwrap().path();
+
+    // Test case 2: The second run reads the cache rather than extracting, as a doctored cache file
+    // shows
+    std::fs::write(&cache_file, "[[0, 255, 0, 255]]").unwrap();
+    assert_eq!(run("1"), "#00ff00");
+
+    // Test case 3: Different options miss the cache
+    assert_ne!(run("2"), "#00ff00");
+    assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 2);
+}
+
+#[test]
+fn test_layout_json() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("two.png");
+    RgbImage::from_fn(16, 16, |x, _| {
+        if x < 8 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    let output = colorbuddy(&[
+        "--layout-json",
+        "-m",
+        "median-cut",
+        "-n",
+        "2",
+        "-p",
+        "4",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    // The swatches sit in the strip under the image, side by side across its width
+    let layout: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(dir.path().join("two_palette.layout.json")).unwrap())
+            .unwrap();
+    assert_eq!(layout["width"], 16);
+    assert_eq!(layout["height"], 20);
+    let swatches = layout["swatches"].as_array().unwrap();
+    assert_eq!(swatches.len(), 2);
+    assert_eq!(swatches[0]["x"], 0);
+    assert_eq!(swatches[1]["x"], 8);
+    for swatch in swatches {
+        assert_eq!(swatch["y"], 16);
+        assert_eq!(swatch["width"], 8);
+        assert_eq!(swatch["height"], 4);
+    }
+}
+
+#[test]
+fn test_from_file() {
+    let dir = TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("images")).unwrap();
+    write_solid_image(&dir.path().join("images/red.png"), [255, 0, 0]);
+    write_solid_image(&dir.path().join("images/blue.png"), [0, 0, 255]);
+    let list = dir.path().join("list.txt");
+    std::fs::write(&list, "# To process\nimages/red.png\n\nimages/blue.png\n").unwrap();
+
+    // The paths are relative to the list, wherever colorbuddy is run from
+    let output = colorbuddy(&[
+        "--from-file",
+        list.to_str().unwrap(),
+        "-m",
+        "median-cut",
+        "-n",
+        "1",
+    ]);
+    assert!(output.status.success());
+    assert!(dir.path().join("images/red_palette.png").is_file());
+    assert!(dir.path().join("images/blue_palette.png").is_file());
+}
+
+#[test]
+fn test_transparent_swatches() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("faded.png");
+    image::RgbaImage::from_pixel(16, 16, image::Rgba([255, 0, 0, 64]))
+        .save(&input)
+        .unwrap();
+
+    let output = colorbuddy(&[
+        "--transparent-swatches",
+        "-t",
+        "standalone-palette",
+        "-m",
+        "median-cut",
+        "-n",
+        "1",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    // The swatch keeps the image's transparency
+    let palette = image::open(dir.path().join("faded_palette.png"))
+        .unwrap()
+        .to_rgba8();
+    assert_eq!(palette.get_pixel(0, 0).0, [255, 0, 0, 64]);
+}
+
+#[test]
+fn test_auto_method_is_recorded() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("sprite.png");
+    RgbImage::from_fn(12, 12, |x, _| match x / 4 {
+        0 => Rgb([20, 20, 60]),
+        1 => Rgb([230, 200, 40]),
+        _ => Rgb([200, 40, 40]),
+    })
+    .save(&input)
+    .unwrap();
+
+    let output = colorbuddy(&[
+        "-m",
+        "auto",
+        "-n",
+        "3",
+        "-t",
+        "json",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    // The JSON records the method picked for the image, not auto
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["quantisation_method"], "popularity");
+    assert_eq!(json["color_1"]["hex"], "#14143c");
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+    let cache = dir.path().join("cache");
+
+    let output = colorbuddy(&[
+        "--dry-run",
+        "--also-json",
+        "--cache-dir",
+        cache.to_str().unwrap(),
+        "-m",
+        "median-cut",
+        "-n",
+        "1",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    // The planned outputs are printed, but nothing is written
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let planned = dir.path().join("red_palette.png");
+    assert!(
+        stdout.contains(&format!("would write {} (1 colors)", planned.display())),
+        "{stdout}"
+    );
+    assert!(stdout.contains("red_palette.json"), "{stdout}");
+    assert!(!planned.exists());
+    assert!(!dir.path().join("red_palette.json").exists());
+    assert!(!cache.exists());
+}
+
+#[test]
+fn test_histogram() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("thirds.png");
+    // Half red, a third blue, and a sixth green
+    RgbImage::from_fn(12, 2, |x, _| match x {
+        0..=5 => Rgb([255, 0, 0]),
+        6..=9 => Rgb([0, 0, 255]),
+        _ => Rgb([0, 255, 0]),
+    })
+    .save(&input)
+    .unwrap();
+
+    // Test case 1: Every exact color, most common first, with its pixel count
+    let output = colorbuddy(&["--histogram", "-t", "json", input.to_str().unwrap()]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json["histogram"],
+        serde_json::json!([
+            {"hex": "#ff0000", "count": 12},
+            {"hex": "#0000ff", "count": 8},
+            {"hex": "#00ff00", "count": 4},
+        ])
+    );
+
+    // Test case 2: Truncated to the most common
+    let output = colorbuddy(&[
+        "--histogram",
+        "--histogram-top",
+        "1",
+        "-t",
+        "json",
+        input.to_str().unwrap(),
+    ]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["histogram"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_palette_in_round_trips_a_gimp_palette() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("halves.png");
+    RgbImage::from_fn(8, 4, |x, _| {
+        if x < 4 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    // Test case 1: The palette is saved as a GIMP palette named after the image
+    let output = colorbuddy(&["-t", "gpl", "-n", "2", input.to_str().unwrap()]);
+    assert!(output.status.success());
+    let palette_file = dir.path().join("halves_palette.gpl");
+    let gpl = std::fs::read_to_string(&palette_file).unwrap();
+    assert!(gpl.starts_with("GIMP Palette\nName: halves\n"));
+
+    // Test case 2: Read back in, it's used as-is rather than extracting from the image
+    let other = dir.path().join("green.png");
+    write_solid_image(&other, [0, 255, 0]);
+    let output = colorbuddy(&[
+        "--palette-in",
+        palette_file.to_str().unwrap(),
+        "-t",
+        "text",
+        "--sort",
+        "hue",
+        other.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "#ff0000\n#0000ff\n"
+    );
+
+    // Test case 3: Images can be posterized to it
+    let output = colorbuddy(&[
+        "--palette-in",
+        palette_file.to_str().unwrap(),
+        "-t",
+        "posterize",
+        other.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let posterized = image::open(dir.path().join("green_posterized.png"))
+        .unwrap()
+        .to_rgb8();
+    assert!(posterized
+        .pixels()
+        .all(|p| p.0 == [255, 0, 0] || p.0 == [0, 0, 255]));
+}
+
+#[test]
+fn test_ndjson_writes_a_line_per_image() {
+    let dir = TempDir::new().unwrap();
+    let red = dir.path().join("red.png");
+    let blue = dir.path().join("blue.png");
+    write_solid_image(&red, [255, 0, 0]);
+    write_solid_image(&blue, [0, 0, 255]);
+
+    // Test case 1: Each image's palette is a compact JSON object on a line of its own
+    let output = colorbuddy(&[
+        "--ndjson",
+        "-t",
+        "json",
+        "-n",
+        "1",
+        red.to_str().unwrap(),
+        blue.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let hexes: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            let json: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(json.is_object());
+            json["color_1"]["hex"].as_str().unwrap().to_owned()
+        })
+        .collect();
+    assert_eq!(hexes, ["#ff0000", "#0000ff"]);
+
+    // Test case 2: Only the json output type goes to stdout to be streamed
+    let output = colorbuddy(&["--ndjson", "-t", "json-file", red.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("--ndjson only applies to the json output type"));
+}
+
+#[test]
+fn test_linear_averages_in_linear_light() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("halves.png");
+    RgbImage::from_fn(8, 4, |x, _| {
+        if x < 4 {
+            Rgb([0, 0, 0])
+        } else {
+            Rgb([255, 255, 255])
+        }
+    })
+    .save(&input)
+    .unwrap();
+
+    // Test case 1: Half black and half white averages to a light gray in linear light...
+    let output = colorbuddy(&[
+        "--average",
+        "--linear",
+        "-t",
+        "text",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "#bcbcbc\n");
+
+    // Test case 2: ... rather than mid-gray
+    let output = colorbuddy(&["--average", "-t", "text", input.to_str().unwrap()]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "#808080\n");
+
+    // Test case 3: CIELAB is already computed from linear light, so isn't linearised twice
+    let output = colorbuddy(&[
+        "--linear",
+        "--color-space",
+        "lab",
+        "-t",
+        "text",
+        input.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("--linear can't be used with --color-space lab"));
+    let output = colorbuddy(&[
+        "--linear",
+        "--color-space",
+        "rgb",
+        "-n",
+        "2",
+        "-t",
+        "text",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_preview_ignores_outputs_kept_by_no_clobber() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    let output = dir.path().join("red_palette.png");
+    write_solid_image(&input, [255, 0, 0]);
+    write_solid_image(&output, [0, 0, 255]);
+
+    // The kept file is from an earlier run, so there's nothing new to preview
+    let result = Command::new(env!("CARGO_BIN_EXE_colorbuddy"))
+        .args([
+            "--no-clobber",
+            "--preview",
+            "-n",
+            "1",
+            "-t",
+            "standalone-palette",
+            input.to_str().unwrap(),
+        ])
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(
+        stderr.contains("--preview skipped as no output file was written"),
+        "{stderr}"
+    );
+    assert_eq!(
+        image::open(&output).unwrap().to_rgb8().get_pixel(0, 0).0,
+        [0, 0, 255]
+    );
+}
+
+#[test]
+fn test_create_dirs_creates_a_new_output_directory() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+    let output_dir = dir.path().join("out");
+    let output_arg = format!("{}/", output_dir.display());
+
+    // Test case 1: An --output ending in a separator is a directory, created with --create-dirs
+    let output = colorbuddy(&[
+        "-o",
+        &output_arg,
+        "--create-dirs",
+        "-t",
+        "json-file",
+        "-n",
+        "1",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert!(output_dir.join("red_palette.json").is_file());
+    assert!(!dir.path().join("red_palette.json").exists());
+
+    // Test case 2: Without --create-dirs, the missing directory is reported
+    let missing_arg = format!("{}/", dir.path().join("missing").display());
+    let output = colorbuddy(&[
+        "-o",
+        &missing_arg,
+        "-t",
+        "json-file",
+        input.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Output directory doesn't exist"));
+}
+
+#[test]
+fn test_min_luminance_above_max_luminance_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+
+    // Test case 1: A --min-luminance above --max-luminance would drop every color
+    let output = colorbuddy(&[
+        "--min-luminance",
+        "1",
+        "--max-luminance",
+        "0",
+        "-t",
+        "text",
+        input.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("--min-luminance (1) is above --max-luminance (0)"));
+
+    // Test case 2: Equal bounds are allowed
+    let output = colorbuddy(&[
+        "--min-luminance",
+        "0",
+        "--max-luminance",
+        "0",
+        "-t",
+        "text",
+        input.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_invalid_palette_height_is_a_usage_error() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("red.png");
+    write_solid_image(&input, [255, 0, 0]);
+
+    // Test case 1: A malformed height is reported like any other invalid value
+    let output = colorbuddy(&["-p", "tall", input.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("invalid value 'tall' for '--palette-height <PALETTE_HEIGHT>'"));
+    assert!(stderr.contains("Usage:"));
+
+    // Test case 2: So is a percentage above 100% without --allow-large-palette
+    let output = colorbuddy(&["-p", "150%", input.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!dir.path().join("red_palette.png").exists());
+
+    // Test case 3: A decimal comma is only accepted with --decimal-comma
+    let output = colorbuddy(&["-p", "50,5%", input.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = colorbuddy(&["--decimal-comma", "-p", "50,5%", input.to_str().unwrap()]);
+    assert!(output.status.success());
+}